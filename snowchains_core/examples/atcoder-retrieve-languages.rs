@@ -66,6 +66,9 @@ fn main() -> eyre::Result<()> {
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell::new(),
     })?;
 
@@ -71,7 +71,10 @@ fn main() -> eyre::Result<()> {
                         }
                         CredentialsVia::Env => env::var("DROPBOX_ACCESS_TOKEN")?,
                     },
+                    dropbox_refresh: None,
                 },
+                jobs: None,
+                timeout: None,
             })
         } else {
             None
@@ -81,6 +84,9 @@ fn main() -> eyre::Result<()> {
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell::new(),
     })?;
 
@@ -90,6 +90,11 @@ fn main() -> eyre::Result<()> {
             }),
         },
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
+        watch_timeout: None,
+        refuse_if_accepted: false,
         shell: Shell::new(),
     })?;
 
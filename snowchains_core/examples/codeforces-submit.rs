@@ -85,6 +85,11 @@ fn main() -> eyre::Result<()> {
             on_update: Box::new(|_| Ok(())),
         },
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
+        watch_timeout: None,
+        refuse_if_accepted: false,
         shell: Shell::new(),
     })?;
 
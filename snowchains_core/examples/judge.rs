@@ -54,6 +54,7 @@ fn main() -> eyre::Result<()> {
             env: btreemap!(),
         },
         &test_cases,
+        &Default::default(),
     )?;
 
     let stdout = BufferedStandardStream::stdout(if atty::is(atty::Stream::Stdout) {
@@ -63,7 +64,7 @@ fn main() -> eyre::Result<()> {
     });
 
     eprintln!();
-    outcome.print_pretty(stdout, None)?;
+    outcome.print_pretty(stdout, &Default::default())?;
 
     Ok(())
 }
@@ -6,10 +6,8 @@ use snowchains_core::{
 use std::{
     fmt,
     io::{self, Write as _},
-    str,
 };
 use structopt::StructOpt;
-use strum::{EnumString, EnumVariantNames};
 use termcolor::{BufferedStandardStream, Color, WriteColor as _};
 
 #[derive(StructOpt, Debug)]
@@ -18,13 +16,6 @@ struct Opt {
     timeout: Option<humantime::Duration>,
 }
 
-#[derive(EnumString, EnumVariantNames, Debug, Clone, Copy)]
-#[strum(serialize_all = "kebab-case")]
-enum CredentialsVia {
-    Prompt,
-    Env,
-}
-
 fn main() -> eyre::Result<()> {
     let Opt { timeout } = Opt::from_args();
 
@@ -33,6 +24,9 @@ fn main() -> eyre::Result<()> {
         credentials: (),
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell::new(),
     })?;
 
@@ -7,7 +7,7 @@ use snowchains_core::{
     },
 };
 use std::{
-    env, fmt,
+    env, fmt, fs,
     io::{self, Write as _},
     str,
 };
@@ -48,6 +48,7 @@ struct Opt {
 enum CredentialsVia {
     Prompt,
     Env,
+    File,
 }
 
 fn main() -> eyre::Result<()> {
@@ -79,14 +80,24 @@ fn main() -> eyre::Result<()> {
                             rpassword::read_password_from_tty(Some("yukicoder API Key: "))?
                         }
                         CredentialsVia::Env => env::var("YUKICODER_API_KEY")?,
+                        CredentialsVia::File => fs::read_to_string(
+                            env::var_os("YUKICODER_API_KEY_FILE").unwrap_or_default(),
+                        )?
+                        .trim()
+                        .to_owned(),
                     },
                 },
+                jobs: None,
+                timeout: None,
             })
         } else {
             None
         },
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell::new(),
     })?;
 
@@ -42,6 +42,7 @@ struct Opt {
 enum CredentialsVia {
     Prompt,
     Env,
+    File,
 }
 
 fn main() -> eyre::Result<()> {
@@ -57,6 +58,11 @@ fn main() -> eyre::Result<()> {
     let api_key = match credentials {
         CredentialsVia::Prompt => rpassword::read_password_from_tty(Some("yukicoder API Key: "))?,
         CredentialsVia::Env => env::var("YUKICODER_API_KEY")?,
+        CredentialsVia::File => {
+            fs::read_to_string(env::var_os("YUKICODER_API_KEY_FILE").unwrap_or_default())?
+                .trim()
+                .to_owned()
+        }
     };
 
     let outcome = Yukicoder::exec(Submit {
@@ -72,6 +78,11 @@ fn main() -> eyre::Result<()> {
         watch_submission: false,
         cookie_storage: (),
         timeout: timeout.map(Into::into),
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
+        watch_timeout: None,
+        refuse_if_accepted: false,
         shell: Shell::new(),
     })?;
 
@@ -1,21 +1,33 @@
-use crate::testsuite::{BatchTestCase, CheckerShell, ExpectedOutput};
+use crate::testsuite::{
+    BatchTestCase, ByteSize, CheckerShell, DeterministicExpectedOutput, ExpectedOutput,
+    TextEncoding,
+};
 use eyre::{bail, eyre};
 use futures_util::{select, FutureExt as _};
-use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
 use std::{
     cmp,
-    collections::BTreeMap,
+    collections::{BTreeMap, VecDeque},
     env,
     ffi::{OsStr, OsString},
+    fmt,
     future::Future,
-    io, iter,
+    io::{self, Seek as _},
+    iter, mem,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     process::{ExitStatus, Output, Stdio},
-    sync::Arc,
+    str::FromStr,
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64, AtomicUsize},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
+use strum::{EnumString, EnumVariantNames};
 use termcolor::{Color, WriteColor};
-use tokio::io::AsyncWriteExt as _;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
 use unicode_width::UnicodeWidthStr as _;
 
 #[non_exhaustive]
@@ -25,11 +37,20 @@ pub struct JudgeOutcome {
 }
 
 impl JudgeOutcome {
+    /// Prints the verdicts in detail.
     pub fn print_pretty<W: WriteColor>(
         &self,
         mut wtr: W,
-        display_limit: Option<usize>,
+        options: &PrettyOptions,
     ) -> io::Result<()> {
+        let PrettyOptions {
+            display_limit,
+            stderr_display_limit,
+            explain,
+            ref palette,
+            diff,
+        } = *options;
+
         for (i, verdict) in self.verdicts.iter().enumerate() {
             if i > 0 {
                 writeln!(wtr)?;
@@ -43,83 +64,201 @@ impl JudgeOutcome {
                 verdict.test_case_name().unwrap_or(""),
             )?;
 
-            wtr.set_color(color_spec!(Bold, Fg(verdict.summary_color())))?;
+            wtr.set_color(color_spec!(Bold, Fg(verdict.summary_color(palette))))?;
             writeln!(wtr, "{}", verdict.summary())?;
             wtr.reset()?;
 
-            let mut write_text =
-                |header: &str, text: &str, highlight_numbers: bool| -> io::Result<()> {
-                    wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
-                    writeln!(wtr, "{}", header)?;
-                    wtr.reset()?;
-
-                    if text.is_empty() {
-                        wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
-                        writeln!(wtr, "EMPTY")?;
-                        return wtr.reset();
-                    }
+            let write_text = |wtr: &mut W,
+                              header: &str,
+                              text: &str,
+                              highlight_numbers: bool,
+                              display_limit: Option<usize>|
+             -> io::Result<()> {
+                wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+                writeln!(wtr, "{}", header)?;
+                wtr.reset()?;
+
+                if text.is_empty() {
+                    wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                    writeln!(wtr, "EMPTY")?;
+                    return wtr.reset();
+                }
 
-                    if matches!(display_limit, Some(l) if l < text.len()) {
-                        wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
-                        writeln!(wtr, "{} B", text.len())?;
-                        return wtr.reset();
-                    }
+                if matches!(display_limit, Some(l) if l < text.len()) {
+                    wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                    writeln!(wtr, "{} B", text.len())?;
+                    return wtr.reset();
+                }
 
-                    for token in parse_to_tokens(text, highlight_numbers) {
-                        match token {
-                            Token::SpcLf(s) | Token::Plain(s) => wtr.write_all(s.as_ref())?,
-                            Token::Cr(n) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                (0..n).try_for_each(|_| wtr.write_all(b"\\r"))?;
-                                wtr.reset()?;
-                            }
-                            Token::Tab(n) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                (0..n).try_for_each(|_| wtr.write_all(b"\\t"))?;
-                                wtr.reset()?;
-                            }
-                            Token::OtherWhitespaceControl(s) => {
-                                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                                write!(wtr, "{}", s.escape_unicode())?;
-                                wtr.reset()?;
-                            }
-                            Token::HighlightedNumber(s) => {
-                                wtr.set_color(color_spec!(Fg(Color::Cyan)))?;
-                                wtr.write_all(s.as_ref())?;
-                                wtr.reset()?;
+                let tokens = parse_to_tokens(text, highlight_numbers);
+                let num_tokens = tokens.len();
+
+                for (i, token) in tokens.into_iter().enumerate() {
+                    match token {
+                        Token::SpcLf(s) => {
+                            // Trailing spaces are shown as `␣`.
+                            let num_segments = s.split('\n').count();
+                            for (j, segment) in s.split('\n').enumerate() {
+                                if j > 0 {
+                                    writeln!(wtr)?;
+                                }
+                                let is_trailing = j + 1 < num_segments || i + 1 == num_tokens;
+                                if is_trailing && !segment.is_empty() {
+                                    wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                                    write!(wtr, "{}", "␣".repeat(segment.len()))?;
+                                    wtr.reset()?;
+                                } else {
+                                    wtr.write_all(segment.as_ref())?;
+                                }
                             }
                         }
+                        Token::Plain(s) => wtr.write_all(s.as_ref())?,
+                        Token::Cr(n) => {
+                            wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                            (0..n).try_for_each(|_| wtr.write_all(b"\\r"))?;
+                            wtr.reset()?;
+                        }
+                        Token::Tab(n) => {
+                            wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                            (0..n).try_for_each(|_| wtr.write_all(b"\\t"))?;
+                            wtr.reset()?;
+                        }
+                        Token::OtherWhitespaceControl(s) => {
+                            wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                            write!(wtr, "{}", s.escape_unicode())?;
+                            wtr.reset()?;
+                        }
+                        Token::HighlightedNumber(s) => {
+                            wtr.set_color(color_spec!(Fg(Color::Cyan)))?;
+                            wtr.write_all(s.as_ref())?;
+                            wtr.reset()?;
+                        }
                     }
+                }
 
-                    if !text.ends_with('\n') {
-                        wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
-                        writeln!(wtr, "⏎")?;
-                        wtr.reset()?;
-                    }
+                if !text.ends_with('\n') {
+                    wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+                    writeln!(wtr, "⏎")?;
+                    wtr.reset()?;
+                }
 
-                    Ok(())
-                };
+                Ok(())
+            };
 
-            write_text("stdin:", verdict.stdin(), false)?;
-            if let Some(expected) = verdict.expected().expected_stdout() {
-                write_text("expected:", expected, verdict.expected().is_float())?;
+            if let Some((path, size)) = verdict.input_file() {
+                wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+                writeln!(wtr, "stdin:")?;
+                wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+                writeln!(wtr, "{} B ({})", size, path.display())?;
+                wtr.reset()?;
+            } else {
+                write_text(&mut wtr, "stdin:", verdict.stdin(), false, display_limit)?;
+            }
+            let diff_texts = match (verdict, verdict.expected_stdout(), verdict.stdout()) {
+                _ if diff == DiffStyle::Raw => None,
+                (
+                    Verdict::WrongAnswer {
+                        expected: ExpectedOutput::Deterministic(output),
+                        ..
+                    },
+                    Some(expected),
+                    Some(actual),
+                ) => Some((
+                    output.normalize_whitespace(expected),
+                    output.normalize_whitespace(actual),
+                )),
+                (Verdict::WrongAnswer { .. }, Some(expected), Some(actual)) => {
+                    Some((expected.into(), actual.into()))
+                }
+                _ => None,
+            };
+            if let Some((expected, actual)) = &diff_texts {
+                wtr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+                writeln!(wtr, "diff:")?;
+                wtr.reset()?;
+                write_diff(&mut wtr, expected, actual, diff, display_limit, palette)?;
+            } else if let Some(expected) = verdict.expected().expected_stdout() {
+                write_text(
+                    &mut wtr,
+                    "expected:",
+                    expected,
+                    verdict.expected().is_float(),
+                    display_limit,
+                )?;
             } else if let Some(example) = verdict.expected().example() {
-                write_text("example:", example, verdict.expected().is_float())?;
+                write_text(
+                    &mut wtr,
+                    "example:",
+                    example,
+                    verdict.expected().is_float(),
+                    display_limit,
+                )?;
             }
-            if let Some(stdout) = verdict.stdout() {
-                write_text("actual:", stdout, verdict.expected().is_float())?;
+            if let Some(stdout) = verdict.stdout().filter(|_| diff_texts.is_none()) {
+                write_text(
+                    &mut wtr,
+                    "actual:",
+                    stdout,
+                    verdict.expected().is_float(),
+                    display_limit,
+                )?;
             }
             if let Some(stderr) = verdict.stderr().filter(|s| !s.is_empty()) {
-                write_text("stderr:", stderr, verdict.expected().is_float())?;
+                write_text(
+                    &mut wtr,
+                    "stderr:",
+                    stderr,
+                    verdict.expected().is_float(),
+                    stderr_display_limit,
+                )?;
             }
             if let Some(checker_stdout) = verdict.checker_stdout().filter(|s| !s.is_empty()) {
-                write_text("checker stdout: ", checker_stdout, false)?;
+                write_text(
+                    &mut wtr,
+                    "checker stdout: ",
+                    checker_stdout,
+                    false,
+                    display_limit,
+                )?;
             }
             if let Some(checker_stderr) = verdict.checker_stderr().filter(|s| !s.is_empty()) {
-                write_text("checker stderr: ", checker_stderr, false)?;
+                write_text(
+                    &mut wtr,
+                    "checker stderr: ",
+                    checker_stderr,
+                    false,
+                    stderr_display_limit,
+                )?;
             }
             if let Some(wrong_answer_note) = verdict.wrong_answer_note() {
-                write_text("note: ", &(wrong_answer_note.to_string() + "\n"), false)?;
+                write_text(
+                    &mut wtr,
+                    "note: ",
+                    &(wrong_answer_note.to_string() + "\n"),
+                    false,
+                    None,
+                )?;
+            }
+            if let Some(score) = verdict.score() {
+                write_text(&mut wtr, "score: ", &format!("{}\n", score), false, None)?;
+            }
+            if let Some(peak_threads_note) = verdict.peak_threads_note() {
+                write_text(&mut wtr, "note: ", &(peak_threads_note + "\n"), false, None)?;
+            }
+            if let Some(peak_memory_note) = verdict.peak_memory_note() {
+                write_text(&mut wtr, "note: ", &(peak_memory_note + "\n"), false, None)?;
+            }
+            if let Some(unread_stdin_note) = verdict.unread_stdin_note() {
+                write_text(&mut wtr, "note: ", &(unread_stdin_note + "\n"), false, None)?;
+            }
+            if let Some(explanation) = verdict.explanation().filter(|_| explain) {
+                write_text(
+                    &mut wtr,
+                    "explanation: ",
+                    &(explanation + "\n"),
+                    false,
+                    None,
+                )?;
             }
         }
 
@@ -199,6 +338,97 @@ impl JudgeOutcome {
         }
     }
 
+    /// Prints the verdicts and the elapsed times, one line for each test case.
+    pub fn print_timings<W: WriteColor>(&self, mut wtr: W, palette: &Palette) -> io::Result<()> {
+        let num_width = self.verdicts.len().to_string().len();
+
+        let quoted_name_width = self
+            .verdicts
+            .iter()
+            .map(|v| format!("{:?}", v.test_case_name().unwrap_or("")).width())
+            .max()
+            .unwrap_or(0);
+
+        let elapsed_width = self
+            .verdicts
+            .iter()
+            .map(|v| display_duration(v.elapsed()).width())
+            .max()
+            .unwrap_or(0);
+
+        for (i, verdict) in self.verdicts.iter().enumerate() {
+            write!(
+                wtr,
+                "{}/{} {} {} ",
+                align_right(&(i + 1).to_string(), num_width),
+                self.verdicts.len(),
+                align_left(
+                    &format!("{:?}", verdict.test_case_name().unwrap_or("")),
+                    quoted_name_width,
+                ),
+                align_right(&display_duration(verdict.elapsed()), elapsed_width),
+            )?;
+
+            wtr.set_color(color_spec!(Bold, Fg(verdict.summary_color(palette))))?;
+            write!(wtr, "{}", verdict.kebab_case_name())?;
+            wtr.reset()?;
+            writeln!(wtr)?;
+        }
+
+        wtr.flush()
+    }
+
+    /// Serializes the verdicts for other programs.
+    ///
+    /// Sizes are in bytes of the text, and `null` where the output was not obtained. `signal` is
+    /// the signal (Unix) or the exception code (Windows) that terminated the program.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.json_report()).expect("should not fail")
+    }
+
+    /// The value [`to_json`] serializes.
+    ///
+    /// [`to_json`]: Self::to_json
+    pub fn json_report(&self) -> impl Serialize + '_ {
+        #[derive(Serialize)]
+        struct Case<'a> {
+            name: Option<&'a str>,
+            verdict: &'static str,
+            elapsed_ms: f64,
+            input_size: u64,
+            expected_size: Option<usize>,
+            actual_size: Option<usize>,
+            exit_code: Option<i32>,
+            signal: Option<String>,
+            score: Option<f64>,
+            peak_memory: Option<u64>,
+        }
+
+        #[derive(Serialize)]
+        struct Json<'a> {
+            cases: Vec<Case<'a>>,
+        }
+
+        let cases = self
+            .verdicts
+            .iter()
+            .map(|verdict| Case {
+                name: verdict.test_case_name(),
+                verdict: verdict.kebab_case_name(),
+                elapsed_ms: verdict.elapsed().as_secs_f64() * 1000.0,
+                input_size: verdict.input_size(),
+                expected_size: verdict.expected_stdout().map(str::len),
+                actual_size: verdict.stdout().map(str::len),
+                exit_code: verdict.exit_code(),
+                signal: verdict.termination().map(|t| t.name),
+                score: verdict.score(),
+                peak_memory: verdict.peak_memory().map(ByteSize::bytes),
+            })
+            .collect::<Vec<_>>();
+
+        Json { cases }
+    }
+
     pub fn error_on_fail(&self) -> eyre::Result<()> {
         let fails = self
             .verdicts
@@ -225,71 +455,150 @@ pub enum Verdict {
         test_case_name: Option<String>,
         elapsed: Duration,
         stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
         stdout: Arc<str>,
         stderr: Arc<str>,
         expected: ExpectedOutput,
+        /// Score printed by the checker. `None` if it printed none.
+        score: Option<f64>,
+        /// `(unread, total)` bytes of the stdin. `None` if not tracked.
+        unread_stdin: Option<(u64, u64)>,
+        /// Peak number of the threads in the process tree. `None` if not tracked.
+        peak_threads: Option<usize>,
+        /// Peak resident set size of the process tree. `None` if not tracked.
+        peak_memory: Option<ByteSize>,
     },
     WrongAnswer {
         test_case_name: Option<String>,
         elapsed: Duration,
         stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
         stdout: Arc<str>,
         stderr: Arc<str>,
         checker_stdout: Arc<str>,
         checker_stderr: Arc<str>,
         expected: ExpectedOutput,
         note: Option<WrongAnswerNote>,
+        /// `(unread, total)` bytes of the stdin. `None` if not tracked.
+        unread_stdin: Option<(u64, u64)>,
+        /// Peak number of the threads in the process tree. `None` if not tracked.
+        peak_threads: Option<usize>,
+        /// Peak resident set size of the process tree. `None` if not tracked.
+        peak_memory: Option<ByteSize>,
     },
     RuntimeError {
         test_case_name: Option<String>,
         elapsed: Duration,
         stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
         stdout: Arc<str>,
         stderr: Arc<str>,
         expected: ExpectedOutput,
         status: ExitStatus,
+        /// Peak number of the threads in the process tree. `None` if not tracked.
+        peak_threads: Option<usize>,
+        /// Peak resident set size of the process tree. `None` if not tracked.
+        peak_memory: Option<ByteSize>,
     },
     TimelimitExceeded {
         test_case_name: Option<String>,
         timelimit: Duration,
         stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
+        expected: ExpectedOutput,
+    },
+    ThreadLimitExceeded {
+        test_case_name: Option<String>,
+        elapsed: Duration,
+        stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
+        stdout: Arc<str>,
+        stderr: Arc<str>,
+        expected: ExpectedOutput,
+        peak_threads: usize,
+        max_threads: usize,
+    },
+    MemoryLimitExceeded {
+        test_case_name: Option<String>,
+        elapsed: Duration,
+        stdin: Arc<str>,
+        /// The file given as the stdin instead of `stdin`, and its size.
+        input_file: Option<(PathBuf, u64)>,
+        stdout: Arc<str>,
+        stderr: Arc<str>,
         expected: ExpectedOutput,
+        peak_memory: ByteSize,
+        memorylimit: ByteSize,
     },
 }
 
 impl Verdict {
-    fn test_case_name(&self) -> Option<&str> {
+    pub fn test_case_name(&self) -> Option<&str> {
         match self {
             Verdict::Accepted { test_case_name, .. }
             | Verdict::WrongAnswer { test_case_name, .. }
             | Verdict::RuntimeError { test_case_name, .. }
-            | Verdict::TimelimitExceeded { test_case_name, .. } => test_case_name.as_deref(),
+            | Verdict::TimelimitExceeded { test_case_name, .. }
+            | Verdict::ThreadLimitExceeded { test_case_name, .. }
+            | Verdict::MemoryLimitExceeded { test_case_name, .. } => test_case_name.as_deref(),
         }
     }
 
-    fn stdin(&self) -> &str {
+    pub fn stdin(&self) -> &str {
         match self {
             Verdict::Accepted { stdin, .. }
             | Verdict::WrongAnswer { stdin, .. }
             | Verdict::RuntimeError { stdin, .. }
-            | Verdict::TimelimitExceeded { stdin, .. } => stdin,
+            | Verdict::TimelimitExceeded { stdin, .. }
+            | Verdict::ThreadLimitExceeded { stdin, .. }
+            | Verdict::MemoryLimitExceeded { stdin, .. } => stdin,
+        }
+    }
+
+    /// The file given as the stdin and its size, if the test case reads the input from a file.
+    pub fn input_file(&self) -> Option<(&Path, u64)> {
+        match self {
+            Verdict::Accepted { input_file, .. }
+            | Verdict::WrongAnswer { input_file, .. }
+            | Verdict::RuntimeError { input_file, .. }
+            | Verdict::TimelimitExceeded { input_file, .. }
+            | Verdict::ThreadLimitExceeded { input_file, .. }
+            | Verdict::MemoryLimitExceeded { input_file, .. } => {
+                input_file.as_ref().map(|(path, size)| (&**path, *size))
+            }
         }
     }
 
-    fn stdout(&self) -> Option<&str> {
+    /// The size of the stdin in bytes.
+    pub fn input_size(&self) -> u64 {
+        self.input_file()
+            .map(|(_, size)| size)
+            .unwrap_or_else(|| self.stdin().len() as _)
+    }
+
+    pub fn stdout(&self) -> Option<&str> {
         match self {
             Verdict::Accepted { stdout, .. }
             | Verdict::WrongAnswer { stdout, .. }
-            | Verdict::RuntimeError { stdout, .. } => Some(stdout),
+            | Verdict::RuntimeError { stdout, .. }
+            | Verdict::ThreadLimitExceeded { stdout, .. }
+            | Verdict::MemoryLimitExceeded { stdout, .. } => Some(stdout),
             Verdict::TimelimitExceeded { .. } => None,
         }
     }
 
-    fn stderr(&self) -> Option<&str> {
+    pub fn stderr(&self) -> Option<&str> {
         match self {
             Verdict::Accepted { stderr, .. }
             | Verdict::WrongAnswer { stderr, .. }
-            | Verdict::RuntimeError { stderr, .. } => Some(stderr),
+            | Verdict::RuntimeError { stderr, .. }
+            | Verdict::ThreadLimitExceeded { stderr, .. }
+            | Verdict::MemoryLimitExceeded { stderr, .. } => Some(stderr),
             Verdict::TimelimitExceeded { .. } => None,
         }
     }
@@ -299,10 +608,16 @@ impl Verdict {
             Verdict::Accepted { expected, .. }
             | Verdict::WrongAnswer { expected, .. }
             | Verdict::RuntimeError { expected, .. }
-            | Verdict::TimelimitExceeded { expected, .. } => expected,
+            | Verdict::TimelimitExceeded { expected, .. }
+            | Verdict::ThreadLimitExceeded { expected, .. }
+            | Verdict::MemoryLimitExceeded { expected, .. } => expected,
         }
     }
 
+    pub fn expected_stdout(&self) -> Option<&str> {
+        self.expected().expected_stdout()
+    }
+
     fn checker_stdout(&self) -> Option<&str> {
         match self {
             Verdict::WrongAnswer { checker_stdout, .. } => Some(checker_stdout),
@@ -324,35 +639,269 @@ impl Verdict {
         }
     }
 
+    /// `None` if the program was killed by a signal or the status was not examined.
+    fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Accepted { .. } | Self::WrongAnswer { .. } => Some(0),
+            Self::RuntimeError { status, .. } => status.code(),
+            Self::TimelimitExceeded { .. }
+            | Self::ThreadLimitExceeded { .. }
+            | Self::MemoryLimitExceeded { .. } => None,
+        }
+    }
+
+    fn termination(&self) -> Option<Termination> {
+        match *self {
+            Self::RuntimeError { status, .. } => Termination::of(status),
+            _ => None,
+        }
+    }
+
+    pub fn score(&self) -> Option<f64> {
+        match *self {
+            Self::Accepted { score, .. } => score,
+            _ => None,
+        }
+    }
+
+    fn unread_stdin_note(&self) -> Option<String> {
+        match *self {
+            Self::Accepted { unread_stdin, .. } | Self::WrongAnswer { unread_stdin, .. } => {
+                let (unread, total) = unread_stdin.filter(|&(unread, _)| unread > 0)?;
+                Some(format!(
+                    "did not read all input ({} of {} bytes left)",
+                    unread, total,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn peak_threads_note(&self) -> Option<String> {
+        match *self {
+            Self::Accepted { peak_threads, .. }
+            | Self::WrongAnswer { peak_threads, .. }
+            | Self::RuntimeError { peak_threads, .. } => {
+                peak_threads.map(|n| format!("peak threads: {}", n))
+            }
+            Self::ThreadLimitExceeded {
+                peak_threads,
+                max_threads,
+                ..
+            } => Some(format!(
+                "peak threads: {} (limit {})",
+                peak_threads, max_threads
+            )),
+            Self::TimelimitExceeded { .. } | Self::MemoryLimitExceeded { .. } => None,
+        }
+    }
+
+    fn peak_memory(&self) -> Option<ByteSize> {
+        match *self {
+            Self::Accepted { peak_memory, .. }
+            | Self::WrongAnswer { peak_memory, .. }
+            | Self::RuntimeError { peak_memory, .. } => peak_memory,
+            Self::MemoryLimitExceeded { peak_memory, .. } => Some(peak_memory),
+            Self::TimelimitExceeded { .. } | Self::ThreadLimitExceeded { .. } => None,
+        }
+    }
+
+    fn peak_memory_note(&self) -> Option<String> {
+        match *self {
+            Self::Accepted { peak_memory, .. }
+            | Self::WrongAnswer { peak_memory, .. }
+            | Self::RuntimeError { peak_memory, .. } => {
+                peak_memory.map(|m| format!("peak memory: {}", m.to_human_readable()))
+            }
+            Self::MemoryLimitExceeded {
+                peak_memory,
+                memorylimit,
+                ..
+            } => Some(format!(
+                "peak memory: {} (limit {})",
+                peak_memory.to_human_readable(),
+                memorylimit.to_human_readable(),
+            )),
+            Self::TimelimitExceeded { .. } | Self::ThreadLimitExceeded { .. } => None,
+        }
+    }
+
+    fn explanation(&self) -> Option<String> {
+        match self {
+            Self::Accepted { .. } => None,
+            Self::TimelimitExceeded { timelimit, .. } => Some(format!(
+                "Your program did not finish within the time limit ({:.1}s).",
+                timelimit.as_secs_f64(),
+            )),
+            Self::RuntimeError { status, .. } => Some(if let Some(t) = Termination::of(*status) {
+                format!("Your program was terminated by {}.", t)
+            } else if let Some(code) = status.code() {
+                format!("Your program exited with code {} instead of 0.", code)
+            } else {
+                "Your program was terminated by a signal.".to_owned()
+            }),
+            Self::ThreadLimitExceeded {
+                peak_threads,
+                max_threads,
+                ..
+            } => Some(format!(
+                "Your program ran {} threads at once but at most {} are allowed.",
+                peak_threads, max_threads,
+            )),
+            Self::MemoryLimitExceeded {
+                peak_memory,
+                memorylimit,
+                ..
+            } => Some(format!(
+                "Your program used {} of memory but at most {} is allowed.",
+                peak_memory.to_human_readable(),
+                memorylimit.to_human_readable(),
+            )),
+            Self::WrongAnswer {
+                stdout, expected, ..
+            } => Some(match expected {
+                ExpectedOutput::Deterministic(DeterministicExpectedOutput::Subsequence {
+                    text,
+                }) => match DeterministicExpectedOutput::unmatched_line(text, stdout) {
+                    Some((i, consumed)) => format!(
+                        "Line {} of the expected output ({:?}) was not found in your output \
+                         after line {}.",
+                        i + 1,
+                        text.lines().nth(i).unwrap_or(""),
+                        consumed,
+                    ),
+                    None => "Your output contains the expected lines in order.".to_owned(),
+                },
+                ExpectedOutput::Deterministic(output) => {
+                    // Compared in the same form as in `accepts`, so that differences in ignored
+                    // whitespace are not reported.
+                    let expected =
+                        output.normalize_whitespace(output.expected_stdout().unwrap_or(""));
+                    let stdout = output.normalize_whitespace(stdout);
+                    let (expected_lines, actual_lines) = (
+                        expected.lines().collect::<Vec<_>>(),
+                        stdout.lines().collect::<Vec<_>>(),
+                    );
+
+                    if expected_lines.len() != actual_lines.len() {
+                        format!(
+                            "Your output has {} line{} but {} {} expected.",
+                            actual_lines.len(),
+                            if actual_lines.len() == 1 { "" } else { "s" },
+                            expected_lines.len(),
+                            if expected_lines.len() == 1 {
+                                "was"
+                            } else {
+                                "were"
+                            },
+                        )
+                    } else {
+                        expected_lines
+                            .iter()
+                            .zip(&actual_lines)
+                            .enumerate()
+                            .find_map(|(i, (expected, actual))| {
+                                let expected = expected.split_whitespace().collect::<Vec<_>>();
+                                let actual = actual.split_whitespace().collect::<Vec<_>>();
+                                if expected.len() != actual.len() {
+                                    return Some(format!(
+                                        "Line {} has {} token{} but {} {} expected.",
+                                        i + 1,
+                                        actual.len(),
+                                        if actual.len() == 1 { "" } else { "s" },
+                                        expected.len(),
+                                        if expected.len() == 1 { "was" } else { "were" },
+                                    ));
+                                }
+                                let (j, (expected, actual)) = expected
+                                    .iter()
+                                    .zip(&actual)
+                                    .enumerate()
+                                    .find(|(_, (e, a))| !output.tokens_eq(e, a))?;
+                                Some(format!(
+                                    "Line {} token {}: expected {:?}, got {:?}.",
+                                    i + 1,
+                                    j + 1,
+                                    expected,
+                                    actual,
+                                ))
+                            })
+                            .unwrap_or_else(|| {
+                                "Your output differs from the expected one only in whitespace \
+                                 or is not within the allowed error."
+                                    .to_owned()
+                            })
+                    }
+                }
+                ExpectedOutput::Checker { .. } => "The checker rejected your output.".to_owned(),
+                ExpectedOutput::Transform { .. } => {
+                    "Your output differs from the expected one after the transform.".to_owned()
+                }
+            }),
+        }
+    }
+
+    pub fn kebab_case_name(&self) -> &'static str {
+        match self {
+            Self::Accepted { .. } => "accepted",
+            Self::WrongAnswer { .. } => "wrong-answer",
+            Self::RuntimeError { .. } => "runtime-error",
+            Self::TimelimitExceeded { .. } => "timelimit-exceeded",
+            Self::ThreadLimitExceeded { .. } => "thread-limit-exceeded",
+            Self::MemoryLimitExceeded { .. } => "memory-limit-exceeded",
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match *self {
+            Self::Accepted { elapsed, .. }
+            | Self::WrongAnswer { elapsed, .. }
+            | Self::RuntimeError { elapsed, .. }
+            | Self::ThreadLimitExceeded { elapsed, .. }
+            | Self::MemoryLimitExceeded { elapsed, .. } => elapsed,
+            Self::TimelimitExceeded { timelimit, .. } => timelimit,
+        }
+    }
+
     fn summary(&self) -> String {
         match self {
-            Self::Accepted { elapsed, .. } => format!("Accepted ({} ms)", elapsed.as_millis()),
+            Self::Accepted { elapsed, .. } => {
+                format!("Accepted ({})", display_duration(*elapsed))
+            }
             Self::TimelimitExceeded { timelimit, .. } => {
-                format!("Timelimit Exceeded ({} ms)", timelimit.as_millis())
+                format!("Timelimit Exceeded ({})", display_duration(*timelimit))
             }
             Self::WrongAnswer { elapsed, .. } => {
-                format!("Wrong Answer ({} ms)", elapsed.as_millis())
+                format!("Wrong Answer ({})", display_duration(*elapsed))
             }
             Self::RuntimeError {
                 elapsed, status, ..
-            } => format!("Runtime Error ({} ms, {})", elapsed.as_millis(), status),
+            } => match Termination::of(*status) {
+                Some(t) => format!("Runtime Error ({}, {})", display_duration(*elapsed), t),
+                None => format!("Runtime Error ({}, {})", display_duration(*elapsed), status),
+            },
+            Self::ThreadLimitExceeded { elapsed, .. } => {
+                format!("Thread Limit Exceeded ({})", display_duration(*elapsed))
+            }
+            Self::MemoryLimitExceeded { elapsed, .. } => {
+                format!("Memory Limit Exceeded ({})", display_duration(*elapsed))
+            }
         }
     }
 
-    fn summary_color(&self) -> Color {
+    fn summary_color(&self, palette: &Palette) -> Color {
         match self {
-            Self::Accepted { .. } => Color::Green,
-            Self::TimelimitExceeded { .. } => Color::Red,
-            Self::WrongAnswer { .. } | Self::RuntimeError { .. } => Color::Yellow,
+            Self::Accepted { .. } => palette.success,
+            Self::WrongAnswer { .. } => palette.warning,
+            Self::RuntimeError { .. }
+            | Self::TimelimitExceeded { .. }
+            | Self::ThreadLimitExceeded { .. }
+            | Self::MemoryLimitExceeded { .. } => palette.failure,
         }
     }
 
-    fn summary_style(&self) -> &'static str {
-        match self {
-            Self::Accepted { .. } => ".bold.green",
-            Self::TimelimitExceeded { .. } => ".bold.red",
-            Self::WrongAnswer { .. } | Self::RuntimeError { .. } => ".bold.yellow",
-        }
+    fn summary_style(&self, palette: &Palette) -> String {
+        format!(".bold{}", console_style(self.summary_color(palette)))
     }
 }
 
@@ -364,6 +913,86 @@ pub enum WrongAnswerNote {
     WordsMatched,
 }
 
+/// An event of the JSON-lines stream written by [`JudgeEventWriter`].
+///
+/// Each event is serialized as one JSON object on its own line, tagged with `"event"`.
+/// This schema is a stable contract: fields and variants may be added, but existing ones are
+/// not renamed or removed.
+///
+/// | `event`         | Fields                                                             |
+/// | --------------- | ------------------------------------------------------------------ |
+/// | `compile-start` | `kind` (`"transpile"` or `"compile"`), `command`                   |
+/// | `compile-done`  | `kind`, `up_to_date`, `elapsed_ms`                                 |
+/// | `case-start`    | `index` (0-based), `total`, `name` (`null` if unnamed)             |
+/// | `case-done`     | `index`, `total`, `name`, `verdict`, `elapsed_ms`                  |
+/// | `summary`       | `total`, `passed`, `failed`                                        |
+///
+/// `verdict` is one of `"accepted"`, `"wrong-answer"`, `"runtime-error"`, `"timelimit-exceeded"`,
+/// `"thread-limit-exceeded"`, or `"memory-limit-exceeded"`. `elapsed_ms` is a floating-point
+/// number of milliseconds. A `compile-done` with `up_to_date: true` is not preceded by a
+/// `compile-start`.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum JudgeEvent {
+    CompileStart {
+        kind: BuildKind,
+        command: String,
+    },
+    CompileDone {
+        kind: BuildKind,
+        up_to_date: bool,
+        elapsed_ms: f64,
+    },
+    CaseStart {
+        index: usize,
+        total: usize,
+        name: Option<String>,
+    },
+    CaseDone {
+        index: usize,
+        total: usize,
+        name: Option<String>,
+        verdict: &'static str,
+        elapsed_ms: f64,
+    },
+    Summary {
+        total: usize,
+        passed: usize,
+        failed: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildKind {
+    Transpile,
+    Compile,
+}
+
+/// Writes [`JudgeEvent`]s as JSON lines, flushing after each event.
+#[derive(Clone)]
+pub struct JudgeEventWriter(Arc<Mutex<dyn io::Write + Send>>);
+
+impl JudgeEventWriter {
+    pub fn new(wtr: impl io::Write + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(wtr)))
+    }
+
+    pub fn emit(&self, event: &JudgeEvent) -> io::Result<()> {
+        let mut wtr = self.0.lock().unwrap();
+        serde_json::to_writer(&mut *wtr, event)?;
+        writeln!(wtr)?;
+        wtr.flush()
+    }
+}
+
+impl fmt::Debug for JudgeEventWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JudgeEventWriter").finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CommandExpression {
     pub program: OsString,
@@ -373,17 +1002,21 @@ pub struct CommandExpression {
 }
 
 impl CommandExpression {
+    /// On Unix, the process is made the leader of a new process group so that [`ProcessGroup`]
+    /// can kill the processes it spawns.
     async fn build(
         &self,
         stdin: Option<&Path>,
         stdout: &Path,
         stderr: &Path,
-    ) -> io::Result<tokio::process::Command> {
-        let mut cmd = tokio::process::Command::new(&self.program);
-        let stdin = if let Some(stdin) = stdin {
-            tokio::fs::File::open(stdin).await?.into_std().await.into()
+    ) -> io::Result<(tokio::process::Command, Option<std::fs::File>)> {
+        let mut cmd = std::process::Command::new(&self.program);
+        let (stdin, stdin_file) = if let Some(stdin) = stdin {
+            let stdin = tokio::fs::File::open(stdin).await?.into_std().await;
+            let stdin_file = stdin.try_clone()?;
+            (stdin.into(), Some(stdin_file))
         } else {
-            Stdio::piped()
+            (Stdio::piped(), None)
         };
         let stdout = tokio::fs::File::create(stdout).await?.into_std().await;
         let stderr = tokio::fs::File::create(stderr).await?.into_std().await;
@@ -393,16 +1026,368 @@ impl CommandExpression {
             .stdin(stdin)
             .stdout(stdout)
             .stderr(stderr);
-        Ok(cmd)
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+        Ok((cmd.into(), stdin_file))
+    }
+}
+
+/// The processes spawned by a test case.
+///
+/// On Unix, this is the process group led by the child. On Windows, this is a job object the child
+/// is assigned to, and the processes left in it are killed when this is dropped.
+struct ProcessGroup {
+    #[cfg(unix)]
+    pgid: Option<u32>,
+    /// `HANDLE`, which is not `Send`.
+    #[cfg(windows)]
+    job: Option<usize>,
+}
+
+impl ProcessGroup {
+    #[cfg(unix)]
+    fn new(child: &tokio::process::Child) -> Self {
+        Self { pgid: child.id() }
+    }
+
+    #[cfg(windows)]
+    fn new(child: &tokio::process::Child) -> Self {
+        use std::{mem, ptr};
+        use winapi::um::{
+            handleapi::CloseHandle,
+            jobapi2::{AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject},
+            winnt::{
+                JobObjectExtendedLimitInformation, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+                JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+            },
+        };
+
+        // The processes the child spawns before this are not in the job.
+        let job = child.raw_handle().and_then(|process| unsafe {
+            let job = CreateJobObjectW(ptr::null_mut(), ptr::null());
+            if job.is_null() {
+                return None;
+            }
+            let mut info = mem::zeroed::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let assigned = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as _,
+                mem::size_of_val(&info) as _,
+            ) != 0
+                && AssignProcessToJobObject(job, process as _) != 0;
+            if !assigned {
+                CloseHandle(job);
+                return None;
+            }
+            Some(job as usize)
+        });
+
+        Self { job }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn new(_: &tokio::process::Child) -> Self {
+        Self {}
+    }
+
+    /// Kills the processes except the child itself, which is killed through [`tokio::process::Child`].
+    fn kill(&self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid {
+            unsafe {
+                libc::killpg(pgid as _, libc::SIGKILL);
+            }
+        }
+
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            unsafe {
+                winapi::um::jobapi2::TerminateJobObject(job as _, 1);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(job) = self.job {
+            unsafe {
+                winapi::um::handleapi::CloseHandle(job as _);
+            }
+        }
+    }
+}
+
+/// How [`JudgeOutcome::print_pretty`] shows the expected and the actual outputs of wrong
+/// answers.
+#[derive(EnumString, EnumVariantNames, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DiffStyle {
+    /// Both of them as they are.
+    #[default]
+    Raw,
+    /// A line diff with `-` for the expected output and `+` for the actual one.
+    Unified,
+    /// A line diff with the expected output on the left and the actual one on the right.
+    SideBySide,
+}
+
+/// Options of [`JudgeOutcome::print_pretty`].
+#[derive(Debug, Default, Clone)]
+pub struct PrettyOptions {
+    /// Texts longer than this in bytes are replaced with their sizes.
+    pub display_limit: Option<usize>,
+    /// Overrides `display_limit` for the stderrs of the program and the checker.
+    pub stderr_display_limit: Option<usize>,
+    /// Describes why each test case failed.
+    pub explain: bool,
+    /// Colors of the verdicts.
+    pub palette: Palette,
+    /// How the expected and the actual outputs of wrong answers are shown.
+    pub diff: DiffStyle,
+}
+
+/// Optional behaviors of [`judge`].
+#[derive(Default, Clone)]
+pub struct JudgeOptions {
+    /// Receives the progress.
+    pub events: Option<JudgeEventWriter>,
+    /// Records how much of the stdin was left unread.
+    pub track_stdin: bool,
+    /// Fails the test cases that run more threads than this at once. Only supported on Linux.
+    pub max_threads: Option<usize>,
+    /// Records the peak memory usage even for the test cases without `memorylimit`. Only
+    /// supported on Linux.
+    pub measure_memory: bool,
+    /// Colors of the verdicts on the progress bars.
+    pub palette: Palette,
+    /// How many test cases are run at once.
+    pub jobs: Jobs,
+    /// Receives the stdout and the stderr of the program as they are written. Outputs of
+    /// multiple test cases would be mixed up.
+    pub live_output: Option<Arc<Mutex<dyn io::Write + Send>>>,
+    /// Ignores the timelimits of the test cases. The program is then only stopped by Ctrl-C.
+    pub no_timelimit: bool,
+}
+
+/// Colors of the verdicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// For `Accepted`.
+    pub success: Color,
+    /// For `Wrong Answer`.
+    pub warning: Color,
+    /// For `Runtime Error`, `Timelimit Exceeded`, `Thread Limit Exceeded`, and `Memory Limit
+    /// Exceeded`.
+    pub failure: Color,
+}
+
+impl Palette {
+    /// Blue, yellow, and vermilion, which can be told apart with red-green color blindness.
+    pub fn colorblind() -> Self {
+        Self {
+            success: Color::Ansi256(33),
+            warning: Color::Ansi256(220),
+            failure: Color::Ansi256(202),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            success: Color::Green,
+            warning: Color::Yellow,
+            failure: Color::Red,
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = eyre::Report;
+
+    /// Parses `default`, `colorblind`, or comma-separated `<role>=<color>` overrides optionally
+    /// following one of them (e.g. `colorblind,failure=magenta`).
+    ///
+    /// Colors are names such as `green` or ANSI 256-color codes.
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let mut palette = Self::default();
+
+        for (i, item) in s.split(',').map(str::trim).enumerate() {
+            if let Some((role, color)) = item.split_once('=') {
+                let color = match color.trim().parse::<Color>() {
+                    Ok(Color::Rgb(..)) | Err(_) => bail!("Invalid color: {:?}", color),
+                    Ok(color) => color,
+                };
+                match role.trim() {
+                    "success" => palette.success = color,
+                    "warning" => palette.warning = color,
+                    "failure" => palette.failure = color,
+                    role => bail!(
+                        "Unknown role {:?}. Expected one of [success, warning, failure]",
+                        role,
+                    ),
+                }
+            } else if i == 0 {
+                palette = match item {
+                    "default" => Self::default(),
+                    "colorblind" => Self::colorblind(),
+                    name => bail!(
+                        "Unknown palette {:?}. Expected one of [default, colorblind]",
+                        name,
+                    ),
+                };
+            } else {
+                bail!("Expected `<role>=<color>`: {:?}", item);
+            }
+        }
+
+        Ok(palette)
+    }
+}
+
+/// How many test cases are run at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jobs {
+    /// The number of the CPUs.
+    #[default]
+    Auto,
+    Count(NonZeroUsize),
+}
+
+impl Jobs {
+    pub fn get(self) -> usize {
+        match self {
+            Self::Auto => num_cpus::get(),
+            Self::Count(n) => n.get(),
+        }
+    }
+}
+
+impl FromStr for Jobs {
+    type Err = eyre::Report;
+
+    /// Parses `auto` or a positive integer.
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        if s == "auto" {
+            return Ok(Self::Auto);
+        }
+        s.parse()
+            .map(Self::Count)
+            .map_err(|_| eyre!("Expected `auto` or a positive integer: {:?}", s))
+    }
+}
+
+/// Converts `color` for the templates of `indicatif`.
+fn console_style(color: Color) -> String {
+    match color {
+        Color::Black => ".black".to_owned(),
+        Color::Red => ".red".to_owned(),
+        Color::Green => ".green".to_owned(),
+        Color::Yellow => ".yellow".to_owned(),
+        Color::Blue => ".blue".to_owned(),
+        Color::Magenta => ".magenta".to_owned(),
+        Color::Cyan => ".cyan".to_owned(),
+        Color::White => ".white".to_owned(),
+        Color::Ansi256(n) => format!(".{}", n),
+        _ => "".to_owned(),
+    }
+}
+
+/// Prints the name, the sizes of the input and the expected output, and the timelimit of each
+/// test case, without running anything.
+pub fn print_test_cases<W: WriteColor>(mut wtr: W, test_cases: &[BatchTestCase]) -> io::Result<()> {
+    let num_width = test_cases.len().to_string().len();
+
+    let rows = test_cases
+        .iter()
+        .map(|test_case| {
+            let name = format!("{:?}", test_case.name.as_deref().unwrap_or(""));
+            let input = match &test_case.input_file {
+                Some(path) => path.metadata()?.len(),
+                None => test_case.input.len() as _,
+            };
+            let input = ByteSize::from_bytes(input).to_human_readable();
+            let expected = match &test_case.output {
+                ExpectedOutput::Checker { .. } => "(checker)".to_owned(),
+                output => output
+                    .expected_stdout()
+                    .map(|s| ByteSize::from_bytes(s.len() as _).to_human_readable())
+                    .unwrap_or_else(|| "-".to_owned()),
+            };
+            let timelimit = test_case
+                .timelimit
+                .map(display_duration)
+                .unwrap_or_else(|| "-".to_owned());
+            let memorylimit = test_case
+                .memorylimit
+                .map(ByteSize::to_human_readable)
+                .unwrap_or_else(|| "-".to_owned());
+            Ok((name, input, expected, timelimit, memorylimit))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let width = |f: fn(&(String, String, String, String, String)) -> &str| {
+        rows.iter().map(|r| f(r).width()).max().unwrap_or(0)
+    };
+    let name_width = width(|(s, _, _, _, _)| s);
+    let input_width = width(|(_, s, _, _, _)| s);
+    let expected_width = width(|(_, _, s, _, _)| s);
+    let timelimit_width = width(|(_, _, _, s, _)| s);
+
+    for (i, (name, input, expected, timelimit, memorylimit)) in rows.iter().enumerate() {
+        write!(
+            wtr,
+            "{}/{} {} ",
+            align_right(&(i + 1).to_string(), num_width),
+            test_cases.len(),
+            align_left(name, name_width),
+        )?;
+
+        for (label, value, width) in &[
+            ("in:", input, input_width),
+            ("out:", expected, expected_width),
+            ("timelimit:", timelimit, timelimit_width),
+            ("memorylimit:", memorylimit, 0),
+        ] {
+            wtr.set_color(color_spec!(Fg(Color::Magenta)))?;
+            write!(wtr, "{}", label)?;
+            wtr.reset()?;
+            write!(wtr, " {}", align_right(value, *width))?;
+            if *width > 0 {
+                write!(wtr, " ")?;
+            }
+        }
+        writeln!(wtr)?;
     }
+
+    wtr.flush()
 }
 
+/// Inputs of at least this size are copied to programs from files.
+const LARGE_STDIN_SIZE: u64 = 10 * 1024;
+
 pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
     draw_target: ProgressDrawTarget,
     ctrl_c: fn() -> C,
     cmd: &CommandExpression,
     test_cases: &[BatchTestCase],
+    options: &JudgeOptions,
 ) -> eyre::Result<JudgeOutcome> {
+    let JudgeOptions {
+        events,
+        track_stdin,
+        max_threads,
+        measure_memory,
+        palette,
+        jobs,
+        live_output,
+        no_timelimit,
+    } = options.clone();
+
     let cmd = Arc::new(cmd.clone());
     let num_test_cases = test_cases.len();
 
@@ -455,6 +1440,23 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
         targets.push((test_case.clone(), pb));
     }
 
+    let jobs = jobs.get();
+
+    // Not drawn to a non-TTY, since `draw_target` is hidden for it.
+    let eta = (num_test_cases > 1).then(|| {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.set_style(progress_style("{msg:bold}"));
+        pb.set_message(&format!("0/{} finished", num_test_cases));
+        Arc::new(Mutex::new(Eta {
+            pb,
+            total: num_test_cases,
+            jobs,
+            finished: 0,
+            failed: None,
+            recent: VecDeque::new(),
+        }))
+    });
+
     let rt = tokio::runtime::Builder::new_multi_thread()
         .enable_io()
         .enable_time()
@@ -478,8 +1480,8 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
             ctrl_c_tx.send(err_msg).unwrap();
         });
 
-        let (job_start_tx, mut job_start_rx) = tokio::sync::mpsc::channel(num_cpus::get());
-        for _ in 0..num_cpus::get() {
+        let (job_start_tx, mut job_start_rx) = tokio::sync::mpsc::channel(jobs);
+        for _ in 0..jobs {
             job_start_tx.send(()).await?;
         }
 
@@ -498,20 +1500,50 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
             let job_start_tx = job_start_tx.clone();
             let mut ctrl_c_rx = ctrl_c_rxs.pop().expect("should have enough length");
             let pb_clone = pb.clone();
+            let events = events.clone();
+            let live_output = live_output.clone();
+            let eta = eta.clone();
+
+            if let Some(events) = &events {
+                let event = JudgeEvent::CaseStart {
+                    index: i,
+                    total: num_targets,
+                    name: test_case.name.clone(),
+                };
+                tokio::task::block_in_place(|| events.emit(&event))?;
+            }
 
             results.push(tokio::task::spawn(async move {
+                let case_started = Instant::now();
+
                 let result = tokio::task::spawn(async move {
-                    tokio::fs::write(&stdin_path, test_case.input.as_ref()).await?;
+                    let encoding = test_case.encoding;
+                    let encoded_stdin = encoding.encode(&test_case.input)?;
+
+                    let (stdin_path, stdin_size) = if let Some(input_file) = &test_case.input_file {
+                        let size = tokio::fs::metadata(input_file).await?.len();
+                        (input_file.clone(), size)
+                    } else {
+                        tokio::fs::write(&stdin_path, &encoded_stdin).await?;
+                        (stdin_path, encoded_stdin.len() as u64)
+                    };
+
+                    // Large inputs are copied from the file in chunks, concurrently with the
+                    // program, so that they are never held in memory as a whole.
+                    let stream_stdin =
+                        test_case.input_file.is_some() || stdin_size >= LARGE_STDIN_SIZE;
 
                     let test_case_name = test_case.name.clone();
-                    let timelimit = test_case.timelimit;
+                    let timelimit = test_case.timelimit.filter(|_| !no_timelimit);
+                    let memorylimit = test_case.memorylimit;
                     let stdin = test_case.input.clone();
+                    let input_file = test_case.input_file.clone().map(|path| (path, stdin_size));
                     let expected = test_case.output.clone();
 
                     let cwd = &cmd.cwd;
-                    let cmd = cmd
+                    let (cmd, stdin_file) = cmd
                         .build(
-                            (stdin.len() >= 10 * 1024).then(|| &*stdin_path),
+                            track_stdin.then(|| &*stdin_path),
                             &actual_stdout_path,
                             &stderr_path,
                         )
@@ -520,9 +1552,34 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                     let started = Instant::now();
 
                     let mut child = { cmd }.spawn()?;
+                    let process_group = ProcessGroup::new(&child);
+
+                    let peak_threads = Arc::new(AtomicUsize::new(0));
+                    let peak_memory = Arc::new(AtomicU64::new(0));
+                    if let Some(pid) = child.id().filter(|_| {
+                        max_threads.is_some() || memorylimit.is_some() || measure_memory
+                    }) {
+                        tokio::task::spawn(watch_process_tree(
+                            pid,
+                            peak_threads.clone(),
+                            peak_memory.clone(),
+                        ));
+                    }
+
+                    let mut live_output = live_output.map(|wtr| {
+                        Tail::spawn([actual_stdout_path.clone(), stderr_path.clone()], wtr)
+                    });
 
                     if let Some(mut child_stdin) = child.stdin.take() {
-                        child_stdin.write_all((*stdin).as_ref()).await?;
+                        if stream_stdin {
+                            let mut file = tokio::fs::File::open(&stdin_path).await?;
+                            tokio::task::spawn(async move {
+                                // The program may exit without reading all of it.
+                                let _ = tokio::io::copy(&mut file, &mut child_stdin).await;
+                            });
+                        } else {
+                            child_stdin.write_all(&encoded_stdin).await?;
+                        }
                     }
 
                     macro_rules! with_ctrl_c {
@@ -530,7 +1587,11 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                             select! {
                                 __output = $future => __output,
                                 err_msg = ctrl_c_rx.recv().fuse() => {
-                                    let _ = child.kill();
+                                    process_group.kill();
+                                    let _ = child.start_kill();
+                                    if let Some(tail) = live_output.take() {
+                                        tail.finish().await?;
+                                    }
                                     bail!("{}", err_msg?);
                                 },
                             }
@@ -545,17 +1606,22 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                         {
                             status?
                         } else {
+                            process_group.kill();
                             let _ = child.kill().await;
+                            if let Some(tail) = live_output.take() {
+                                tail.finish().await?;
+                            }
                             let verdict = Verdict::TimelimitExceeded {
                                 test_case_name,
                                 timelimit,
                                 stdin,
+                                input_file,
                                 expected,
                             };
                             tokio::task::block_in_place(|| {
-                                pb_clone.set_style(progress_style(&format!(
+                                pb_clone.set_style(progress_style(format!(
                                     "{{prefix}}{{msg:{}}}",
-                                    verdict.summary_style(),
+                                    verdict.summary_style(&palette),
                                 )));
                                 pb_clone.finish_with_message(&verdict.summary());
                             });
@@ -567,57 +1633,122 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
 
                     let elapsed = Instant::now() - started;
 
-                    let stdout = utf8(tokio::fs::read(&actual_stdout_path).await?)?;
-                    let stderr = utf8(tokio::fs::read(&stderr_path).await?)?;
+                    if let Some(tail) = live_output.take() {
+                        tail.finish().await?;
+                    }
+
+                    // The child shares the file offset with `stdin_file`.
+                    let unread_stdin = stdin_file
+                        .filter(|_| track_stdin)
+                        .map(|mut f| f.stream_position())
+                        .transpose()?
+                        .map(|pos| (stdin_size.saturating_sub(pos), stdin_size));
+
+                    // The process has been sampled at least once if it is still alive.
+                    let peak_threads = max_threads
+                        .filter(|_| cfg!(target_os = "linux"))
+                        .map(|_| cmp::max(peak_threads.load(atomic::Ordering::SeqCst), 1));
+                    let peak_memory = Some(peak_memory.load(atomic::Ordering::SeqCst))
+                        .filter(|_| memorylimit.is_some() || measure_memory)
+                        // The process may exit before it has been sampled.
+                        .filter(|&m| m > 0)
+                        .map(ByteSize::from_bytes);
+
+                    let stdout = decode(tokio::fs::read(&actual_stdout_path).await?, encoding)?;
+                    let stderr = decode(tokio::fs::read(&stderr_path).await?, encoding)?;
 
                     if matches!(timelimit, Some(t) if t < elapsed) {
                         Ok(Verdict::TimelimitExceeded {
                             test_case_name,
                             timelimit: timelimit.unwrap(),
                             stdin,
+                            input_file,
                             expected,
                         })
-                    } else if !status.success() {
-                        Ok(Verdict::RuntimeError {
+                    } else if let Some((peak_threads, max_threads)) =
+                        peak_threads.zip(max_threads).filter(|(p, m)| p > m)
+                    {
+                        Ok(Verdict::ThreadLimitExceeded {
                             test_case_name,
                             elapsed,
                             stdin,
+                            input_file,
                             stdout,
                             stderr,
                             expected,
-                            status,
+                            peak_threads,
+                            max_threads,
                         })
-                    } else if let Err((checker_stdout, checker_stderr, note)) = check(
-                        &test_case.output,
-                        &stdout,
-                        cwd,
-                        &stdin_path,
-                        &actual_stdout_path,
-                        &expected_stdout_path,
-                        &bash_exe,
-                    )
-                    .await?
+                    } else if let Some((peak_memory, memorylimit)) =
+                        peak_memory.zip(memorylimit).filter(|(p, m)| p > m)
                     {
-                        Ok(Verdict::WrongAnswer {
+                        Ok(Verdict::MemoryLimitExceeded {
                             test_case_name,
                             elapsed,
                             stdin,
+                            input_file,
                             stdout,
                             stderr,
-                            checker_stdout,
-                            checker_stderr,
                             expected,
-                            note,
+                            peak_memory,
+                            memorylimit,
                         })
-                    } else {
-                        Ok(Verdict::Accepted {
+                    } else if !status.success() {
+                        Ok(Verdict::RuntimeError {
                             test_case_name,
                             elapsed,
                             stdin,
+                            input_file,
                             stdout,
                             stderr,
                             expected,
+                            status,
+                            peak_threads,
+                            peak_memory,
                         })
+                    } else {
+                        match check(
+                            &test_case,
+                            &stdout,
+                            cwd,
+                            &stdin_path,
+                            &actual_stdout_path,
+                            &expected_stdout_path,
+                            &bash_exe,
+                        )
+                        .await?
+                        {
+                            Ok(score) => Ok(Verdict::Accepted {
+                                test_case_name,
+                                elapsed,
+                                stdin,
+                                input_file,
+                                stdout,
+                                stderr,
+                                expected,
+                                score,
+                                unread_stdin,
+                                peak_threads,
+                                peak_memory,
+                            }),
+                            Err((checker_stdout, checker_stderr, note)) => {
+                                Ok(Verdict::WrongAnswer {
+                                    test_case_name,
+                                    elapsed,
+                                    stdin,
+                                    input_file,
+                                    stdout,
+                                    stderr,
+                                    checker_stdout,
+                                    checker_stderr,
+                                    expected,
+                                    note,
+                                    unread_stdin,
+                                    peak_threads,
+                                    peak_memory,
+                                })
+                            }
+                        }
                     }
                 })
                 .await
@@ -626,12 +1757,23 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                 match &result {
                     Ok(verdict) => {
                         tokio::task::block_in_place(|| {
-                            pb.set_style(progress_style(&format!(
+                            pb.set_style(progress_style(format!(
                                 "{{prefix}}{{msg:{}}}",
-                                verdict.summary_style(),
+                                verdict.summary_style(&palette),
                             )));
                             pb.finish_with_message(&verdict.summary());
-                        });
+
+                            if let Some(events) = &events {
+                                events.emit(&JudgeEvent::CaseDone {
+                                    index: i,
+                                    total: num_targets,
+                                    name: verdict.test_case_name().map(ToOwned::to_owned),
+                                    verdict: verdict.kebab_case_name(),
+                                    elapsed_ms: verdict.elapsed().as_secs_f64() * 1000.0,
+                                })?;
+                            }
+                            Ok::<_, io::Error>(())
+                        })?;
                     }
                     Err(err) => {
                         tokio::task::block_in_place(|| {
@@ -641,6 +1783,19 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
                     }
                 }
 
+                if let Some(eta) = &eta {
+                    let failed = match &result {
+                        Ok(Verdict::Accepted { .. }) => None,
+                        Ok(verdict) => Some(verdict.summary_style(&palette)),
+                        Err(_) => Some(".bold".to_owned()),
+                    };
+                    tokio::task::block_in_place(|| {
+                        eta.lock()
+                            .unwrap()
+                            .on_finish(case_started.elapsed(), failed)
+                    });
+                }
+
                 job_start_tx.send(()).await?;
                 let verdict = result?;
                 Ok::<_, eyre::Error>((i, verdict))
@@ -652,7 +1807,22 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
             let (i, element) = result.await??;
             verdicts[i] = Some(element);
         }
-        let verdicts = verdicts.into_iter().map(Option::unwrap).collect();
+        let verdicts = verdicts.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+        if let Some(events) = &events {
+            let passed = verdicts
+                .iter()
+                .filter(|v| matches!(v, Verdict::Accepted { .. }))
+                .count();
+
+            tokio::task::block_in_place(|| {
+                events.emit(&JudgeEvent::Summary {
+                    total: num_targets,
+                    passed,
+                    failed: num_targets - passed,
+                })
+            })?;
+        }
 
         Ok::<_, eyre::Error>(JudgeOutcome { verdicts })
     });
@@ -667,29 +1837,467 @@ pub fn judge<C: 'static + Future<Output = tokio::io::Result<()>> + Send>(
         ProgressStyle::default_spinner().template(template.as_ref())
     }
 
-    fn align_left(s: &str, n: usize) -> String {
-        let spaces = n.saturating_sub(s.width());
-        s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
+    /// The last line of the progress, which estimates the remaining time from the recent test
+    /// cases.
+    struct Eta {
+        pb: ProgressBar,
+        total: usize,
+        jobs: usize,
+        finished: usize,
+        /// The number of the failed test cases and the style of the last one.
+        failed: Option<(usize, String)>,
+        recent: VecDeque<Duration>,
+    }
+
+    impl Eta {
+        const WINDOW: usize = 16;
+
+        fn on_finish(&mut self, elapsed: Duration, failed: Option<String>) {
+            self.finished += 1;
+
+            if let Some(style) = failed {
+                let num_failed = self.failed.as_ref().map_or(0, |(n, _)| *n);
+                self.failed = Some((num_failed + 1, style));
+            }
+
+            if self.recent.len() == Self::WINDOW {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(elapsed);
+
+            let mut msg = format!("{}/{} finished", self.finished, self.total);
+            if let Some((num_failed, style)) = &self.failed {
+                self.pb
+                    .set_style(progress_style(format!("{{msg:{}}}", style)));
+                msg += &format!(", {} failed", num_failed);
+            }
+
+            if self.finished == self.total {
+                self.pb.finish_with_message(&msg);
+                return;
+            }
+
+            let average = self.recent.iter().sum::<Duration>() / self.recent.len() as u32;
+            let remaining = self.total - self.finished;
+            // Running ones are counted as a whole, which is fine for an estimate.
+            let rounds = remaining.div_ceil(self.jobs);
+            msg += &format!(" (ETA {})", HumanDuration(average * rounds as u32));
+            self.pb.set_message(&msg);
+        }
+    }
+}
+
+/// How a program was terminated other than by exiting normally.
+#[derive(Debug, PartialEq)]
+struct Termination {
+    /// `"signal"` or `"exception"`.
+    kind: &'static str,
+    /// e.g. `SIGSEGV` or `0xC0000005`.
+    name: String,
+    description: Option<&'static str>,
+}
+
+impl Termination {
+    /// The signal that terminated the program on Unix, or the exception code it exited with on
+    /// Windows.
+    #[cfg(unix)]
+    fn of(status: ExitStatus) -> Option<Self> {
+        use std::os::unix::process::ExitStatusExt as _;
+
+        let signal = status.signal()?;
+
+        let (name, description) = match signal {
+            libc::SIGSEGV => ("SIGSEGV", "segmentation fault"),
+            libc::SIGABRT => ("SIGABRT", "aborted"),
+            libc::SIGFPE => ("SIGFPE", "arithmetic error"),
+            libc::SIGBUS => ("SIGBUS", "bus error"),
+            libc::SIGILL => ("SIGILL", "illegal instruction"),
+            libc::SIGTRAP => ("SIGTRAP", "trace trap"),
+            libc::SIGSYS => ("SIGSYS", "bad system call"),
+            libc::SIGKILL => ("SIGKILL", "killed"),
+            libc::SIGTERM => ("SIGTERM", "terminated"),
+            libc::SIGINT => ("SIGINT", "interrupted"),
+            libc::SIGHUP => ("SIGHUP", "hangup"),
+            libc::SIGPIPE => ("SIGPIPE", "broken pipe"),
+            libc::SIGXCPU => ("SIGXCPU", "CPU time limit exceeded"),
+            libc::SIGXFSZ => ("SIGXFSZ", "file size limit exceeded"),
+            signal => {
+                return Some(Self {
+                    kind: "signal",
+                    name: signal.to_string(),
+                    description: None,
+                })
+            }
+        };
+
+        Some(Self {
+            kind: "signal",
+            name: name.to_owned(),
+            description: Some(description),
+        })
+    }
+
+    #[cfg(windows)]
+    fn of(status: ExitStatus) -> Option<Self> {
+        // `NTSTATUS`es with the severity of errors.
+        let code = status.code()? as u32;
+        if code & 0xC000_0000 != 0xC000_0000 {
+            return None;
+        }
+
+        let description = match code {
+            0xC000_0005 => Some("access violation"),
+            0xC000_001D => Some("illegal instruction"),
+            0xC000_0017 | 0xC000_009A => Some("out of memory"),
+            0xC000_008E => Some("floating-point division by zero"),
+            0xC000_0094 => Some("integer division by zero"),
+            0xC000_0095 => Some("integer overflow"),
+            0xC000_00FD => Some("stack overflow"),
+            0xC000_013A => Some("interrupted"),
+            0xC000_0409 => Some("stack buffer overrun"),
+            _ => None,
+        };
+
+        Some(Self {
+            kind: "exception",
+            name: format!("0x{:08X}", code),
+            description,
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn of(_: ExitStatus) -> Option<Self> {
+        None
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.name)?;
+        if let Some(description) = self.description {
+            write!(f, " ({})", description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats `d` in µs, ms, or s so that short durations are not shown as "0 ms".
+fn display_duration(d: Duration) -> String {
+    if d < Duration::from_millis(1) {
+        format!("{} µs", d.as_micros())
+    } else if d < Duration::from_millis(10) {
+        format!("{:.3} ms", d.as_secs_f64() * 1000.0)
+    } else if d < Duration::from_secs(10) {
+        format!("{} ms", d.as_millis())
+    } else {
+        format!("{:.2} s", d.as_secs_f64())
+    }
+}
+
+/// Writes a line diff between `expected` and `actual`, underlining the first differing
+/// character.
+///
+/// When the lines exceed `display_limit` bytes, the ones far from the first difference are
+/// omitted.
+fn write_diff(
+    mut wtr: impl WriteColor,
+    expected: &str,
+    actual: &str,
+    style: DiffStyle,
+    display_limit: Option<usize>,
+    palette: &Palette,
+) -> io::Result<()> {
+    use itertools::{EitherOrBoth, Itertools as _};
+    use similar::{DiffTag, TextDiff};
+
+    #[derive(Default)]
+    struct Row<'a> {
+        expected: Option<&'a str>,
+        actual: Option<&'a str>,
+        // Character indices of the first difference.
+        expected_mark: Option<usize>,
+        actual_mark: Option<usize>,
+    }
+
+    impl Row<'_> {
+        fn is_changed(&self) -> bool {
+            self.expected != self.actual
+        }
+    }
+
+    let text_diff = TextDiff::from_lines(expected, actual);
+    let (old, new) = (text_diff.old_slices(), text_diff.new_slices());
+
+    let mut rows = vec![];
+    let mut marked = false;
+
+    for op in text_diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let (old, new) = (&old[old_range], &new[new_range]);
+
+        if tag == DiffTag::Equal {
+            rows.extend(old.iter().map(|&line| Row {
+                expected: Some(line),
+                actual: Some(line),
+                ..Row::default()
+            }));
+            continue;
+        }
+
+        let start = rows.len();
+
+        if style == DiffStyle::SideBySide {
+            rows.extend(old.iter().zip_longest(new).map(|pair| match pair {
+                EitherOrBoth::Both(&e, &a) => Row {
+                    expected: Some(e),
+                    actual: Some(a),
+                    ..Row::default()
+                },
+                EitherOrBoth::Left(&e) => Row {
+                    expected: Some(e),
+                    ..Row::default()
+                },
+                EitherOrBoth::Right(&a) => Row {
+                    actual: Some(a),
+                    ..Row::default()
+                },
+            }));
+        } else {
+            rows.extend(old.iter().map(|&e| Row {
+                expected: Some(e),
+                ..Row::default()
+            }));
+            rows.extend(new.iter().map(|&a| Row {
+                actual: Some(a),
+                ..Row::default()
+            }));
+        }
+
+        if !mem::replace(&mut marked, true) {
+            let common = match (old.first(), new.first()) {
+                (Some(e), Some(a)) => e.chars().zip(a.chars()).take_while(|(e, a)| e == a).count(),
+                _ => 0,
+            };
+            if let Some(row) = rows[start..].iter_mut().find(|r| r.expected.is_some()) {
+                row.expected_mark = Some(common);
+            }
+            if let Some(row) = rows[start..].iter_mut().find(|r| r.actual.is_some()) {
+                row.actual_mark = Some(common);
+            }
+        }
     }
 
-    fn align_right(s: &str, n: usize) -> String {
-        let spaces = n.saturating_sub(s.width());
-        itertools::repeat_n(' ', spaces).chain(s.chars()).collect()
+    let row_len = |row: &Row<'_>| match (style, row.is_changed()) {
+        (DiffStyle::SideBySide, _) | (_, true) => {
+            row.expected.map_or(0, str::len) + row.actual.map_or(0, str::len)
+        }
+        _ => row.expected.map_or(0, str::len),
+    };
+
+    // Centers the window on the first difference.
+    let (mut start, mut end) = (0, rows.len());
+    if let Some(display_limit) = display_limit {
+        let first = rows.iter().position(Row::is_changed).unwrap_or(0);
+        start = first;
+        end = cmp::min(first + 1, rows.len());
+        let mut total = rows[start..end].iter().map(row_len).sum::<usize>();
+        loop {
+            let mut extended = false;
+            if end < rows.len() && total + row_len(&rows[end]) <= display_limit {
+                total += row_len(&rows[end]);
+                end += 1;
+                extended = true;
+            }
+            if start > 0 && total + row_len(&rows[start - 1]) <= display_limit {
+                start -= 1;
+                total += row_len(&rows[start]);
+                extended = true;
+            }
+            if !extended {
+                break;
+            }
+        }
     }
+    let num_rows = rows.len();
+    let rows = &rows[start..end];
+
+    let write_omitted = |wtr: &mut dyn WriteColor, n: usize| -> io::Result<()> {
+        if n > 0 {
+            wtr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+            writeln!(wtr, "({} line(s) omitted)", n)?;
+            wtr.reset()?;
+        }
+        Ok(())
+    };
+
+    write_omitted(&mut wtr, start)?;
+
+    if style == DiffStyle::SideBySide {
+        let width = rows
+            .iter()
+            .flat_map(|r| r.expected)
+            .map(line_width)
+            .chain(iter::once("expected".len()))
+            .max()
+            .unwrap_or(0);
+
+        writeln!(wtr, "{} | actual", align_left("expected", width))?;
+
+        for row in rows {
+            let (color, sep) = match (row.expected, row.actual) {
+                (Some(_), Some(_)) if !row.is_changed() => (None, ' '),
+                (Some(_), None) => (Some(palette.success), '<'),
+                (None, Some(_)) => (Some(palette.failure), '>'),
+                _ => (Some(palette.failure), '|'),
+            };
+            let written = if let Some(line) = row.expected {
+                let color = color.map(|_| palette.success);
+                write_line(&mut wtr, line, color, row.expected_mark)?
+            } else {
+                0
+            };
+            write!(wtr, "{} {}", " ".repeat(width - written), sep)?;
+            if let Some(line) = row.actual {
+                write!(wtr, " ")?;
+                write_line(
+                    &mut wtr,
+                    line,
+                    color.map(|_| palette.failure),
+                    row.actual_mark,
+                )?;
+            }
+            writeln!(wtr)?;
+        }
+    } else {
+        wtr.set_color(color_spec!(Fg(palette.success)))?;
+        write!(wtr, "--- expected")?;
+        wtr.reset()?;
+        writeln!(wtr)?;
+        wtr.set_color(color_spec!(Fg(palette.failure)))?;
+        write!(wtr, "+++ actual")?;
+        wtr.reset()?;
+        writeln!(wtr)?;
+
+        for row in rows {
+            match (row.expected, row.actual) {
+                (Some(line), _) if !row.is_changed() => {
+                    write!(wtr, "  ")?;
+                    write_line(&mut wtr, line, None, None)?;
+                }
+                (Some(line), _) => {
+                    wtr.set_color(color_spec!(Fg(palette.success)))?;
+                    write!(wtr, "- ")?;
+                    write_line(&mut wtr, line, Some(palette.success), row.expected_mark)?;
+                }
+                (None, Some(line)) => {
+                    wtr.set_color(color_spec!(Fg(palette.failure)))?;
+                    write!(wtr, "+ ")?;
+                    write_line(&mut wtr, line, Some(palette.failure), row.actual_mark)?;
+                }
+                (None, None) => continue,
+            }
+            writeln!(wtr)?;
+        }
+    }
+
+    write_omitted(&mut wtr, num_rows - end)?;
+    return wtr.flush();
+
+    /// Writes `line` without the line feed, returning its width.
+    fn write_line(
+        mut wtr: impl WriteColor,
+        line: &str,
+        color: Option<Color>,
+        mark: Option<usize>,
+    ) -> io::Result<usize> {
+        let content = line.strip_suffix('\n');
+        let mut visible = content
+            .unwrap_or(line)
+            .chars()
+            .map(visible_char)
+            .collect::<Vec<_>>();
+        let num_trailing_spaces = visible.iter().rev().take_while(|s| *s == " ").count();
+        let len = visible.len();
+        for s in &mut visible[len - num_trailing_spaces..] {
+            *s = "␣".to_owned();
+        }
+        if content.is_none() {
+            visible.push("⏎".to_owned());
+        }
+
+        let spec = color_spec!().set_fg(color).set_bold(mark.is_some()).clone();
+        let mark = mark.filter(|&i| i < visible.len());
+
+        for (i, s) in visible.iter().enumerate() {
+            if content.is_none() && i + 1 == visible.len() {
+                wtr.reset()?;
+                wtr.set_color(color_spec!(Fg(Color::Yellow)))?;
+            } else if i == 0 || mark.map(|m| m + 1) == Some(i) {
+                wtr.reset()?;
+                wtr.set_color(&spec)?;
+            }
+            if mark == Some(i) {
+                wtr.set_color(color_spec!().set_underline(true))?;
+            }
+            write!(wtr, "{}", s)?;
+            if mark == Some(i) {
+                wtr.reset()?;
+            }
+        }
+
+        wtr.reset()?;
+        Ok(visible.iter().map(|s| s.width()).sum())
+    }
+
+    fn line_width(line: &str) -> usize {
+        let content = line.strip_suffix('\n');
+        let width = content
+            .unwrap_or(line)
+            .chars()
+            .map(|c| visible_char(c).width())
+            .sum::<usize>();
+        width + usize::from(content.is_none())
+    }
+
+    fn visible_char(c: char) -> String {
+        match c {
+            '\t' => "\\t".to_owned(),
+            '\r' => "\\r".to_owned(),
+            c if c.is_control() => c.escape_unicode().to_string(),
+            c => c.to_string(),
+        }
+    }
+}
+
+fn align_left(s: &str, n: usize) -> String {
+    let spaces = n.saturating_sub(s.width());
+    s.chars().chain(itertools::repeat_n(' ', spaces)).collect()
+}
+
+fn align_right(s: &str, n: usize) -> String {
+    let spaces = n.saturating_sub(s.width());
+    itertools::repeat_n(' ', spaces).chain(s.chars()).collect()
 }
 
 async fn check(
-    expected: &ExpectedOutput,
+    test_case: &BatchTestCase,
     actual: &str,
     cwd: &Path,
     stdin_path: &Path,
     actual_stdout_path: &Path,
     expected_stdout_path: &Path,
     bash_exe: &Path,
-) -> eyre::Result<Result<(), (Arc<str>, Arc<str>, Option<WrongAnswerNote>)>> {
+) -> eyre::Result<Result<Option<f64>, (Arc<str>, Arc<str>, Option<WrongAnswerNote>)>> {
+    let BatchTestCase {
+        output: expected,
+        encoding,
+        ..
+    } = test_case;
+    let encoding = *encoding;
+
     match expected {
         ExpectedOutput::Deterministic(expected) => Ok(if expected.accepts(actual) {
-            Ok(())
+            Ok(None)
         } else {
             let note = expected
                 .expected_stdout()
@@ -704,7 +2312,7 @@ async fn check(
 
             let mut env_vars = vec![("INPUT", stdin_path), ("ACTUAL_OUTPUT", actual_stdout_path)];
             if let Some(text) = text {
-                tokio::fs::write(expected_stdout_path, text.as_ref()).await?;
+                tokio::fs::write(expected_stdout_path, encoding.encode(text)?).await?;
                 env_vars.push(("EXPECTED_OUTPUT", expected_stdout_path));
             }
 
@@ -713,7 +2321,7 @@ async fn check(
                 stdout,
                 stderr,
             } = tokio::process::Command::new(program)
-                .args(&args)
+                .args(args)
                 .envs(env_vars)
                 .current_dir(cwd)
                 .stdin(Stdio::null())
@@ -723,19 +2331,912 @@ async fn check(
                 .output()
                 .await?;
 
-            let (stdout, stderr) = (utf8(stdout)?, utf8(stderr)?);
+            let (stdout, stderr) = (decode(stdout, encoding)?, decode(stderr, encoding)?);
 
             Ok(if status.success() {
-                Ok(())
+                // The first token is regarded as the score if it is a number.
+                Ok(stdout
+                    .split_whitespace()
+                    .next()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .filter(|s| s.is_finite()))
             } else {
                 Err((stdout, stderr, None))
             })
         }
+        ExpectedOutput::Transform { text, cmd, shell } => {
+            let (program, args) = match shell {
+                CheckerShell::Bash => (bash_exe, [OsStr::new("-c"), OsStr::new(cmd)]),
+            };
+
+            tokio::fs::write(expected_stdout_path, encoding.encode(text)?).await?;
+
+            let transform = |which: &'static str, path| async move {
+                let stdin = tokio::fs::File::open(path).await?.into_std().await;
+
+                let Output {
+                    status,
+                    stdout,
+                    stderr,
+                } = tokio::process::Command::new(program)
+                    .args(args)
+                    .env("INPUT", stdin_path)
+                    .current_dir(cwd)
+                    .stdin(stdin)
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true)
+                    .output()
+                    .await?;
+
+                if !status.success() {
+                    bail!(
+                        "the transform command failed for the {} output ({}): {}",
+                        which,
+                        status,
+                        String::from_utf8_lossy(&stderr),
+                    );
+                }
+                Ok::<_, eyre::Error>((decode(stdout, encoding)?, decode(stderr, encoding)?))
+            };
+
+            let (expected, expected_stderr) = transform("expected", expected_stdout_path).await?;
+            let (actual, actual_stderr) = transform("actual", actual_stdout_path).await?;
+
+            Ok(if expected == actual {
+                Ok(None)
+            } else {
+                let stderr = format!("{}{}", expected_stderr, actual_stderr);
+                Err((Arc::from(""), stderr.into(), None))
+            })
+        }
+    }
+}
+
+/// A [`tail`] task, which is told to stop when this is dropped.
+struct Tail {
+    finished: Arc<AtomicBool>,
+    handle: Option<tokio::task::JoinHandle<io::Result<()>>>,
+}
+
+impl Tail {
+    fn spawn(paths: [PathBuf; 2], wtr: Arc<Mutex<dyn io::Write + Send>>) -> Self {
+        let finished = Arc::new(AtomicBool::new(false));
+        let handle = tokio::task::spawn(tail(paths, wtr, finished.clone()));
+        Self {
+            finished,
+            handle: Some(handle),
+        }
+    }
+
+    /// Lets the task copy the rest of the files, and waits for it.
+    async fn finish(mut self) -> eyre::Result<()> {
+        self.finished.store(true, atomic::Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            handle.await??;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Tail {
+    fn drop(&mut self) {
+        self.finished.store(true, atomic::Ordering::SeqCst);
+    }
+}
+
+/// Copies what is appended to the files to `wtr` until `finished` is set.
+async fn tail(
+    paths: [PathBuf; 2],
+    wtr: Arc<Mutex<dyn io::Write + Send>>,
+    finished: Arc<AtomicBool>,
+) -> io::Result<()> {
+    let mut files = vec![];
+    for path in &paths {
+        files.push(tokio::fs::File::open(path).await?);
+    }
+
+    let mut buf = vec![];
+    loop {
+        // Reads once more after the process exits.
+        let last = finished.load(atomic::Ordering::SeqCst);
+
+        for file in &mut files {
+            buf.clear();
+            file.read_to_end(&mut buf).await?;
+            if !buf.is_empty() {
+                let mut wtr = wtr.lock().unwrap();
+                wtr.write_all(&buf)?;
+                wtr.flush()?;
+            }
+        }
+
+        if last {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
 }
 
-fn utf8(bytes: Vec<u8>) -> eyre::Result<Arc<str>> {
-    String::from_utf8(bytes)
+/// Samples the number of the threads and the resident set size in the process tree of `pid`
+/// until it exits.
+///
+/// The peak memory usage is the maximum of the sums of the current resident set sizes. The peak of
+/// each single process (`VmHWM`) is also taken into account, so that the peaks between the samples
+/// are not missed for a single process.
+async fn watch_process_tree(pid: u32, peak_threads: Arc<AtomicUsize>, peak_memory: Arc<AtomicU64>) {
+    while let Ok(Some(Sample { threads, rss, hwm })) =
+        tokio::task::spawn_blocking(move || sample(pid)).await
+    {
+        peak_threads.fetch_max(threads, atomic::Ordering::SeqCst);
+        peak_memory.fetch_max(cmp::max(rss, hwm), atomic::Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    struct Sample {
+        /// The number of the threads in the tree.
+        threads: usize,
+        /// The sum of the resident set sizes in bytes.
+        rss: u64,
+        /// The largest peak resident set size of a single process in bytes.
+        hwm: u64,
+    }
+
+    #[cfg(target_os = "linux")]
+    fn sample(pid: u32) -> Option<Sample> {
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        let field = |name: &str| -> Option<u64> {
+            status
+                .lines()
+                .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))?
+                .trim()
+                .trim_end_matches(" kB")
+                .parse()
+                .ok()
+        };
+        let threads = field("Threads")? as usize;
+        // Kernel threads and zombies do not have them.
+        let rss = field("VmRSS").unwrap_or(0) * 1024;
+        let hwm = field("VmHWM").unwrap_or(0) * 1024;
+
+        let sample = std::fs::read_dir(format!("/proc/{}/task", pid))
+            .ok()?
+            .flatten()
+            .flat_map(|task| std::fs::read_to_string(task.path().join("children")))
+            .flat_map(|children| {
+                children
+                    .split_whitespace()
+                    .flat_map(str::parse)
+                    .collect::<Vec<_>>()
+            })
+            .flat_map(sample)
+            .fold(Sample { threads, rss, hwm }, |acc, child| Sample {
+                threads: acc.threads + child.threads,
+                rss: acc.rss + child.rss,
+                hwm: cmp::max(acc.hwm, child.hwm),
+            });
+
+        Some(sample)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn sample(_: u32) -> Option<Sample> {
+        None
+    }
+}
+
+fn decode(bytes: Vec<u8>, encoding: TextEncoding) -> eyre::Result<Arc<str>> {
+    encoding
+        .decode(&bytes)
         .map(Into::into)
-        .map_err(|_| eyre!("the output was not a valid UTF-8 string"))
+        .ok_or_else(|| eyre!("the output was not a valid {} string", encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        judge::{BuildKind, JudgeEvent, JudgeOutcome, Verdict},
+        testsuite::{BatchTestCase, ByteSize, DeterministicExpectedOutput, ExpectedOutput},
+    };
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    /// `bash -c <script>` in `cwd`.
+    #[cfg(unix)]
+    fn bash(script: &str, cwd: &std::path::Path) -> crate::judge::CommandExpression {
+        crate::judge::CommandExpression {
+            program: "bash".into(),
+            args: vec!["-c".into(), script.into()],
+            cwd: cwd.to_owned(),
+            env: maplit::btreemap!(),
+        }
+    }
+
+    /// A test case that accepts any output.
+    #[cfg(unix)]
+    fn case(input: &str, timelimit: Option<Duration>) -> BatchTestCase {
+        BatchTestCase {
+            name: None,
+            timelimit,
+            memorylimit: None,
+            input: input.into(),
+            input_file: None,
+            output: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+            encoding: Default::default(),
+        }
+    }
+
+    #[test]
+    fn judge_event_schema() {
+        let events = [
+            JudgeEvent::CompileStart {
+                kind: BuildKind::Compile,
+                command: "g++ ./a.cpp".to_owned(),
+            },
+            JudgeEvent::CompileDone {
+                kind: BuildKind::Transpile,
+                up_to_date: true,
+                elapsed_ms: 0.0,
+            },
+            JudgeEvent::CaseStart {
+                index: 0,
+                total: 2,
+                name: Some("Sample 1".to_owned()),
+            },
+            JudgeEvent::CaseDone {
+                index: 1,
+                total: 2,
+                name: None,
+                verdict: "wrong-answer",
+                elapsed_ms: 1.5,
+            },
+            JudgeEvent::Summary {
+                total: 2,
+                passed: 1,
+                failed: 1,
+            },
+        ];
+
+        let lines = events
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            [
+                r#"{"event":"compile-start","kind":"compile","command":"g++ ./a.cpp"}"#,
+                r#"{"event":"compile-done","kind":"transpile","up_to_date":true,"elapsed_ms":0.0}"#,
+                r#"{"event":"case-start","index":0,"total":2,"name":"Sample 1"}"#,
+                r#"{"event":"case-done","index":1,"total":2,"name":null,"verdict":"wrong-answer","elapsed_ms":1.5}"#,
+                r#"{"event":"summary","total":2,"passed":1,"failed":1}"#,
+            ],
+            &*lines,
+        );
+    }
+
+    #[test]
+    fn to_json() {
+        let outcome = JudgeOutcome {
+            verdicts: vec![
+                Verdict::Accepted {
+                    test_case_name: Some("sample1".to_owned()),
+                    elapsed: Duration::from_millis(12),
+                    stdin: "1 2\n".into(),
+                    input_file: None,
+                    stdout: "3\n".into(),
+                    stderr: "".into(),
+                    expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                        text: "3\n".into(),
+                    }),
+                    score: None,
+                    unread_stdin: None,
+                    peak_threads: None,
+                    peak_memory: None,
+                },
+                Verdict::TimelimitExceeded {
+                    test_case_name: None,
+                    timelimit: Duration::from_secs(2),
+                    stdin: "".into(),
+                    input_file: None,
+                    expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+                },
+            ],
+        };
+
+        assert_eq!(
+            serde_json::json!({
+                "cases": [
+                    {
+                        "name": "sample1",
+                        "verdict": "accepted",
+                        "elapsed_ms": 12.0,
+                        "input_size": 4,
+                        "expected_size": 2,
+                        "actual_size": 2,
+                        "exit_code": 0,
+                        "signal": null,
+                        "score": null,
+                        "peak_memory": null,
+                    },
+                    {
+                        "name": null,
+                        "verdict": "timelimit-exceeded",
+                        "elapsed_ms": 2000.0,
+                        "input_size": 0,
+                        "expected_size": null,
+                        "actual_size": null,
+                        "exit_code": null,
+                        "signal": null,
+                        "score": null,
+                        "peak_memory": null,
+                    },
+                ],
+            }),
+            serde_json::from_str::<serde_json::Value>(&outcome.to_json()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn print_test_cases() -> std::io::Result<()> {
+        let test_case = |name: &str, input: &str, output, timelimit, memorylimit| BatchTestCase {
+            name: Some(name.to_owned()),
+            timelimit,
+            memorylimit,
+            input: input.into(),
+            input_file: None,
+            output,
+            encoding: Default::default(),
+        };
+
+        let test_cases = [
+            test_case(
+                "1",
+                "1 2\n",
+                ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "3\n".into(),
+                }),
+                Some(Duration::from_secs(2)),
+                Some(ByteSize::from_bytes(1 << 30)),
+            ),
+            test_case(
+                "あ",
+                "",
+                ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+                None,
+                None,
+            ),
+        ];
+
+        let mut wtr = termcolor::NoColor::new(vec![]);
+        super::print_test_cases(&mut wtr, &test_cases)?;
+
+        assert_eq!(
+            "1/2 \"1\"  in: 4 B out: 2 B timelimit: 2000 ms memorylimit: 1.00 GiB\n\
+             2/2 \"あ\" in: 0 B out:   - timelimit:       - memorylimit: -\n",
+            String::from_utf8(wtr.into_inner()).unwrap(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn palette() {
+        use super::Palette;
+        use termcolor::Color;
+
+        assert_eq!(Palette::default(), "default".parse().unwrap());
+        assert_eq!(Palette::colorblind(), "colorblind".parse().unwrap());
+        assert_eq!(
+            Palette {
+                failure: Color::Magenta,
+                ..Palette::colorblind()
+            },
+            "colorblind, failure=magenta".parse().unwrap(),
+        );
+        assert_eq!(
+            Palette {
+                success: Color::Ansi256(39),
+                ..Palette::default()
+            },
+            "success=39".parse().unwrap(),
+        );
+        assert!("rainbow".parse::<Palette>().is_err());
+        assert!("success=0,255,0".parse::<Palette>().is_err());
+        assert!("error=red".parse::<Palette>().is_err());
+    }
+
+    #[test]
+    fn jobs() {
+        use super::Jobs;
+        use std::num::NonZeroUsize;
+
+        assert_eq!(Jobs::Auto, "auto".parse().unwrap());
+        assert_eq!(
+            Jobs::Count(NonZeroUsize::new(4).unwrap()),
+            "4".parse().unwrap(),
+        );
+        assert_eq!(num_cpus::get(), Jobs::Auto.get());
+        assert!("0".parse::<Jobs>().is_err());
+        assert!("all".parse::<Jobs>().is_err());
+    }
+
+    #[test]
+    fn write_diff() -> std::io::Result<()> {
+        use super::{DiffStyle, Palette};
+
+        let diff = |expected, actual, style, display_limit| -> std::io::Result<String> {
+            let mut wtr = termcolor::NoColor::new(vec![]);
+            super::write_diff(
+                &mut wtr,
+                expected,
+                actual,
+                style,
+                display_limit,
+                &Palette::default(),
+            )?;
+            Ok(String::from_utf8(wtr.into_inner()).unwrap())
+        };
+
+        assert_eq!(
+            "--- expected\n\
+             +++ actual\n  \
+             1\n\
+             - 2\n\
+             + 3\n  \
+             4\n",
+            diff("1\n2\n4\n", "1\n3\n4\n", DiffStyle::Unified, None)?,
+        );
+
+        assert_eq!(
+            "expected | actual\n\
+             あい       あい\n\
+             う       | え\n\
+             お       <\n",
+            diff("あい\nう\nお\n", "あい\nえ\n", DiffStyle::SideBySide, None)?,
+        );
+
+        assert_eq!(
+            "(2 line(s) omitted)\n\
+             --- expected\n\
+             +++ actual\n  \
+             3\n\
+             - 4\n\
+             + 5\n  \
+             6\n\
+             (2 line(s) omitted)\n",
+            diff(
+                "1\n2\n3\n4\n6\n7\n8\n",
+                "1\n2\n3\n5\n6\n7\n8\n",
+                DiffStyle::Unified,
+                Some(8),
+            )?,
+        );
+
+        assert_eq!(
+            "--- expected\n\
+             +++ actual\n\
+             - 3\n\
+             + 3⏎\n",
+            diff("3\n", "3", DiffStyle::Unified, None)?,
+        );
+
+        assert_eq!(
+            "--- expected\n\
+             +++ actual\n\
+             - 1 2\n\
+             + 1 2␣␣\n",
+            diff("1 2\n", "1 2  \n", DiffStyle::Unified, None)?,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_spaces() -> std::io::Result<()> {
+        use super::{DiffStyle, PrettyOptions};
+
+        let outcome = JudgeOutcome {
+            verdicts: vec![Verdict::WrongAnswer {
+                test_case_name: Some("sample1".to_owned()),
+                elapsed: Duration::from_millis(12),
+                stdin: "1 2\n".into(),
+                input_file: None,
+                stdout: "1 2  \n".into(),
+                stderr: "".into(),
+                checker_stdout: "".into(),
+                checker_stderr: "".into(),
+                expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                    text: "1 2\n".into(),
+                }),
+                note: None,
+                unread_stdin: None,
+                peak_threads: None,
+                peak_memory: None,
+            }],
+        };
+
+        let print_pretty = |diff| -> std::io::Result<String> {
+            let mut wtr = termcolor::NoColor::new(vec![]);
+            outcome.print_pretty(
+                &mut wtr,
+                &PrettyOptions {
+                    diff,
+                    ..Default::default()
+                },
+            )?;
+            Ok(String::from_utf8(wtr.into_inner()).unwrap())
+        };
+
+        assert_eq!(
+            "1/1 (\"sample1\") Wrong Answer (12 ms)\n\
+             stdin:\n\
+             1 2\n\
+             expected:\n\
+             1 2\n\
+             actual:\n\
+             1 2␣␣\n",
+            print_pretty(DiffStyle::Raw)?,
+        );
+
+        let unified = print_pretty(DiffStyle::Unified)?;
+        assert!(unified.contains("- 1 2\n+ 1 2␣␣\n"), "{}", unified);
+        Ok(())
+    }
+
+    #[test]
+    fn display_duration() {
+        assert_eq!(
+            "300 µs",
+            super::display_duration(Duration::from_micros(300))
+        );
+        assert_eq!("0 µs", super::display_duration(Duration::from_nanos(100)));
+        assert_eq!(
+            "1.500 ms",
+            super::display_duration(Duration::from_micros(1500))
+        );
+        assert_eq!(
+            "9.999 ms",
+            super::display_duration(Duration::from_nanos(9_999_400))
+        );
+        assert_eq!("10 ms", super::display_duration(Duration::from_millis(10)));
+        assert_eq!(
+            "9999 ms",
+            super::display_duration(Duration::from_millis(9999))
+        );
+        assert_eq!(
+            "12.35 s",
+            super::display_duration(Duration::from_millis(12345))
+        );
+    }
+
+    #[test]
+    fn explanation() {
+        let wrong_answer = |stdout: &str, expected: &str| Verdict::WrongAnswer {
+            test_case_name: None,
+            elapsed: Duration::from_millis(100),
+            stdin: "".into(),
+            input_file: None,
+            stdout: stdout.into(),
+            stderr: "".into(),
+            checker_stdout: "".into(),
+            checker_stderr: "".into(),
+            expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::SplitWhitespace {
+                text: expected.into(),
+            }),
+            note: None,
+            unread_stdin: None,
+            peak_threads: None,
+            peak_memory: None,
+        };
+
+        assert_eq!(
+            Some("Your output has 5 lines but 4 were expected."),
+            wrong_answer("1\n2\n3\n4\n5\n", "1\n2\n3\n4\n")
+                .explanation()
+                .as_deref(),
+        );
+        assert_eq!(
+            Some(r#"Line 2 token 3: expected "7", got "8"."#),
+            wrong_answer("1\n2 3 8\n", "1\n2 3 7\n")
+                .explanation()
+                .as_deref(),
+        );
+
+        let float_wrong_answer = Verdict::WrongAnswer {
+            test_case_name: None,
+            elapsed: Duration::from_millis(100),
+            stdin: "".into(),
+            input_file: None,
+            stdout: "1.0001 2.1\n".into(),
+            stderr: "".into(),
+            checker_stdout: "".into(),
+            checker_stderr: "".into(),
+            expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Float {
+                text: "1.0 2.0\n".into(),
+                relative_error: None,
+                absolute_error: Some("1e-3".parse().unwrap()),
+            }),
+            note: None,
+            unread_stdin: None,
+            peak_threads: None,
+            peak_memory: None,
+        };
+
+        assert_eq!(
+            Some(r#"Line 1 token 2: expected "2.0", got "2.1"."#),
+            float_wrong_answer.explanation().as_deref(),
+        );
+
+        let subsequence_wrong_answer = Verdict::WrongAnswer {
+            test_case_name: None,
+            elapsed: Duration::from_millis(100),
+            stdin: "".into(),
+            input_file: None,
+            stdout: "debug\n3\n5\n".into(),
+            stderr: "".into(),
+            checker_stdout: "".into(),
+            checker_stderr: "".into(),
+            expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Subsequence {
+                text: "3\n4\n".into(),
+            }),
+            note: None,
+            unread_stdin: None,
+            peak_threads: None,
+            peak_memory: None,
+        };
+
+        assert_eq!(
+            Some(
+                r#"Line 2 of the expected output ("4") was not found in your output after line 2."#
+            ),
+            subsequence_wrong_answer.explanation().as_deref(),
+        );
+
+        let timelimit_exceeded = Verdict::TimelimitExceeded {
+            test_case_name: None,
+            timelimit: Duration::from_secs(2),
+            stdin: "".into(),
+            input_file: None,
+            expected: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass),
+        };
+
+        assert_eq!(
+            Some("Your program did not finish within the time limit (2.0s)."),
+            timelimit_exceeded.explanation().as_deref(),
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn termination() {
+        use crate::judge::Termination;
+        use std::{os::unix::process::ExitStatusExt as _, process::ExitStatus};
+
+        // The raw wait statuses of being terminated by signals, and of `exit(1)`.
+        let status = ExitStatus::from_raw;
+
+        assert_eq!(
+            Some("signal SIGSEGV (segmentation fault)".to_owned()),
+            Termination::of(status(libc::SIGSEGV)).map(|t| t.to_string()),
+        );
+        assert_eq!(
+            Some("signal SIGABRT (aborted)".to_owned()),
+            Termination::of(status(libc::SIGABRT)).map(|t| t.to_string()),
+        );
+        assert_eq!(
+            Some("signal 40".to_owned()),
+            Termination::of(status(40)).map(|t| t.to_string()),
+        );
+        assert_eq!(None, Termination::of(status(1 << 8)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn input_file_is_streamed() -> eyre::Result<()> {
+        use crate::judge::JudgeOptions;
+        use indicatif::ProgressDrawTarget;
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-judge-test-")
+            .tempdir()?;
+        let input_file = tempdir.path().join("large.in");
+        let size = 1 << 20;
+        std::fs::write(&input_file, "1\n".repeat(size / 2))?;
+
+        let test_case = |expected: String| BatchTestCase {
+            input_file: Some(input_file.clone()),
+            output: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                text: expected.into(),
+            }),
+            ..case("", Some(Duration::from_secs(10)))
+        };
+
+        let judge = |script: &str, expected: String| {
+            super::judge(
+                ProgressDrawTarget::hidden(),
+                futures_util::future::pending::<tokio::io::Result<()>>,
+                &bash(script, tempdir.path()),
+                &[test_case(expected)],
+                &JudgeOptions::default(),
+            )
+        };
+
+        let outcome = judge("wc -c", format!("{}\n", size))?;
+        assert!(matches!(&*outcome.verdicts, [Verdict::Accepted { .. }]));
+
+        // The input is not loaded, so only its size and its path are shown.
+        let mut wtr = termcolor::NoColor::new(vec![]);
+        outcome.print_pretty(&mut wtr, &Default::default())?;
+        let pretty = String::from_utf8(wtr.into_inner())?;
+        let stdin = format!("stdin:\n{} B ({})\n", size, input_file.display());
+        assert!(pretty.contains(&stdin), "{}", pretty);
+
+        let json = serde_json::from_str::<serde_json::Value>(&outcome.to_json())?;
+        assert_eq!(json["cases"][0]["input_size"], size);
+
+        // The rest of the input is discarded when the program does not read it.
+        let outcome = judge("head -n 1", "1\n".to_owned())?;
+        assert!(matches!(&*outcome.verdicts, [Verdict::Accepted { .. }]));
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn timelimit_exceeded_kills_process_group() -> eyre::Result<()> {
+        use crate::judge::JudgeOptions;
+        use indicatif::ProgressDrawTarget;
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-judge-test-")
+            .tempdir()?;
+        let pid_path = tempdir.path().join("pid");
+
+        let cmd = bash(
+            &format!("sleep 60 & echo $! > {:?}; wait", pid_path),
+            tempdir.path(),
+        );
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            futures_util::future::pending::<tokio::io::Result<()>>,
+            &cmd,
+            &[case("", Some(Duration::from_millis(500)))],
+            &JudgeOptions::default(),
+        )?;
+
+        assert!(matches!(
+            &*outcome.verdicts,
+            [Verdict::TimelimitExceeded { .. }],
+        ));
+
+        let pid = std::fs::read_to_string(&pid_path)?;
+        let stat_path = format!("/proc/{}/stat", pid.trim());
+
+        // The sleeper is reaped by init, which may take a moment.
+        let reaped = (0..100).any(|_| {
+            let alive =
+                std::fs::read_to_string(&stat_path).is_ok_and(|stat| !stat.contains(") Z "));
+            if alive {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            !alive
+        });
+        assert!(reaped, "`sleep` was left running");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn json_report_of_run() -> eyre::Result<()> {
+        use crate::judge::JudgeOptions;
+        use indicatif::ProgressDrawTarget;
+        use serde_json::json;
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-judge-test-")
+            .tempdir()?;
+
+        let cmd = bash(
+            r#"read -r x; if [ "$x" = re ]; then exit 3; fi; echo "$x""#,
+            tempdir.path(),
+        );
+
+        let test_case = |name: &str, input: &str, output: &str| BatchTestCase {
+            name: Some(name.to_owned()),
+            output: ExpectedOutput::Deterministic(DeterministicExpectedOutput::Exact {
+                text: output.into(),
+            }),
+            ..case(input, None)
+        };
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            futures_util::future::pending::<tokio::io::Result<()>>,
+            &cmd,
+            &[
+                test_case("ok", "1\n", "1\n"),
+                test_case("wa", "2\n", "3\n"),
+                test_case("re", "re\n", "4\n"),
+            ],
+            &JudgeOptions::default(),
+        )?;
+
+        let mut json = serde_json::from_str::<serde_json::Value>(&outcome.to_json())?;
+        for case in json["cases"].as_array_mut().unwrap() {
+            assert!(case["elapsed_ms"].as_f64().unwrap() > 0.0);
+            case["elapsed_ms"] = json!(0.0);
+        }
+
+        let case = |name, verdict, input_size, actual_size, exit_code| {
+            json!({
+                "name": name,
+                "verdict": verdict,
+                "elapsed_ms": 0.0,
+                "input_size": input_size,
+                "expected_size": 2,
+                "actual_size": actual_size,
+                "exit_code": exit_code,
+                "signal": null,
+                "score": null,
+                "peak_memory": null,
+            })
+        };
+
+        assert_eq!(
+            json!({
+                "cases": [
+                    case("ok", "accepted", 2, 2, 0),
+                    case("wa", "wrong-answer", 2, 2, 0),
+                    case("re", "runtime-error", 3, 0, 3),
+                ],
+            }),
+            json,
+        );
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn max_threads() -> eyre::Result<()> {
+        use crate::judge::JudgeOptions;
+        use indicatif::ProgressDrawTarget;
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-core-judge-test-")
+            .tempdir()?;
+
+        // `n` processes with a thread each, alive for 0.5 seconds.
+        let cmd = bash(
+            r#"read -r n; for _ in $(seq "$n"); do sleep 0.5 & done; wait"#,
+            tempdir.path(),
+        );
+
+        let outcome = super::judge(
+            ProgressDrawTarget::hidden(),
+            futures_util::future::pending::<tokio::io::Result<()>>,
+            &cmd,
+            &[case("1\n", None), case("4\n", None)],
+            &JudgeOptions {
+                max_threads: Some(3),
+                ..Default::default()
+            },
+        )?;
+
+        assert!(
+            matches!(outcome.verdicts[0], Verdict::Accepted { .. }),
+            "{:?}",
+            outcome.verdicts[0].kebab_case_name(),
+        );
+        match &outcome.verdicts[1] {
+            Verdict::ThreadLimitExceeded {
+                peak_threads,
+                max_threads,
+                ..
+            } => {
+                assert_eq!(5, *peak_threads);
+                assert_eq!(3, *max_threads);
+            }
+            verdict => panic!("got {}", verdict.kebab_case_name()),
+        }
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use eyre::{bail, ensure, Context as _, ContextCompat as _};
 use humantime_serde::Serde;
 use ignore::{overrides::OverrideBuilder, WalkBuilder};
@@ -6,11 +6,13 @@ use itertools::{EitherOrBoth, Itertools as _};
 use maplit::hashmap;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use std::{
-    borrow::Borrow,
+    borrow::{Borrow, Cow},
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    fs,
+    convert::TryFrom,
+    fmt, fs,
     hash::Hash,
-    path::Path,
+    iter,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
     time::Duration,
@@ -33,8 +35,21 @@ impl TestSuite {
 
                 yaml += &key_value("type", "Batch").ok()?;
                 yaml += &key_value("timelimit", Serde::from(suite.timelimit)).ok()?;
+
+                if let Some(memorylimit) = suite.memorylimit {
+                    yaml += &key_value("memorylimit", memorylimit).ok()?;
+                }
+
                 yaml += &key_value("match", &suite.r#match).ok()?;
 
+                if suite.whitespace != Whitespace::default() {
+                    yaml += &key_value("whitespace", suite.whitespace).ok()?;
+                }
+
+                if let Some(encoding) = suite.encoding {
+                    yaml += &key_value("encoding", encoding).ok()?;
+                }
+
                 yaml += if suite.cases.is_empty() {
                     "\ncases: []\n"
                 } else {
@@ -48,16 +63,24 @@ impl TestSuite {
                         part += &key_value("name", name).ok()?;
                     }
 
-                    part += &key_value_in_literal_style("in", &case.r#in).ok()?;
-
-                    if let Some(out) = &case.out {
-                        part += &key_value_in_literal_style("out", out).ok()?;
+                    for (key, text) in iter::once(("in", &case.r#in))
+                        .chain(case.out.as_ref().map(|out| ("out", out)))
+                    {
+                        part += &match text {
+                            CaseText::Text(text) => key_value_in_literal_style(key, text),
+                            CaseText::File { .. } => key_value(key, text),
+                        }
+                        .ok()?;
                     }
 
                     if let Some(timelimit) = case.timelimit {
                         part += &key_value("timelimit", Serde::from(timelimit)).ok()?;
                     }
 
+                    if let Some(memorylimit) = case.memorylimit {
+                        part += &key_value("memorylimit", memorylimit).ok()?;
+                    }
+
                     if let Some(r#match) = &case.r#match {
                         part += &key_value("match", r#match).ok()?;
                     }
@@ -147,13 +170,112 @@ impl TestSuite {
             .unwrap_or_else(|| key_value(key, value))
         }
     }
+
+    /// Appends the test cases that `original`, the YAML this was read from, does not have yet,
+    /// editing it in place so that the comments and the formatting are kept.
+    ///
+    /// Returns `None` if this differs from `original` other than by the appended cases, or if
+    /// `cases` of `original` is neither a block sequence nor `[]`.
+    pub fn append_cases_to_yaml(&self, original: &str) -> Option<String> {
+        let (new, old) = match (self, serde_yaml::from_str(original).ok()?) {
+            (Self::Batch(new), Self::Batch(old)) => (new, old),
+            _ => return None,
+        };
+
+        let added = new.cases.strip_prefix(&*old.cases)?;
+
+        if (
+            new.timelimit,
+            new.memorylimit,
+            &new.r#match,
+            new.encoding,
+            &new.extend,
+        ) != (
+            old.timelimit,
+            old.memorylimit,
+            &old.r#match,
+            old.encoding,
+            &old.extend,
+        ) {
+            return None;
+        }
+        if added.is_empty() {
+            return Some(original.to_owned());
+        }
+
+        let rendered = Self::Batch(BatchTestSuite {
+            timelimit: None,
+            memorylimit: None,
+            r#match: Match::Lines,
+            whitespace: Default::default(),
+            encoding: None,
+            cases: added.to_owned(),
+            extend: vec![],
+        })
+        .to_yaml_pretty();
+        let (_, items) = rendered.split_once("\ncases:\n")?;
+        let (items, _) = items.split_once("\n\nextend:")?;
+
+        let mut lines = original.lines().map(Cow::from).collect::<Vec<_>>();
+
+        let start = lines.iter().position(|l| l.starts_with("cases:"))?;
+        let rest = lines[start]["cases:".len()..].trim_start();
+        if let Some(rest) = rest.strip_prefix("[]") {
+            let comment = rest.trim_start();
+            if !(comment.is_empty() || comment.starts_with('#')) {
+                return None;
+            }
+            lines[start] = format!("cases: {}", comment).trim_end().to_owned().into();
+        } else if !(rest.is_empty() || rest.starts_with('#')) {
+            return None;
+        }
+
+        let is_top_level_key = |l: &str| !(l.is_empty() || l.starts_with([' ', '\t', '-', '#']));
+        let end = lines[start + 1..]
+            .iter()
+            .position(|l| is_top_level_key(l))
+            .map_or(lines.len(), |i| start + 1 + i);
+
+        let indent = lines[start + 1..end]
+            .iter()
+            .find_map(|l| {
+                let trimmed = l.trim_start_matches(' ');
+                trimmed.starts_with('-').then(|| l.len() - trimmed.len())
+            })
+            .unwrap_or(2);
+
+        // After the last item, leaving the blank lines and the comments for the next key.
+        let insert_at = (start + 1..end)
+            .rev()
+            .find(|&i| !(lines[i].trim().is_empty() || lines[i].starts_with('#')))
+            .map_or(start + 1, |i| i + 1);
+
+        let items = items
+            .lines()
+            .map(|l| Cow::from(format!("{}{}", " ".repeat(indent), &l[2..])));
+        lines.splice(insert_at..insert_at, items);
+
+        let mut yaml = lines.join("\n");
+        yaml += "\n";
+
+        if serde_yaml::from_str::<Self>(&yaml).ok()? != *self {
+            return None;
+        }
+        Some(yaml)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct BatchTestSuite {
     #[serde(default, with = "humantime_serde")]
     pub timelimit: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memorylimit: Option<ByteSize>,
     pub r#match: Match,
+    #[serde(default, skip_serializing_if = "Whitespace::is_default")]
+    pub whitespace: Whitespace,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TextEncoding>,
     #[serde(default)]
     pub cases: Vec<PartialBatchTestCase>,
     #[serde(default)]
@@ -170,9 +292,19 @@ impl BatchTestSuite {
         mut names: Option<HashSet<S>>,
         mut prepare_system_test_cases: F,
     ) -> eyre::Result<Vec<BatchTestCase>> {
+        if let Some(preset) = self.presets().next() {
+            bail!("The preset `{}` is not resolved", preset);
+        }
+
+        let encoding = self.encoding.unwrap_or_default();
+
         let mut cases = self.cases.clone();
         for extend in &self.extend {
-            cases.extend(extend.load_test_cases(parent_dir, &mut prepare_system_test_cases)?);
+            cases.extend(extend.load_test_cases(
+                parent_dir,
+                encoding,
+                &mut prepare_system_test_cases,
+            )?);
         }
 
         let cases = cases
@@ -183,8 +315,8 @@ impl BatchTestSuite {
                     _ => true,
                 },
             )
-            .map(|case| BatchTestCase::new(case, self.timelimit, &self.r#match))
-            .collect();
+            .map(|case| BatchTestCase::new(case, self, encoding, parent_dir))
+            .collect::<eyre::Result<_>>()?;
 
         if let Some(names) = names {
             if !names.is_empty() {
@@ -197,20 +329,136 @@ impl BatchTestSuite {
 
         Ok(cases)
     }
+
+    /// Names of the `Preset`s referred from this test suite.
+    pub fn presets(&self) -> impl Iterator<Item = &str> {
+        self.matches().flat_map(|m| match m {
+            Match::Preset(name) => Some(&**name),
+            _ => None,
+        })
+    }
+
+    /// Replaces `Preset`s with the ones in `presets`.
+    pub fn resolve_presets(&mut self, presets: &BTreeMap<String, Match>) -> eyre::Result<()> {
+        for r#match in self.matches_mut() {
+            if let Match::Preset(name) = r#match {
+                let preset = presets.get(name).with_context(|| {
+                    format!(
+                        "No such preset: `{}` (expected one of [{}])",
+                        name,
+                        presets.keys().format(", "),
+                    )
+                })?;
+                ensure!(
+                    !matches!(preset, Match::Preset(_)),
+                    "A preset cannot refer to another preset: `{}`",
+                    name,
+                );
+                *r#match = preset.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Uses `r#match` for all of the test cases.
+    pub fn override_match(&mut self, r#match: Match) {
+        self.r#match = r#match;
+        for case in &mut self.cases {
+            case.r#match = None;
+        }
+        for extend in &mut self.extend {
+            if let Additional::Text { r#match, .. } = extend {
+                *r#match = None;
+            }
+        }
+    }
+
+    fn matches(&self) -> impl Iterator<Item = &Match> {
+        iter::once(&self.r#match)
+            .chain(self.cases.iter().flat_map(|c| &c.r#match))
+            .chain(self.extend.iter().flat_map(|e| match e {
+                Additional::Text { r#match, .. } => r#match.as_ref(),
+                Additional::SystemTestCases { .. } => None,
+            }))
+    }
+
+    fn matches_mut(&mut self) -> impl Iterator<Item = &mut Match> {
+        iter::once(&mut self.r#match)
+            .chain(self.cases.iter_mut().flat_map(|c| &mut c.r#match))
+            .chain(self.extend.iter_mut().flat_map(|e| match e {
+                Additional::Text { r#match, .. } => r#match.as_mut(),
+                Additional::SystemTestCases { .. } => None,
+            }))
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct PartialBatchTestCase {
     pub name: Option<String>,
-    #[serde(with = "serde_fn::arc_str")]
-    pub r#in: Arc<str>,
-    #[serde(default, with = "serde_fn::option_arc_str")]
-    pub out: Option<Arc<str>>,
+    pub r#in: CaseText,
+    #[serde(default)]
+    pub out: Option<CaseText>,
     #[serde(default, with = "humantime_serde")]
     pub timelimit: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memorylimit: Option<ByteSize>,
     pub r#match: Option<Match>,
 }
 
+/// `in` or `out` of a test case: the text itself, or `{ file: <path> }` where the path is relative
+/// to the directory of the test suite.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CaseText {
+    Text(#[serde(with = "serde_fn::arc_str")] Arc<str>),
+    File { file: Utf8PathBuf },
+}
+
+impl CaseText {
+    fn path(&self, parent_dir: &Path) -> Option<PathBuf> {
+        match self {
+            Self::Text(_) => None,
+            Self::File { file } => Some(Self::join(parent_dir, file)),
+        }
+    }
+
+    fn load(self, parent_dir: &Path, encoding: TextEncoding) -> eyre::Result<Arc<str>> {
+        let path = match self {
+            Self::Text(text) => return Ok(text),
+            Self::File { file } => Self::join(parent_dir, &file),
+        };
+        let content =
+            fs::read(&path).with_context(|| format!("Could not read {}", path.display()))?;
+        let content = encoding
+            .decode(&content)
+            .with_context(|| format!("{} is not valid {}", path.display(), encoding))?;
+        Ok(content.into())
+    }
+
+    fn join(parent_dir: &Path, file: &Utf8Path) -> PathBuf {
+        let path = Path::new(file);
+        parent_dir.join(path.strip_prefix(".").unwrap_or(path))
+    }
+}
+
+impl From<Arc<str>> for CaseText {
+    fn from(text: Arc<str>) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<String> for CaseText {
+    fn from(text: String) -> Self {
+        Self::Text(text.into())
+    }
+}
+
+impl From<&'_ str> for CaseText {
+    fn from(text: &'_ str) -> Self {
+        Self::Text(text.into())
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(tag = "type")]
 pub enum Additional {
@@ -237,6 +485,7 @@ impl Additional {
     fn load_test_cases(
         &self,
         parent_dir: &Path,
+        encoding: TextEncoding,
         mut prepare_system_test_cases: impl FnMut(
             Option<&Url>,
         ) -> eyre::Result<Vec<PartialBatchTestCase>>,
@@ -274,9 +523,25 @@ impl Additional {
                                 .to_string_lossy()
                                 .into_owned();
 
-                            let content = fs::read_to_string(&path)
-                                .with_context(|| format!("Could not read {}", path.display()))?
-                                .into();
+                            // Read when the test case is selected.
+                            let file = path
+                                .strip_prefix(parent_dir)
+                                .ok()
+                                .and_then(|p| Utf8PathBuf::from_path_buf(p.to_owned()).ok());
+
+                            let content = if let Some(file) = file {
+                                CaseText::File { file }
+                            } else {
+                                let content = fs::read(&path).with_context(|| {
+                                    format!("Could not read {}", path.display())
+                                })?;
+                                encoding
+                                    .decode(&content)
+                                    .with_context(|| {
+                                        format!("{} is not valid {}", path.display(), encoding)
+                                    })?
+                                    .into()
+                            };
 
                             Ok(Some((name, content)))
                         })
@@ -311,6 +576,7 @@ impl Additional {
                             r#in,
                             out,
                             timelimit: *timelimit,
+                            memorylimit: None,
                             r#match: r#match.clone(),
                         })
                     })
@@ -326,6 +592,9 @@ pub enum Match {
     Exact,
     SplitWhitespace,
     Lines,
+    /// Accepts if the lines of the expected output appear in the actual output in order. The
+    /// other lines, such as logs, are ignored.
+    Subsequence,
     Float {
         relative_error: Option<PositiveFinite<f64>>,
         absolute_error: Option<PositiveFinite<f64>>,
@@ -334,6 +603,14 @@ pub enum Match {
         cmd: String,
         shell: CheckerShell,
     },
+    /// Pipes both the expected and the actual output through `cmd`, then compares them exactly.
+    Transform {
+        cmd: String,
+        shell: CheckerShell,
+    },
+    /// Refers to a preset defined in the config. Resolved with
+    /// [`BatchTestSuite::resolve_presets`].
+    Preset(String),
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -341,6 +618,263 @@ pub enum CheckerShell {
     Bash,
 }
 
+/// How whitespace is normalized in both the expected and the actual outputs before comparing
+/// them.
+///
+/// By default, only empty lines at the end are ignored, so that a missing or an extra final
+/// newline does not matter as on AtCoder. This does not apply to `Match::Exact`, which compares
+/// the outputs verbatim.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct Whitespace {
+    /// Removes spaces and tabs at the end of each line.
+    pub trim_trailing: bool,
+    /// Replaces each run of spaces and tabs with a single space.
+    pub collapse_spaces: bool,
+    /// Ignores empty lines at the end, and whether the last line ends with a newline.
+    pub ignore_trailing_blank_lines: bool,
+}
+
+impl Whitespace {
+    /// Leaves the text as it is.
+    pub const KEEP: Self = Self {
+        trim_trailing: false,
+        collapse_spaces: false,
+        ignore_trailing_blank_lines: false,
+    };
+
+    pub fn normalize(self, text: &str) -> Cow<'_, str> {
+        if self == Self::KEEP {
+            return Cow::Borrowed(text);
+        }
+
+        let mut normalized = String::with_capacity(text.len());
+
+        for line in text.split_inclusive('\n') {
+            let (mut line, eol) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (line, ""),
+            };
+            if self.trim_trailing {
+                line = line.trim_end_matches(&[' ', '\t', '\r'][..]);
+            }
+            if self.collapse_spaces {
+                let mut prev_is_space = false;
+                for c in line.chars() {
+                    let is_space = c == ' ' || c == '\t';
+                    if !(is_space && prev_is_space) {
+                        normalized.push(if is_space { ' ' } else { c });
+                    }
+                    prev_is_space = is_space;
+                }
+            } else {
+                normalized += line;
+            }
+            normalized += eol;
+        }
+
+        if self.ignore_trailing_blank_lines {
+            normalized.truncate(normalized.trim_end_matches('\n').len());
+            if !normalized.is_empty() {
+                normalized.push('\n');
+            }
+        }
+
+        Cow::Owned(normalized)
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for Whitespace {
+    fn default() -> Self {
+        Self {
+            ignore_trailing_blank_lines: true,
+            ..Self::KEEP
+        }
+    }
+}
+
+/// How the inputs and the outputs are converted from/to bytes.
+///
+/// Written as an encoding label (e.g. `Shift_JIS`) or `binary` in test suites. Comparison is
+/// done on the decoded text.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum TextEncoding {
+    Encoding(&'static encoding_rs::Encoding),
+    /// Maps each byte to the `char` of the same value, so that `Exact` compares raw bytes.
+    Binary,
+}
+
+impl TextEncoding {
+    /// Decodes `bytes`, returning `None` if they are malformed.
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Self::Encoding(encoding) => encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(Cow::into_owned),
+            Self::Binary => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+        }
+    }
+
+    /// Encodes `text`, failing if it contains characters not representable in this encoding.
+    pub fn encode(self, text: &str) -> eyre::Result<Cow<'_, [u8]>> {
+        match self {
+            Self::Encoding(encoding) => {
+                let (bytes, _, had_errors) = encoding.encode(text);
+                ensure!(!had_errors, "the text is not representable in {}", self);
+                Ok(bytes)
+            }
+            Self::Binary => text
+                .chars()
+                .map(|c| u8::try_from(c).ok())
+                .collect::<Option<_>>()
+                .map(Cow::Owned)
+                .with_context(|| format!("the text contains characters above U+00FF: {:?}", text)),
+        }
+    }
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        Self::Encoding(encoding_rs::UTF_8)
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encoding(encoding) => f.write_str(encoding.name()),
+            Self::Binary => f.write_str("binary"),
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        if s.eq_ignore_ascii_case("binary") {
+            Ok(Self::Binary)
+        } else {
+            encoding_rs::Encoding::for_label(s.as_ref())
+                .map(Self::Encoding)
+                .with_context(|| format!("Unknown encoding: {:?}", s))
+        }
+    }
+}
+
+impl TryFrom<String> for TextEncoding {
+    type Error = eyre::Report;
+
+    fn try_from(s: String) -> eyre::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<TextEncoding> for String {
+    fn from(encoding: TextEncoding) -> Self {
+        encoding.to_string()
+    }
+}
+
+/// An amount of memory.
+///
+/// Written as a number followed by a unit (e.g. `256MiB`, `1024 MB`) in test suites. `KB`, `MB`,
+/// and `GB` are powers of 1000, and `KiB`, `MiB`, and `GiB` are powers of 1024.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    const UNITS: &'static [(&'static str, u64)] = &[
+        ("GiB", 1 << 30),
+        ("GB", 1_000_000_000),
+        ("MiB", 1 << 20),
+        ("MB", 1_000_000),
+        ("KiB", 1 << 10),
+        ("KB", 1_000),
+        ("B", 1),
+    ];
+
+    pub fn from_bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+
+    /// Formats in the largest binary unit that keeps the number at least 1, such as `1.5 KiB`.
+    ///
+    /// Unlike [`Display`], the number may be rounded.
+    ///
+    /// [`Display`]: fmt::Display
+    pub fn to_human_readable(self) -> String {
+        let bytes = self.0;
+        if bytes < 1 << 10 {
+            format!("{} B", bytes)
+        } else if bytes < 1 << 20 {
+            format!("{:.1} KiB", bytes as f64 / f64::from(1 << 10))
+        } else if bytes < 1 << 30 {
+            format!("{:.1} MiB", bytes as f64 / f64::from(1 << 20))
+        } else {
+            format!("{:.2} GiB", bytes as f64 / f64::from(1 << 30))
+        }
+    }
+}
+
+impl fmt::Display for ByteSize {
+    /// Writes with the unit that divides it with the smallest quotient.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, n) = Self::UNITS
+            .iter()
+            .filter(|&&(_, size)| self.0.is_multiple_of(size))
+            .map(|&(unit, size)| (unit, self.0 / size))
+            .min_by_key(|&(_, n)| n)
+            .unwrap_or(("B", self.0));
+        write!(f, "{}{}", n, unit)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let s = s.trim();
+        let (number, unit) = s.split_at(
+            s.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(s.len()),
+        );
+        let number = number
+            .parse::<f64>()
+            .with_context(|| format!("Invalid size: {:?}", s))?;
+        let unit = unit.trim();
+        let (_, size) = Self::UNITS
+            .iter()
+            .find(|(u, _)| u.eq_ignore_ascii_case(unit) || unit.is_empty() && *u == "B")
+            .with_context(|| format!("Unknown unit in {:?}", s))?;
+        Ok(Self((number * *size as f64).round() as _))
+    }
+}
+
+impl TryFrom<String> for ByteSize {
+    type Error = eyre::Report;
+
+    fn try_from(s: String) -> eyre::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<ByteSize> for String {
+    fn from(size: ByteSize) -> Self {
+        size.to_string()
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct InteractiveTestSuite {
     #[serde(default, with = "humantime_serde")]
@@ -351,19 +885,139 @@ pub struct InteractiveTestSuite {
 pub struct BatchTestCase {
     pub name: Option<String>,
     pub timelimit: Option<Duration>,
+    pub memorylimit: Option<ByteSize>,
+    /// Empty if `input_file` is set.
     pub input: Arc<str>,
+    /// The file of the input, which is copied to the program without being loaded into memory.
+    pub input_file: Option<PathBuf>,
     pub output: ExpectedOutput,
+    pub encoding: TextEncoding,
 }
 
 impl BatchTestCase {
-    fn new(case: PartialBatchTestCase, timelimit: Option<Duration>, matching: &Match) -> Self {
-        BatchTestCase {
-            name: case.name,
-            timelimit: case.timelimit.or(timelimit),
-            input: case.r#in,
-            output: ExpectedOutput::new(case.out, case.r#match.unwrap_or_else(|| matching.clone())),
+    /// Overrides the comparison settings from the test suite.
+    ///
+    /// `Checker` and `Transform` cases and cases without expected outputs are left as they are.
+    pub fn override_match(&mut self, r#override: &MatchOverride) {
+        let MatchOverride {
+            relative_error,
+            absolute_error,
+            unordered,
+            case_insensitive,
+        } = *r#override;
+
+        if let ExpectedOutput::Deterministic(expected) = &mut self.output {
+            let text = match expected.expected_stdout() {
+                Some(text) => Arc::from(text),
+                None => return,
+            };
+
+            if relative_error.is_some() || absolute_error.is_some() {
+                *expected.innermost_mut() = DeterministicExpectedOutput::Float {
+                    text,
+                    relative_error,
+                    absolute_error,
+                };
+            }
+
+            if unordered || case_insensitive {
+                if let DeterministicExpectedOutput::Normalized {
+                    unordered: u,
+                    case_insensitive: c,
+                    ..
+                } = expected
+                {
+                    *u |= unordered;
+                    *c |= case_insensitive;
+                } else {
+                    *expected = DeterministicExpectedOutput::Normalized {
+                        inner: Box::new(expected.clone()),
+                        unordered,
+                        case_insensitive,
+                        whitespace: Whitespace::KEEP,
+                    };
+                }
+            }
         }
     }
+
+    fn new(
+        case: PartialBatchTestCase,
+        suite: &BatchTestSuite,
+        encoding: TextEncoding,
+        parent_dir: &Path,
+    ) -> eyre::Result<Self> {
+        let PartialBatchTestCase {
+            name,
+            r#in,
+            out,
+            timelimit,
+            memorylimit,
+            r#match,
+        } = case;
+
+        let context = |key| {
+            format!(
+                "Could not load `{}` of the test case {:?}",
+                key,
+                name.as_deref().unwrap_or(""),
+            )
+        };
+        let load = |key, text: CaseText| {
+            text.load(parent_dir, encoding)
+                .with_context(|| context(key))
+        };
+
+        let (input, input_file) = match r#in.path(parent_dir) {
+            Some(path) => {
+                fs::metadata(&path)
+                    .with_context(|| format!("Could not read {}", path.display()))
+                    .with_context(|| context("in"))?;
+                ("".into(), Some(path))
+            }
+            None => (load("in", r#in)?, None),
+        };
+        let out = out.map(|out| load("out", out)).transpose()?;
+
+        let r#match = r#match.unwrap_or_else(|| suite.r#match.clone());
+        let is_exact = r#match == Match::Exact;
+        let mut output = ExpectedOutput::new(out, r#match);
+
+        if let ExpectedOutput::Deterministic(expected) = &mut output {
+            // `Exact` and binary outputs are compared byte by byte.
+            if !is_exact
+                && encoding != TextEncoding::Binary
+                && suite.whitespace != Whitespace::KEEP
+                && expected.expected_stdout().is_some()
+            {
+                *expected = DeterministicExpectedOutput::Normalized {
+                    inner: Box::new(expected.clone()),
+                    unordered: false,
+                    case_insensitive: false,
+                    whitespace: suite.whitespace,
+                };
+            }
+        }
+
+        Ok(BatchTestCase {
+            name,
+            timelimit: timelimit.or(suite.timelimit),
+            memorylimit: memorylimit.or(suite.memorylimit),
+            input,
+            input_file,
+            output,
+            encoding,
+        })
+    }
+}
+
+/// Comparison settings given for one invocation, which take precedence over the test suite.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MatchOverride {
+    pub relative_error: Option<PositiveFinite<f64>>,
+    pub absolute_error: Option<PositiveFinite<f64>>,
+    pub unordered: bool,
+    pub case_insensitive: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -374,12 +1028,19 @@ pub enum ExpectedOutput {
         cmd: String,
         shell: CheckerShell,
     },
+    Transform {
+        text: Arc<str>,
+        cmd: String,
+        shell: CheckerShell,
+    },
 }
 
 impl ExpectedOutput {
     fn new(text: Option<Arc<str>>, matching: Match) -> Self {
         match (text, matching) {
             (text, Match::Checker { cmd, shell }) => Self::Checker { text, cmd, shell },
+            (Some(text), Match::Transform { cmd, shell }) => Self::Transform { text, cmd, shell },
+            (_, Match::Preset(name)) => unreachable!("unresolved preset: {:?}", name),
             (Some(text), Match::Exact) => {
                 Self::Deterministic(DeterministicExpectedOutput::Exact { text })
             }
@@ -389,6 +1050,9 @@ impl ExpectedOutput {
             (Some(text), Match::Lines) => {
                 Self::Deterministic(DeterministicExpectedOutput::Lines { text })
             }
+            (Some(text), Match::Subsequence) => {
+                Self::Deterministic(DeterministicExpectedOutput::Subsequence { text })
+            }
             (
                 Some(text),
                 Match::Float {
@@ -405,16 +1069,14 @@ impl ExpectedOutput {
     }
 
     pub(crate) fn is_float(&self) -> bool {
-        matches!(
-            self,
-            Self::Deterministic(DeterministicExpectedOutput::Float { .. })
-        )
+        matches!(self, Self::Deterministic(expected) if expected.is_float())
     }
 
     pub(crate) fn expected_stdout(&self) -> Option<&str> {
         match self {
             Self::Deterministic(expected) => expected.expected_stdout(),
             Self::Checker { .. } => None,
+            Self::Transform { text, .. } => Some(text),
         }
     }
 
@@ -438,20 +1100,54 @@ pub enum DeterministicExpectedOutput {
     Lines {
         text: Arc<str>,
     },
+    Subsequence {
+        text: Arc<str>,
+    },
     Float {
         text: Arc<str>,
         relative_error: Option<PositiveFinite<f64>>,
         absolute_error: Option<PositiveFinite<f64>>,
     },
+    /// Compares with `inner` after normalizing the whitespace, sorting the lines, and/or
+    /// lowercasing both outputs.
+    Normalized {
+        inner: Box<Self>,
+        unordered: bool,
+        case_insensitive: bool,
+        whitespace: Whitespace,
+    },
 }
 
 impl DeterministicExpectedOutput {
     pub(crate) fn accepts(&self, actual: &str) -> bool {
         match self {
             Self::Pass => true,
+            Self::Normalized {
+                inner,
+                unordered,
+                case_insensitive,
+                whitespace,
+            } => {
+                let normalize = |text: &str| -> Arc<str> {
+                    let text = whitespace.normalize(text);
+                    let text = if *case_insensitive {
+                        text.to_lowercase()
+                    } else {
+                        text.into_owned()
+                    };
+                    if *unordered {
+                        text.lines().sorted().map(|l| format!("{}\n", l)).join("")
+                    } else {
+                        text
+                    }
+                    .into()
+                };
+                inner.map_text(normalize).accepts(&normalize(actual))
+            }
             Self::Exact { text } => &**text == actual,
             Self::SplitWhitespace { text } => text.split_whitespace().eq(actual.split_whitespace()),
             Self::Lines { text } => text.lines().eq(actual.lines()),
+            Self::Subsequence { text } => Self::unmatched_line(text, actual).is_none(),
             Self::Float {
                 text,
                 relative_error,
@@ -466,13 +1162,7 @@ impl DeterministicExpectedOutput {
                         let (words1, words2) = (line1.split_whitespace(), line2.split_whitespace());
                         words1.zip_longest(words2).all(|zip| match zip {
                             EitherOrBoth::Both(s1, s2) => {
-                                match (s1.parse::<f64>(), s2.parse::<f64>()) {
-                                    (Ok(v1), Ok(v2)) => {
-                                        (v1 - v2).abs() <= absolute_error
-                                            || ((v1 - v2) / v2).abs() <= relative_error
-                                    }
-                                    _ => s1 == s2,
-                                }
+                                float_tokens_eq(s1, s2, relative_error, absolute_error)
                             }
                             EitherOrBoth::Left(_) | EitherOrBoth::Right(_) => false,
                         })
@@ -484,15 +1174,91 @@ impl DeterministicExpectedOutput {
         }
     }
 
+    /// Whether the whitespace-separated tokens are regarded as the same.
+    pub(crate) fn tokens_eq(&self, expected: &str, actual: &str) -> bool {
+        match self {
+            Self::Float {
+                relative_error,
+                absolute_error,
+                ..
+            } => float_tokens_eq(
+                expected,
+                actual,
+                relative_error.map(PositiveFinite::get).unwrap_or(0.0),
+                absolute_error.map(PositiveFinite::get).unwrap_or(0.0),
+            ),
+            Self::Normalized {
+                inner,
+                case_insensitive: true,
+                ..
+            } => inner.tokens_eq(&expected.to_lowercase(), &actual.to_lowercase()),
+            Self::Normalized { inner, .. } => inner.tokens_eq(expected, actual),
+            _ => expected == actual,
+        }
+    }
+
     pub(crate) fn expected_stdout(&self) -> Option<&str> {
         match self {
             Self::Pass => None,
             Self::Exact { text }
             | Self::SplitWhitespace { text }
             | Self::Lines { text }
+            | Self::Subsequence { text }
             | Self::Float { text, .. } => Some(text),
+            Self::Normalized { inner, .. } => inner.expected_stdout(),
+        }
+    }
+
+    /// Normalizes the whitespace in `text` as done before comparison.
+    pub(crate) fn normalize_whitespace<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::Normalized { whitespace, .. } => whitespace.normalize(text),
+            _ => Cow::Borrowed(text),
         }
     }
+
+    /// Finds the first line of `expected` that does not appear in `actual` after the previous
+    /// ones, returning the 0-based index and the number of the lines of `actual` consumed so far.
+    pub(crate) fn unmatched_line(expected: &str, actual: &str) -> Option<(usize, usize)> {
+        let mut actual_lines = actual.lines().enumerate();
+        let mut consumed = 0;
+        for (i, expected) in expected.lines().enumerate() {
+            match actual_lines.find(|(_, actual)| *actual == expected) {
+                Some((j, _)) => consumed = j + 1,
+                None => return Some((i, consumed)),
+            }
+        }
+        None
+    }
+
+    fn is_float(&self) -> bool {
+        match self {
+            Self::Float { .. } => true,
+            Self::Normalized { inner, .. } => inner.is_float(),
+            _ => false,
+        }
+    }
+
+    fn innermost_mut(&mut self) -> &mut Self {
+        match self {
+            Self::Normalized { inner, .. } => inner.innermost_mut(),
+            this => this,
+        }
+    }
+
+    fn map_text(&self, f: impl Fn(&str) -> Arc<str>) -> Self {
+        let mut this = self.clone();
+        match &mut this {
+            Self::Pass => {}
+            Self::Exact { text }
+            | Self::SplitWhitespace { text }
+            | Self::Lines { text }
+            | Self::Subsequence { text }
+            | Self::Float { text, .. } => *text = f(text),
+            Self::Normalized { inner, .. } => **inner = inner.map_text(f),
+        }
+        this
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize)]
@@ -531,6 +1297,33 @@ impl<'de> Deserialize<'de> for PositiveFinite<f64> {
     }
 }
 
+/// Compares two tokens as floating-point numbers if both of them are ones.
+///
+/// Integers that `f64` cannot represent exactly are compared as strings, and so are `NaN`s and
+/// infinities.
+fn float_tokens_eq(s1: &str, s2: &str, relative_error: f64, absolute_error: f64) -> bool {
+    if s1 == s2 {
+        return true;
+    }
+
+    let is_large_integer = |s: &str| {
+        let digits = s.strip_prefix(&['-', '+'][..]).unwrap_or(s);
+        !digits.is_empty()
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && digits.trim_start_matches('0').len() > 15
+    };
+    if is_large_integer(s1) || is_large_integer(s2) {
+        return false;
+    }
+
+    match (s1.parse::<f64>(), s2.parse::<f64>()) {
+        (Ok(v1), Ok(v2)) if v1.is_finite() && v2.is_finite() => {
+            (v1 - v2).abs() <= absolute_error || ((v1 - v2) / v2).abs() <= relative_error
+        }
+        _ => false,
+    }
+}
+
 mod serde_fn {
     pub(super) mod arc_str {
         use serde::{Deserialize, Deserializer, Serializer};
@@ -550,43 +1343,19 @@ mod serde_fn {
             String::deserialize(deserializer).map(Into::into)
         }
     }
-
-    pub(super) mod option_arc_str {
-        use serde::{Deserialize, Deserializer, Serializer};
-        use std::sync::Arc;
-
-        pub(crate) fn serialize<S>(
-            this: &Option<Arc<str>>,
-            serializer: S,
-        ) -> Result<S::Ok, S::Error>
-        where
-            S: Serializer,
-        {
-            if let Some(s) = this {
-                serializer.serialize_some(&**s)
-            } else {
-                serializer.serialize_none()
-            }
-        }
-
-        pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Arc<str>>, D::Error>
-        where
-            D: Deserializer<'de>,
-        {
-            Option::<String>::deserialize(deserializer).map(|s| s.map(Into::into))
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::testsuite::{
-        Additional, BatchTestSuite, DeterministicExpectedOutput, Match, PartialBatchTestCase,
-        PositiveFinite, TestSuite,
+        Additional, BatchTestSuite, ByteSize, CaseText, CheckerShell, DeterministicExpectedOutput,
+        ExpectedOutput, Match, PartialBatchTestCase, PositiveFinite, TestSuite, TextEncoding,
+        Whitespace,
     };
     use difference::assert_diff;
+    use maplit::hashset;
     use pretty_assertions::assert_eq;
-    use std::time::Duration;
+    use std::{collections::HashSet, path::Path, time::Duration};
 
     #[test]
     fn atcoder_abc162_a() {
@@ -617,13 +1386,17 @@ extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
                 timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
                 r#match: Match::Lines,
+                whitespace: Default::default(),
+                encoding: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
                         r#in: "117\n".into(),
                         out: Some("Yes\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                     PartialBatchTestCase {
@@ -631,6 +1404,7 @@ extend: []
                         r#in: "123\n".into(),
                         out: Some("No\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                     PartialBatchTestCase {
@@ -638,6 +1412,7 @@ extend: []
                         r#in: "777\n".into(),
                         out: Some("Yes\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                 ],
@@ -661,7 +1436,10 @@ extend:
 "#,
             &TestSuite::Batch(BatchTestSuite {
                 timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
                 r#match: Match::Lines,
+                whitespace: Default::default(),
+                encoding: None,
                 cases: vec![],
                 extend: vec![Additional::Text {
                     path: "./a".into(),
@@ -701,16 +1479,20 @@ extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
                 timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
                 r#match: Match::Float {
                     relative_error: Some(PositiveFinite(0.01)),
                     absolute_error: Some(PositiveFinite(0.01)),
                 },
+                whitespace: Default::default(),
+                encoding: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
                         r#in: "1\n".into(),
                         out: Some("6.28318530717958623200\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                     PartialBatchTestCase {
@@ -718,6 +1500,7 @@ extend: []
                         r#in: "73\n".into(),
                         out: Some("458.67252742410977361942\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                 ],
@@ -726,6 +1509,305 @@ extend: []
         );
     }
 
+    #[test]
+    fn transform() {
+        test_serialize_deserialize(
+            r#"---
+type: Batch
+timelimit: 2s
+match:
+  Transform:
+    cmd: sort
+    shell: Bash
+
+cases:
+  - name: Sample 1
+    in: |
+      2
+    out: |
+      1 2
+      2 1
+
+extend: []
+"#,
+            &TestSuite::Batch(BatchTestSuite {
+                timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
+                r#match: Match::Transform {
+                    cmd: "sort".to_owned(),
+                    shell: CheckerShell::Bash,
+                },
+                whitespace: Default::default(),
+                encoding: None,
+                cases: vec![PartialBatchTestCase {
+                    name: Some("Sample 1".to_owned()),
+                    r#in: "2\n".into(),
+                    out: Some("1 2\n2 1\n".into()),
+                    timelimit: None,
+                    memorylimit: None,
+                    r#match: None,
+                }],
+                extend: vec![],
+            }),
+        );
+    }
+
+    #[test]
+    fn memorylimit() {
+        test_serialize_deserialize(
+            r#"---
+type: Batch
+timelimit: 2s
+memorylimit: 1024MB
+match: Exact
+
+cases:
+  - in: |
+      1
+    out: |
+      1
+    memorylimit: 256MiB
+
+extend: []
+"#,
+            &TestSuite::Batch(BatchTestSuite {
+                timelimit: Some(Duration::from_secs(2)),
+                memorylimit: Some(ByteSize::from_bytes(1_024_000_000)),
+                r#match: Match::Exact,
+                whitespace: Default::default(),
+                encoding: None,
+                cases: vec![PartialBatchTestCase {
+                    name: None,
+                    r#in: "1\n".into(),
+                    out: Some("1\n".into()),
+                    timelimit: None,
+                    memorylimit: Some(ByteSize::from_bytes(256 << 20)),
+                    r#match: None,
+                }],
+                extend: vec![],
+            }),
+        );
+
+        let parse = |s: &str| s.parse::<ByteSize>().ok().map(ByteSize::bytes);
+        assert_eq!(Some(1 << 30), parse("1GiB"));
+        assert_eq!(Some(1_500_000), parse("1.5 MB"));
+        assert_eq!(Some(1024), parse("1024"));
+        assert_eq!(Some(2048), parse("2kib"));
+        assert_eq!(None, parse("1 parsec"));
+        assert_eq!(None, parse("MB"));
+    }
+
+    #[test]
+    fn encoding() {
+        test_serialize_deserialize(
+            r#"---
+type: Batch
+timelimit: 2s
+match: Exact
+encoding: Shift_JIS
+
+cases:
+  - in: |
+      1
+    out: |
+      あ
+
+extend: []
+"#,
+            &TestSuite::Batch(BatchTestSuite {
+                timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
+                r#match: Match::Exact,
+                whitespace: Default::default(),
+                encoding: Some(TextEncoding::Encoding(encoding_rs::SHIFT_JIS)),
+                cases: vec![PartialBatchTestCase {
+                    name: None,
+                    r#in: "1\n".into(),
+                    out: Some("あ\n".into()),
+                    timelimit: None,
+                    memorylimit: None,
+                    r#match: None,
+                }],
+                extend: vec![],
+            }),
+        );
+
+        let sjis = "sjis".parse::<TextEncoding>().unwrap();
+        assert_eq!(b"\x82\xa0\n", &*sjis.encode("あ\n").unwrap());
+        assert_eq!(Some("あ\n"), sjis.decode(b"\x82\xa0\n").as_deref());
+        assert_eq!(None, TextEncoding::default().decode(b"\x82\xa0\n"));
+
+        let binary = "binary".parse::<TextEncoding>().unwrap();
+        assert_eq!(
+            Some("\u{82}\u{a0}\n"),
+            binary.decode(b"\x82\xa0\n").as_deref()
+        );
+        assert_eq!(b"\x82\xa0\n", &*binary.encode("\u{82}\u{a0}\n").unwrap());
+        assert!(binary.encode("あ").is_err());
+
+        assert!("no-such-encoding".parse::<TextEncoding>().is_err());
+    }
+
+    #[test]
+    fn whitespace() {
+        test_serialize_deserialize(
+            r#"---
+type: Batch
+timelimit: 2s
+match: Exact
+whitespace:
+  trim_trailing: true
+  collapse_spaces: false
+  ignore_trailing_blank_lines: true
+
+cases:
+  - in: |
+      1
+    out: |
+      1 2
+
+extend: []
+"#,
+            &TestSuite::Batch(BatchTestSuite {
+                timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
+                r#match: Match::Exact,
+                whitespace: Whitespace {
+                    trim_trailing: true,
+                    ..Whitespace::default()
+                },
+                encoding: None,
+                cases: vec![PartialBatchTestCase {
+                    name: None,
+                    r#in: "1\n".into(),
+                    out: Some("1 2\n".into()),
+                    timelimit: None,
+                    memorylimit: None,
+                    r#match: None,
+                }],
+                extend: vec![],
+            }),
+        );
+
+        let mut suite = serde_yaml::from_str::<BatchTestSuite>(
+            r#"---
+match: Lines
+whitespace:
+  trim_trailing: true
+cases:
+  - in: ""
+    out: "1 2\n"
+"#,
+        )
+        .unwrap();
+
+        let accepts = |suite: &BatchTestSuite, actual: &str| {
+            let cases = suite
+                .load_test_cases(Path::new(""), None::<HashSet<String>>, |_| unreachable!())
+                .unwrap();
+            match &cases[0].output {
+                ExpectedOutput::Deterministic(expected) => expected.accepts(actual),
+                _ => unreachable!(),
+            }
+        };
+
+        assert!(accepts(&suite, "1 2 \n\n"));
+        assert!(!accepts(&suite, "1  2\n"));
+
+        suite.r#match = Match::Exact;
+        assert!(!accepts(&suite, "1 2 \n"));
+        assert!(accepts(&suite, "1 2\n"));
+
+        suite.r#match = Match::Lines;
+        suite.encoding = Some(TextEncoding::Binary);
+        assert!(!accepts(&suite, "1 2 \n"));
+    }
+
+    #[test]
+    fn exact_keeps_final_newline() {
+        let suite = serde_yaml::from_str::<BatchTestSuite>(
+            r#"---
+match: Exact
+cases:
+  - in: ""
+    out: "1 2\n"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(Whitespace::default(), suite.whitespace);
+
+        let cases = suite
+            .load_test_cases(Path::new(""), None::<HashSet<String>>, |_| unreachable!())
+            .unwrap();
+        let expected = match &cases[0].output {
+            ExpectedOutput::Deterministic(expected) => expected,
+            _ => unreachable!(),
+        };
+
+        assert!(expected.accepts("1 2\n"));
+        assert!(!expected.accepts("1 2"));
+        assert!(!expected.accepts("1 2\n\n"));
+    }
+
+    #[test]
+    fn crlf() {
+        let accepts = |r#match: &str, actual: &str| {
+            let suite = serde_yaml::from_str::<BatchTestSuite>(&format!(
+                "match: {}\ncases:\n  - in: \"\"\n    out: \"1 2\\n3\\n\"\n",
+                r#match,
+            ))
+            .unwrap();
+            let cases = suite
+                .load_test_cases(Path::new(""), None::<HashSet<String>>, |_| unreachable!())
+                .unwrap();
+            match &cases[0].output {
+                ExpectedOutput::Deterministic(expected) => expected.accepts(actual),
+                _ => unreachable!(),
+            }
+        };
+
+        for r#match in &["SplitWhitespace", "Lines", "Subsequence"] {
+            assert!(accepts(r#match, "1 2\r\n3\r\n"), "{}", r#match);
+        }
+        assert!(!accepts("Exact", "1 2\r\n3\r\n"));
+    }
+
+    #[test]
+    fn resolve_presets() {
+        let mut suite = serde_yaml::from_str::<BatchTestSuite>(
+            r#"---
+match:
+  Preset: geometry
+cases:
+  - in: ""
+    match: Exact
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(vec!["geometry"], suite.presets().collect::<Vec<_>>());
+
+        let presets = maplit::btreemap!(
+            "geometry".to_owned() => Match::Float {
+                relative_error: None,
+                absolute_error: Some(PositiveFinite(1e-6)),
+            },
+        );
+
+        suite.resolve_presets(&presets).unwrap();
+        assert_eq!(presets["geometry"], suite.r#match);
+        assert_eq!(Some(Match::Exact), suite.cases[0].r#match);
+
+        suite.override_match(Match::Preset("lines".to_owned()));
+        assert_eq!(None, suite.cases[0].r#match);
+        let err = suite.resolve_presets(&presets).unwrap_err();
+        assert_eq!(
+            "No such preset: `lines` (expected one of [geometry])",
+            err.to_string(),
+        );
+    }
+
     #[test]
     fn atcoder_arc071_c() {
         test_serialize_deserialize(
@@ -755,13 +1837,17 @@ extend: []
 "#,
             &TestSuite::Batch(BatchTestSuite {
                 timelimit: Some(Duration::from_secs(2)),
+                memorylimit: None,
                 r#match: Match::Lines,
+                whitespace: Default::default(),
+                encoding: None,
                 cases: vec![
                     PartialBatchTestCase {
                         name: Some("Sample 1".to_owned()),
                         r#in: "3\ncbaa\ndaacc\nacacac\n".into(),
                         out: Some("aac\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                     PartialBatchTestCase {
@@ -769,6 +1855,7 @@ extend: []
                         r#in: "3\na\naa\nb\n".into(),
                         out: Some("\n".into()),
                         timelimit: None,
+                        memorylimit: None,
                         r#match: None,
                     },
                 ],
@@ -777,6 +1864,133 @@ extend: []
         );
     }
 
+    #[test]
+    fn append_cases_to_yaml() {
+        let case = |name: &str, r#in: &str| PartialBatchTestCase {
+            name: Some(name.to_owned()),
+            r#in: r#in.into(),
+            out: None,
+            timelimit: None,
+            memorylimit: None,
+            r#match: None,
+        };
+
+        let append = |original: &str, cases| {
+            let mut suite = serde_yaml::from_str::<TestSuite>(original).unwrap();
+            if let TestSuite::Batch(suite) = &mut suite {
+                suite.cases.extend(cases);
+            }
+            suite.append_cases_to_yaml(original)
+        };
+
+        let original = r#"# https://atcoder.jp/contests/practice/tasks/practice_1
+type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+# From the statement.
+- name: Sample 1
+  in: |
+    1
+  out: |
+    1 # not a comment
+
+# Extends nothing.
+extend: []
+"#;
+        assert_diff!(
+            r#"# https://atcoder.jp/contests/practice/tasks/practice_1
+type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+# From the statement.
+- name: Sample 1
+  in: |
+    1
+  out: |
+    1 # not a comment
+- name: custom
+  in: |
+    2
+
+# Extends nothing.
+extend: []
+"#,
+            &append(original, vec![case("custom", "2\n")]).unwrap(),
+            "\n",
+            0
+        );
+
+        let original = "type: Batch # Only `cases` is edited.\ntimelimit: ~\nmatch: Exact\ncases: [] # none yet\n";
+        assert_diff!(
+            "type: Batch # Only `cases` is edited.\ntimelimit: ~\nmatch: Exact\ncases: # none yet\n  - name: custom\n    in: |\n      2\n",
+            &append(original, vec![case("custom", "2\n")]).unwrap(),
+            "\n",
+            0
+        );
+
+        let original = "type: Batch\ntimelimit: ~\nmatch: Exact\ncases: [{ name: a, in: '' }]\n";
+        assert_eq!(None, append(original, vec![case("custom", "2\n")]));
+    }
+
+    #[test]
+    fn case_text_file() -> eyre::Result<()> {
+        let yaml = r#"---
+type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+  - name: large
+    in:
+      file: large.in
+    out:
+      file: "./large.out"
+  - name: missing
+    in:
+      file: missing.in
+
+extend: []
+"#;
+
+        let suite = serde_yaml::from_str::<TestSuite>(yaml)?;
+        assert_diff!(yaml, &suite.to_yaml_pretty(), "\n", 0);
+
+        let suite = match suite {
+            TestSuite::Batch(suite) => suite,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            CaseText::File {
+                file: "large.in".into(),
+            },
+            suite.cases[0].r#in,
+        );
+
+        let dir = tempfile::Builder::new()
+            .prefix("snowchains-core-testsuite-test-")
+            .tempdir()?;
+        std::fs::write(dir.path().join("large.in"), "1 2\n")?;
+        std::fs::write(dir.path().join("large.out"), "3\n")?;
+
+        let cases = suite.load_test_cases(dir.path(), Some(hashset!("large")), |_| Ok(vec![]))?;
+        assert_eq!("", &*cases[0].input);
+        assert_eq!(Some(dir.path().join("large.in")), cases[0].input_file);
+        assert_eq!(Some("3\n"), cases[0].output.expected_stdout());
+
+        let err = suite
+            .load_test_cases(dir.path(), None::<HashSet<&str>>, |_| Ok(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            r#"Could not load `in` of the test case "missing""#,
+            err.to_string(),
+        );
+        Ok(())
+    }
+
     fn test_serialize_deserialize(yaml: &str, expected: &TestSuite) {
         let actual = serde_yaml::from_str::<TestSuite>(yaml).unwrap();
         assert_eq!(*expected, actual);
@@ -837,6 +2051,21 @@ extend: []
         }
         .accepts("1\n2\n"));
 
+        assert!(DeterministicExpectedOutput::Subsequence {
+            text: "3\n4\n".into()
+        }
+        .accepts("debug\n3\ndebug\n4\n"));
+
+        assert!(!DeterministicExpectedOutput::Subsequence {
+            text: "3\n4\n".into()
+        }
+        .accepts("4\n3\n"));
+
+        assert!(!DeterministicExpectedOutput::Subsequence {
+            text: "3\n4\n".into()
+        }
+        .accepts("3\n"));
+
         assert!(DeterministicExpectedOutput::Float {
             text: "10000.0\n".into(),
             relative_error: Some(PositiveFinite(0.01)),
@@ -850,5 +2079,96 @@ extend: []
             absolute_error: None,
         }
         .accepts("0\n"));
+
+        let float = |text: &str| DeterministicExpectedOutput::Float {
+            text: text.into(),
+            relative_error: None,
+            absolute_error: Some(PositiveFinite(1e-6)),
+        };
+
+        assert!(!float("1 2\n").accepts("1\n"));
+        assert!(float("nan inf -inf\n").accepts("nan inf -inf\n"));
+        assert!(!float("inf\n").accepts("1e400\n"));
+        assert!(!float("12345678901234567890\n").accepts("12345678901234567891\n"));
+        assert!(float("1.0000000000000000001\n").accepts("1\n"));
+        assert!(float("3.0\n").tokens_eq("3.0", "3.0000001"));
+        assert!(!float("3.0\n").tokens_eq("3.0", "3.1"));
+
+        assert!(DeterministicExpectedOutput::Normalized {
+            inner: Box::new(DeterministicExpectedOutput::Lines {
+                text: "Yes\nb a\n".into()
+            }),
+            unordered: true,
+            case_insensitive: true,
+            whitespace: Whitespace::KEEP,
+        }
+        .accepts("B A\nyes\n"));
+
+        assert!(!DeterministicExpectedOutput::Normalized {
+            inner: Box::new(DeterministicExpectedOutput::Lines {
+                text: "Yes\nb a\n".into()
+            }),
+            unordered: true,
+            case_insensitive: false,
+            whitespace: Whitespace::KEEP,
+        }
+        .accepts("b a\nyes\n"));
+
+        assert!(!DeterministicExpectedOutput::Normalized {
+            inner: Box::new(DeterministicExpectedOutput::Lines {
+                text: "1\n2\n".into()
+            }),
+            unordered: false,
+            case_insensitive: true,
+            whitespace: Whitespace::KEEP,
+        }
+        .accepts("2\n1\n"));
+
+        let normalized = |text: &str, whitespace| DeterministicExpectedOutput::Normalized {
+            inner: Box::new(DeterministicExpectedOutput::Exact { text: text.into() }),
+            unordered: false,
+            case_insensitive: false,
+            whitespace,
+        };
+
+        assert!(normalized("1 2\n", Whitespace::default()).accepts("1 2"));
+        assert!(normalized("1 2", Whitespace::default()).accepts("1 2\n\n"));
+        assert!(!normalized("1 2\n", Whitespace::default()).accepts("1 2 \n"));
+        assert!(!normalized("1 2\n", Whitespace::KEEP).accepts("1 2"));
+
+        let lenient = Whitespace {
+            trim_trailing: true,
+            collapse_spaces: true,
+            ignore_trailing_blank_lines: true,
+        };
+        assert!(normalized("1 2\n3\n", lenient).accepts("1  \t2 \n3\t\n\n"));
+        assert!(!normalized("1 2\n3\n", lenient).accepts("1 2\n\n3\n"));
+    }
+
+    #[test]
+    fn byte_size_to_human_readable() {
+        let to_human_readable = |n| ByteSize::from_bytes(n).to_human_readable();
+        assert_eq!("1000 B", to_human_readable(1000));
+        assert_eq!("1.5 KiB", to_human_readable(1536));
+        assert_eq!("256.0 MiB", to_human_readable(256 << 20));
+        assert_eq!("1.00 GiB", to_human_readable(1 << 30));
+    }
+
+    #[test]
+    fn whitespace_normalize() {
+        assert_eq!("", Whitespace::default().normalize("\n\n"));
+        assert_eq!("a \n", Whitespace::default().normalize("a \n\n"));
+
+        let trim = Whitespace {
+            trim_trailing: true,
+            ..Whitespace::KEEP
+        };
+        assert_eq!("a\n b", trim.normalize("a \t\r\n b "));
+
+        let collapse = Whitespace {
+            collapse_spaces: true,
+            ..Whitespace::KEEP
+        };
+        assert_eq!(" a b \n", collapse.normalize("  a\t \tb  \n"));
     }
 }
@@ -0,0 +1,423 @@
+use crate::{
+    testsuite::{BatchTestSuite, ByteSize, Match, PartialBatchTestCase, TestSuite},
+    web::{
+        aoj::api::SessionMutExt as _, Exec, Platform, Progress, RetrieveFullTestCases,
+        RetrieveTestCases, RetrieveTestCasesOutcome, RetrieveTestCasesOutcomeProblem,
+        RetrieveTestCasesOutcomeProblemTextFiles, Session, Shell,
+    },
+};
+use easy_ext::ext;
+use eyre::bail;
+use indexmap::{indexmap, IndexMap};
+use itertools::Itertools as _;
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html};
+use std::{collections::BTreeSet, convert::Infallible, time::Duration};
+use url::Url;
+
+static BASE_URL: Lazy<Url> = lazy_url!("https://onlinejudge.u-aizu.ac.jp");
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Aoj {}
+
+impl Aoj {
+    pub fn exec<A>(args: A) -> eyre::Result<<Self as Exec<A>>::Output>
+    where
+        Self: Exec<A>,
+    {
+        <Self as Exec<_>>::exec(args)
+    }
+}
+
+impl Platform for Aoj {
+    type CookieStorage = ();
+    type LoginCredentials = Infallible;
+    type ParticipateTarget = Infallible;
+    type ParticipateCredentials = Infallible;
+    type RetrieveLanguagesTarget = Infallible;
+    type RetrieveLanguagesCredentials = Infallible;
+    type RetrieveTestCasesTargets = AojRetrieveTestCasesTargets;
+    type RetrieveTestCasesCredentials = ();
+    type RetrieveFullTestCasesCredentials = ();
+    type RetrieveSubmissionSummariesTarget = Infallible;
+    type RetrieveSubmissionSummariesCredentials = Infallible;
+    type RetrieveSolvedProblemsTarget = Infallible;
+    type RetrieveSolvedProblemsCredentials = Infallible;
+    type RetrieveSubmissionsTarget = Infallible;
+    type RetrieveSubmissionsCredentials = Infallible;
+    type WatchSubmissionsTarget = Infallible;
+    type WatchSubmissionsCredentials = Infallible;
+    type SubmitTarget = Infallible;
+    type SubmitCredentials = Infallible;
+}
+
+impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Aoj {
+    type Output = RetrieveTestCasesOutcome;
+
+    fn exec(args: RetrieveTestCases<Self, S>) -> eyre::Result<RetrieveTestCasesOutcome> {
+        let RetrieveTestCases {
+            targets,
+            credentials: (),
+            full,
+            cookie_storage: (),
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            shell,
+        } = args;
+
+        let mut sess = Session::new(timeout, retry, min_interval, proxy, None, shell)?;
+
+        let problem_ids = match targets {
+            AojRetrieveTestCasesTargets::ProblemIds(problem_ids) => problem_ids,
+            AojRetrieveTestCasesTargets::Urls(urls) => urls
+                .iter()
+                .map(problem_id_from_url)
+                .collect::<eyre::Result<_>>()?,
+        };
+
+        let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
+
+        for (position, problem_id) in problem_ids.iter().enumerate() {
+            sess.shell.on_progress(Progress::Retrieving {
+                name: problem_id,
+                position,
+                total: problem_ids.len(),
+            })?;
+
+            let api::Problem {
+                name,
+                problem_time_limit,
+                problem_memory_limit,
+            } = sess.get_problem(problem_id)?;
+
+            let api::Description { html } = sess.get_description(problem_id)?;
+            let html = Html::parse_fragment(&html);
+
+            let mut test_suite = BatchTestSuite {
+                timelimit: Some(Duration::from_secs(problem_time_limit)),
+                memorylimit: Some(ByteSize::from_bytes(problem_memory_limit * 1024)),
+                r#match: Match::Lines,
+                whitespace: Default::default(),
+                encoding: None,
+                cases: vec![],
+                extend: vec![],
+            };
+
+            let samples = sess.get_samples(problem_id)?;
+
+            test_suite.cases = if samples.is_empty() {
+                // Old problems have their samples only in the statements.
+                html.extract_samples()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (input, output))| PartialBatchTestCase {
+                        name: Some(format!("sample{}", i + 1)),
+                        r#in: input.into(),
+                        out: Some(output.into()),
+                        timelimit: None,
+                        memorylimit: None,
+                        r#match: None,
+                    })
+                    .collect()
+            } else {
+                samples
+                    .into_iter()
+                    .map(|api::Sample { serial, r#in, out }| PartialBatchTestCase {
+                        name: Some(format!("sample{}", serial)),
+                        r#in: r#in.into(),
+                        out: Some(out.into()),
+                        timelimit: None,
+                        memorylimit: None,
+                        r#match: None,
+                    })
+                    .collect()
+            };
+
+            if test_suite.cases.is_empty() {
+                sess.shell
+                    .warn(format!("{}: Could not find any sample cases", problem_id))?;
+            }
+
+            outcome.problems.push(RetrieveTestCasesOutcomeProblem {
+                contest: None,
+                index: problem_id.clone(),
+                url: url!("/problems/{}", problem_id),
+                screen_name: Some(problem_id.clone()),
+                display_name: name,
+                test_suite: TestSuite::Batch(test_suite),
+                text_files: indexmap!(),
+                statement: Some(html.root_element().html()),
+                full_error: None,
+            });
+        }
+
+        if let Some(RetrieveFullTestCases {
+            credentials: (),
+            jobs,
+            timeout: full_timeout,
+        }) = full
+        {
+            // Lists the test cases of all the problems first, so that they are downloaded at once.
+            let mut headers = vec![];
+
+            for outcome_problem in &mut outcome.problems {
+                match sess.get_headers(&outcome_problem.index) {
+                    Ok(api::Headers { headers: hs }) => headers.push(Some(hs)),
+                    Err(err) => {
+                        sess.shell
+                            .warn(format!("{}: {:#}", outcome_problem.index, err))?;
+                        outcome_problem.full_error = Some(format!("{:#}", err));
+                        headers.push(None);
+                    }
+                }
+            }
+
+            let mut dl_targets = vec![];
+            for (outcome_problem, headers) in outcome.problems.iter().zip_eq(&headers) {
+                for header in headers.iter().flatten() {
+                    for which in [api::Which::In, api::Which::Out] {
+                        let req =
+                            sess.get_test_case(&outcome_problem.index, header.serial, which)?;
+                        let name = format!("{}/{}/{}", outcome_problem.index, which, header.name);
+                        dl_targets.push((name, req));
+                    }
+                }
+            }
+
+            let mut contents = super::download_with_progress(
+                sess.shell.progress_draw_target(),
+                jobs,
+                full_timeout,
+                sess.retry,
+                sess.min_interval,
+                &sess.async_client,
+                dl_targets,
+            )?
+            .into_iter();
+
+            for (outcome_problem, headers) in outcome.problems.iter_mut().zip_eq(headers) {
+                let headers = match headers {
+                    Some(headers) => headers,
+                    None => continue,
+                };
+
+                // Consumes all of the contents for this problem even if some of them failed.
+                let text_files = headers
+                    .into_iter()
+                    .map(|api::Header { name, .. }| {
+                        let r#in = contents.next().expect("should have enough length");
+                        let out = contents.next().expect("should have enough length");
+                        Ok((
+                            name,
+                            RetrieveTestCasesOutcomeProblemTextFiles {
+                                r#in: r#in?,
+                                out: Some(out?),
+                            },
+                        ))
+                    })
+                    .collect::<Vec<eyre::Result<_>>>();
+                let text_files = text_files
+                    .into_iter()
+                    .collect::<eyre::Result<IndexMap<_, _>>>();
+
+                match text_files {
+                    Ok(text_files) => outcome_problem.text_files.extend(text_files),
+                    Err(err) => {
+                        sess.shell
+                            .warn(format!("{}: {:#}", outcome_problem.index, err))?;
+                        outcome_problem.full_error = Some(format!("{:#}", err));
+                    }
+                }
+            }
+        }
+
+        super::report_retrieved(&mut sess.shell, &outcome)?;
+
+        Ok(outcome)
+    }
+}
+
+#[derive(Debug)]
+pub enum AojRetrieveTestCasesTargets {
+    /// Problem IDs such as `ITP1_1_A` and `0001`. Problems in courses have such IDs too.
+    ProblemIds(BTreeSet<String>),
+    Urls(BTreeSet<Url>),
+}
+
+fn problem_id_from_url(url: &Url) -> eyre::Result<String> {
+    match url.domain() {
+        Some("onlinejudge.u-aizu.ac.jp") => {
+            // `/problems/{id}` or `/courses/lesson/{n}/{course}/{topic}/{id}`
+            if static_regex!(r"\A/(problems|courses|challenges)/").is_match(url.path()) {
+                if let Some(id) = url.path_segments().and_then(|mut ss| ss.next_back()) {
+                    if !id.is_empty() {
+                        return Ok(id.to_owned());
+                    }
+                }
+            }
+        }
+        Some("judge.u-aizu.ac.jp") => {
+            if let Some((_, id)) = url.query_pairs().find(|(k, _)| k == "id") {
+                return Ok(id.into_owned());
+            }
+        }
+        _ => bail!("wrong domain. expected `onlinejudge.u-aizu.ac.jp`: {}", url),
+    }
+    bail!("not a URL for a problem in AOJ: {}", url);
+}
+
+#[ext]
+impl Html {
+    /// Pairs up the `<pre>`s following the headings such as "Sample Input 1" and "出力例 1".
+    fn extract_samples(&self) -> Vec<(String, String)> {
+        let mut samples = vec![];
+        let mut input = None;
+        let mut next = None;
+
+        for elem in self.select(static_selector!("h1, h2, h3, h4, h5, h6, pre")) {
+            if elem.value().name() != "pre" {
+                let heading = elem.text().collect::<String>().to_lowercase();
+                next = if heading.contains("sample input") || heading.contains("入力例") {
+                    Some(true)
+                } else if heading.contains("sample output") || heading.contains("出力例") {
+                    Some(false)
+                } else {
+                    None
+                };
+            } else if let Some(is_input) = next.take() {
+                let text = elem.pre_text();
+                if is_input {
+                    input = Some(text);
+                } else if let Some(input) = input.take() {
+                    samples.push((input, text));
+                }
+            }
+        }
+
+        return samples;
+
+        #[ext]
+        impl ElementRef<'_> {
+            fn pre_text(&self) -> String {
+                let text = self.text().collect::<String>();
+                let text = text.trim_start_matches('\n');
+                if text.ends_with('\n') {
+                    text.to_owned()
+                } else {
+                    format!("{}\n", text)
+                }
+            }
+        }
+    }
+}
+
+mod api {
+    //! <http://developers.u-aizu.ac.jp/index>
+
+    use crate::web::{ResponseExt as _, SessionMut};
+    use eyre::ContextCompat as _;
+    use once_cell::sync::Lazy;
+    use serde::{de::DeserializeOwned, Deserialize};
+    use url::Url;
+
+    static JUDGEAPI_URL: Lazy<Url> = lazy_url!("https://judgeapi.u-aizu.ac.jp/");
+    static JUDGEDAT_URL: Lazy<Url> = lazy_url!("https://judgedat.u-aizu.ac.jp/");
+
+    pub(super) trait SessionMutExt: SessionMut {
+        /// > Find a problem by problem id.
+        fn get_problem(&mut self, problem_id: &str) -> eyre::Result<Problem> {
+            let url = JUDGEAPI_URL.join(&format!("problems/{}", problem_id))?;
+            get_json(self, url)
+        }
+
+        /// > Find a problem description by problem id and language.
+        fn get_description(&mut self, problem_id: &str) -> eyre::Result<Description> {
+            let url = JUDGEAPI_URL.join(&format!("resources/descriptions/en/{}", problem_id))?;
+            get_json(self, url)
+        }
+
+        /// > Find sample testcases by problem id.
+        ///
+        /// Empty for the problems whose samples are only in the statements.
+        fn get_samples(&mut self, problem_id: &str) -> eyre::Result<Vec<Sample>> {
+            let url = JUDGEDAT_URL.join(&format!("testcases/samples/{}", problem_id))?;
+            get_json(self, url)
+        }
+
+        /// > Find headers of testcases by problem id.
+        fn get_headers(&mut self, problem_id: &str) -> eyre::Result<Headers> {
+            let url = JUDGEDAT_URL.join(&format!("testcases/{}/header", problem_id))?;
+            get_json(self, url)
+        }
+
+        /// > Find a testcase input (or output) by problem id and serial.
+        fn get_test_case(
+            &self,
+            problem_id: &str,
+            serial: u64,
+            which: Which,
+        ) -> Result<reqwest::RequestBuilder, url::ParseError> {
+            let url =
+                JUDGEDAT_URL.join(&format!("testcases/{}/{}/{}", problem_id, serial, which))?;
+            Ok(self.async_client().get(url))
+        }
+    }
+
+    impl<S: SessionMut> SessionMutExt for S {}
+
+    fn get_json<T: DeserializeOwned>(sess: &mut impl SessionMut, url: Url) -> eyre::Result<T> {
+        let res = sess
+            .get(url.clone())
+            .colorize_status_code(&[200], (), ..)
+            .send()?
+            .ensure_status(&[200, 404])?;
+
+        if res.status() == 404 {
+            return None.with_context(|| format!("Not found: {}", url));
+        }
+        res.json().map_err(Into::into)
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub(super) struct Problem {
+        //pub(super) id: String,
+        pub(super) name: String,
+        /// In seconds.
+        pub(super) problem_time_limit: u64,
+        /// In KiB.
+        pub(super) problem_memory_limit: u64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Description {
+        pub(super) html: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Sample {
+        pub(super) serial: u64,
+        pub(super) r#in: String,
+        pub(super) out: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Headers {
+        pub(super) headers: Vec<Header>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Header {
+        pub(super) serial: u64,
+        pub(super) name: String,
+    }
+
+    #[derive(Debug, Clone, Copy, strum::Display)]
+    #[strum(serialize_all = "lowercase")]
+    pub(super) enum Which {
+        In,
+        Out,
+    }
+}
@@ -1,16 +1,18 @@
 use crate::{
     testsuite::{
-        BatchTestSuite, InteractiveTestSuite, Match, PartialBatchTestCase, PositiveFinite,
-        TestSuite,
+        BatchTestSuite, ByteSize, InteractiveTestSuite, Match, PartialBatchTestCase,
+        PositiveFinite, TestSuite,
     },
     web::{
-        AnsiColored, CaseConverted, CookieStorage, Exec, Login, LoginOutcome, LowerCase,
-        Participate, ParticipateOutcome, Platform, ProblemInContest, ProblemsInContest,
-        ResponseExt as _, RetrieveFullTestCases, RetrieveLanguages, RetrieveLanguagesOutcome,
-        RetrieveSubmissionSummaries, RetrieveTestCases, RetrieveTestCasesOutcome,
-        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest,
-        RetrieveTestCasesOutcomeProblemTextFiles, Session, SessionMut, Shell, Submit,
-        SubmitOutcome, WatchSubmissions,
+        AlreadyAccepted, AnsiColored, CaseConverted, CookieStorage, Exec, Login, LoginOutcome,
+        LowerCase, NotLoggedIn, Participate, ParticipateOutcome, Platform, ProblemInContest,
+        ProblemsInContest, Progress, ResponseExt as _, RetrieveFullTestCases, RetrieveLanguages,
+        RetrieveLanguagesOutcome, RetrieveSolvedProblems, RetrieveSolvedProblemsOutcome,
+        RetrieveSolvedProblemsOutcomeProblem, RetrieveSubmissionSummaries, RetrieveSubmissions,
+        RetrieveSubmissionsOutcome, RetrieveSubmissionsOutcomeSubmission, RetrieveTestCases,
+        RetrieveTestCasesOutcome, RetrieveTestCasesOutcomeProblem,
+        RetrieveTestCasesOutcomeProblemContest, RetrieveTestCasesOutcomeProblemTextFiles, Session,
+        SessionMut, Shell, Submit, SubmitOutcome, WatchSubmissions,
     },
 };
 use chrono::{DateTime, FixedOffset, Local, Utc};
@@ -35,6 +37,7 @@ use std::{
     io,
     marker::PhantomData,
     mem,
+    num::NonZeroUsize,
     ops::Deref,
     path::Path,
     str::FromStr,
@@ -85,6 +88,10 @@ impl<'closures> Platform for Atcoder<'closures> {
     type RetrieveSubmissionSummariesTarget = AtcoderRetrieveSubmissionSummariesTarget;
     type RetrieveSubmissionSummariesCredentials =
         AtcoderRetrieveSubmissionSummariesCredentials<'closures>;
+    type RetrieveSolvedProblemsTarget = AtcoderRetrieveSolvedProblemsTarget;
+    type RetrieveSolvedProblemsCredentials = AtcoderRetrieveSolvedProblemsCredentials<'closures>;
+    type RetrieveSubmissionsTarget = AtcoderRetrieveSubmissionsTarget;
+    type RetrieveSubmissionsCredentials = AtcoderRetrieveSubmissionsCredentials<'closures>;
     type WatchSubmissionsTarget = AtcoderWatchSubmissionsTarget;
     type WatchSubmissionsCredentials = AtcoderWatchSubmissionsCredentials<'closures>;
     type SubmitTarget = ProblemInContest;
@@ -102,10 +109,20 @@ impl<S: Shell> Exec<Login<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         if check_logged_in(&mut sess)? {
             Ok(LoginOutcome::AlreadyLoggedIn)
@@ -128,11 +145,21 @@ impl<S: Shell> Exec<Participate<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
         let contest = CaseConverted::new(contest);
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
         participate(sess, username_and_password, &contest, true)
     }
 }
@@ -149,6 +176,9 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
@@ -161,7 +191,14 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Atcoder<'_> {
             (CaseConverted::<LowerCase>::new("practice"), None)
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         if !check_logged_in(&mut sess)? {
             login(&mut sess, username_and_password)?;
@@ -203,18 +240,31 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
             full,
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         let mut outcome = retrieve_sample_test_cases(&mut sess, username_and_password, &targets)?;
 
         if let Some(RetrieveFullTestCases {
             credentials:
                 AtcoderRetrieveFullTestCasesCredentials {
-                    dropbox_access_token,
+                    mut dropbox_access_token,
+                    mut dropbox_refresh,
                 },
+            jobs,
+            timeout: full_timeout,
         }) = full
         {
             static DROPBOX_PATH_PREFIXES: Lazy<HashMap<String, String>> = Lazy::new(|| {
@@ -222,6 +272,9 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                     .unwrap()
             });
 
+            // Lists the files of all the problems first, so that they are downloaded at once.
+            let mut file_paths = vec![];
+
             for problem in &mut outcome.problems {
                 let path_prefix = {
                     let contest = &problem.contest.as_ref().expect("should be `Some`").id;
@@ -231,53 +284,115 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                         .unwrap_or_else(|| format!("/{}/", contest))
                 };
 
-                let problem_dir = format!("{}{}", path_prefix, problem.index);
-
-                let problem_dir_entries =
-                    list_paths_with_name_filter(&mut sess, &dropbox_access_token, &problem_dir)?;
+                let mut list_problem_dir = || -> eyre::Result<_> {
+                    let problem_dir = format!("{}{}", path_prefix, problem.index);
+
+                    let problem_dir_entries = list_paths_with_name_filter(
+                        &mut sess,
+                        &mut dropbox_access_token,
+                        &mut dropbox_refresh,
+                        &problem_dir,
+                    )?;
+
+                    let mut list_file_paths = |in_out_dir_file_name: &'static str| -> _ {
+                        if problem_dir_entries.has_folder(in_out_dir_file_name) {
+                            list_paths_with_name_filter(
+                                &mut sess,
+                                &mut dropbox_access_token,
+                                &mut dropbox_refresh,
+                                &format!("{}/{}", problem_dir, in_out_dir_file_name),
+                            )
+                            .map(|es| es.files())
+                        } else {
+                            Ok(vec![])
+                        }
+                    };
 
-                let mut list_file_paths = |in_out_dir_file_name: &'static str| -> _ {
-                    if problem_dir_entries.has_folder(in_out_dir_file_name) {
-                        list_paths_with_name_filter(
-                            &mut sess,
-                            &dropbox_access_token,
-                            &format!("{}{}/{}", path_prefix, problem.index, in_out_dir_file_name),
-                        )
-                        .map(|es| es.files())
-                    } else {
-                        Ok(vec![])
-                    }
+                    Ok(match *problem_dir_entries.folder_names() {
+                        ["in", "out"] => (list_file_paths("in")?, list_file_paths("out")?),
+                        ["in"] => (list_file_paths("in")?, vec![]),
+                        ["out"] => (problem_dir_entries.files(), list_file_paths("out")?),
+                        [] => (problem_dir_entries.files(), vec![]),
+                        _ => bail!(
+                            "unexpected format (path-prefix: {:?}, files: {:?}, folders: {:?})",
+                            path_prefix,
+                            problem_dir_entries.files(),
+                            problem_dir_entries.folders(),
+                        ),
+                    })
                 };
 
-                let (in_file_paths, out_file_paths) = match *problem_dir_entries.folder_names() {
-                    ["in", "out"] => (list_file_paths("in")?, list_file_paths("out")?),
-                    ["in"] => (list_file_paths("in")?, vec![]),
-                    ["out"] => (problem_dir_entries.files(), list_file_paths("out")?),
-                    [] => (problem_dir_entries.files(), vec![]),
-                    _ => bail!(
-                        "unexpected format (path-prefix: {:?}, files: {:?}, folders: {:?})",
-                        path_prefix,
-                        problem_dir_entries.files(),
-                        problem_dir_entries.folders(),
-                    ),
-                };
+                match list_problem_dir() {
+                    Ok(paths) => file_paths.push(Some(paths)),
+                    Err(err) => {
+                        sess.shell().warn(format!("{}: {:#}", problem.index, err))?;
+                        problem.full_error = Some(format!("{:#}", err));
+                        file_paths.push(None);
+                    }
+                }
+            }
 
-                let mut retrieve_files = |file_paths| -> eyre::Result<_> {
-                    retrieve_files(&mut sess, &dropbox_access_token, file_paths)
+            let mut contents = retrieve_files(
+                &mut sess,
+                &dropbox_access_token,
+                jobs,
+                full_timeout,
+                &file_paths
+                    .iter()
+                    .flatten()
+                    .flat_map(|(ins, outs)| ins.iter().chain(outs))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )?
+            .into_iter();
+
+            for (problem, paths) in outcome.problems.iter_mut().zip_eq(file_paths) {
+                let (in_file_paths, out_file_paths) = match paths {
+                    Some(paths) => paths,
+                    None => continue,
                 };
-                let in_contents = retrieve_files(&in_file_paths)?;
-                let mut out_contents = retrieve_files(&out_file_paths)?;
 
-                problem.text_files = in_contents
-                    .into_iter()
-                    .map(|(name, r#in)| {
-                        let out = out_contents.remove(&name);
-                        (name, RetrieveTestCasesOutcomeProblemTextFiles { r#in, out })
-                    })
-                    .collect();
+                let in_contents = contents
+                    .by_ref()
+                    .take(in_file_paths.len())
+                    .collect::<Vec<_>>();
+                let out_contents = contents
+                    .by_ref()
+                    .take(out_file_paths.len())
+                    .collect::<Vec<_>>();
+
+                let text_files = (|| -> eyre::Result<_> {
+                    let mut out_contents = out_file_paths
+                        .iter()
+                        .map(file_stem)
+                        .zip_eq(out_contents)
+                        .map(|(name, out)| Ok((name, out?)))
+                        .collect::<eyre::Result<HashMap<_, _>>>()?;
+
+                    in_file_paths
+                        .iter()
+                        .map(file_stem)
+                        .zip_eq(in_contents)
+                        .map(|(name, r#in)| {
+                            let r#in = r#in?;
+                            let out = out_contents.remove(&name);
+                            Ok((name, RetrieveTestCasesOutcomeProblemTextFiles { r#in, out }))
+                        })
+                        .collect()
+                })();
+
+                match text_files {
+                    Ok(text_files) => problem.text_files = text_files,
+                    Err(err) => {
+                        sess.shell().warn(format!("{}: {:#}", problem.index, err))?;
+                        problem.full_error = Some(format!("{:#}", err));
+                    }
+                }
             }
         }
 
+        super::report_retrieved(sess.shell(), &outcome)?;
+
         return Ok(outcome);
 
         static URL: &str =
@@ -287,9 +402,9 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
 
         impl Entries {
             fn has_folder(&self, name: &str) -> bool {
-                self.0
-                    .iter()
-                    .any(|e| matches!(e, Either::Right(s) if s.split('/').last().unwrap() == name))
+                self.0.iter().any(
+                    |e| matches!(e, Either::Right(s) if s.split('/').next_back().unwrap() == name),
+                )
             }
 
             fn files(&self) -> Vec<String> {
@@ -310,14 +425,15 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                 self.0
                     .iter()
                     .flat_map(|e| e.as_ref().right().map(Deref::deref))
-                    .map(|p| p.split('/').last().unwrap())
+                    .map(|p| p.split('/').next_back().unwrap())
                     .collect()
             }
         }
 
         fn list_paths_with_name_filter(
             mut sess: impl SessionMut,
-            access_token: &str,
+            access_token: &mut String,
+            refresh: &mut Option<AtcoderDropboxRefresh>,
             path: &str,
         ) -> eyre::Result<Entries> {
             #[derive(Deserialize)]
@@ -368,13 +484,36 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                 Ok(res)
             }
 
-            let res = sess
-                .post(static_url!("https://api.dropboxapi.com/2/files/list_folder").clone())
-                .bearer_auth(access_token)
-                .json(&json!({ "shared_link": { "url": URL }, "path": path }))
-                .colorize_status_code(&[200], (), ..)
-                .send()?
-                .ensure_status(&[200, 400, 409])?;
+            // Short-lived access tokens expire in a few hours. When one does, renew it once
+            // with the refresh token and send the same request again.
+            let mut list_folder = |url: &Url, body: serde_json::Value| -> eyre::Result<_> {
+                let mut refreshed = false;
+                loop {
+                    let res = sess
+                        .post(url.clone())
+                        .bearer_auth(&*access_token)
+                        .json(&body)
+                        .colorize_status_code(&[200], (), ..)
+                        .send()?
+                        .ensure_status(&[200, 400, 401, 409])?;
+
+                    if res.status() == 401 && !refreshed {
+                        if let Some(refresh) = refresh {
+                            sess.shell()
+                                .warn("The Dropbox access token has expired. Refreshing it")?;
+                            *access_token = refresh_access_token(&mut sess, refresh)?;
+                            refreshed = true;
+                            continue;
+                        }
+                    }
+                    break Ok(res);
+                }
+            };
+
+            let res = list_folder(
+                static_url!("https://api.dropboxapi.com/2/files/list_folder"),
+                json!({ "shared_link": { "url": URL }, "path": path }),
+            )?;
 
             let res = ensure_status_ok(res, || {
                 format!("could not retrieve file names in `{}`", path)
@@ -387,16 +526,10 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
             while list_folder_result.has_more {
                 debug_assert!(list_folder_result.entries.is_empty());
 
-                let res = sess
-                    .post(
-                        static_url!("https://api.dropboxapi.com/2/files/list_folder/continue")
-                            .clone(),
-                    )
-                    .bearer_auth(access_token)
-                    .json(&json!({ "cursor": &list_folder_result.cursor }))
-                    .colorize_status_code(&[200], (), ..)
-                    .send()?
-                    .ensure_status(&[200, 400, 409])?;
+                let res = list_folder(
+                    static_url!("https://api.dropboxapi.com/2/files/list_folder/continue"),
+                    json!({ "cursor": &list_folder_result.cursor }),
+                )?;
 
                 let res = ensure_status_ok(res, || {
                     format!(
@@ -424,13 +557,51 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                 .map(Entries)
         }
 
+        fn refresh_access_token(
+            mut sess: impl SessionMut,
+            refresh: &mut AtcoderDropboxRefresh,
+        ) -> eyre::Result<String> {
+            #[derive(Deserialize)]
+            struct Token {
+                access_token: String,
+            }
+
+            let mut form = vec![
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &refresh.refresh_token),
+                ("client_id", &refresh.app_key),
+            ];
+            if let Some(app_secret) = &refresh.app_secret {
+                form.push(("client_secret", app_secret));
+            }
+
+            let Token { access_token } = sess
+                .post(static_url!("https://api.dropboxapi.com/oauth2/token").clone())
+                .form(&form)
+                .colorize_status_code(&[200], (), ..)
+                .send()?
+                .ensure_status(&[200])
+                .wrap_err("Could not refresh the Dropbox access token")?
+                .json()?;
+
+            (refresh.on_refresh)(&access_token)?;
+            Ok(access_token)
+        }
+
         fn retrieve_files(
             mut sess: impl SessionMut,
             access_token: &str,
+            jobs: Option<NonZeroUsize>,
+            timeout: Option<Duration>,
             file_paths: &[String],
-        ) -> eyre::Result<IndexMap<String, String>> {
-            let contents = super::download_with_progress(
+        ) -> eyre::Result<Vec<eyre::Result<String>>> {
+            super::download_with_progress(
                 sess.shell().progress_draw_target(),
+                jobs,
+                timeout,
+                sess.retry(),
+                sess.min_interval(),
+                sess.async_client(),
                 file_paths
                     .iter()
                     .map(|path| {
@@ -445,20 +616,18 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Atcoder<'_> {
                         (path.clone(), req)
                     })
                     .collect(),
-            )?;
-
-            return Ok(file_paths.iter().map(file_stem).zip_eq(contents).collect());
+            )
+        }
 
-            fn file_stem(path: impl AsRef<str>) -> String {
-                path.as_ref()
-                    .split('/')
-                    .last()
-                    .unwrap()
-                    .split('.')
-                    .next()
-                    .unwrap()
-                    .to_owned()
-            }
+        fn file_stem(path: impl AsRef<str>) -> String {
+            path.as_ref()
+                .split('/')
+                .next_back()
+                .unwrap()
+                .split('.')
+                .next()
+                .unwrap()
+                .to_owned()
         }
     }
 }
@@ -477,12 +646,22 @@ impl<S: Shell> Exec<RetrieveSubmissionSummaries<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
         let contest = CaseConverted::<LowerCase>::new(contest);
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         let (mut summaries, num_pages) =
             retrieve_submission_summaries(&mut sess, &contest, 1, username_and_password)?;
@@ -498,6 +677,68 @@ impl<S: Shell> Exec<RetrieveSubmissionSummaries<Self, S>> for Atcoder<'_> {
     }
 }
 
+impl<S: Shell> Exec<RetrieveSolvedProblems<Self, S>> for Atcoder<'_> {
+    type Output = RetrieveSolvedProblemsOutcome;
+
+    fn exec(args: RetrieveSolvedProblems<Self, S>) -> eyre::Result<RetrieveSolvedProblemsOutcome> {
+        let RetrieveSolvedProblems {
+            target: AtcoderRetrieveSolvedProblemsTarget { contest, user },
+            credentials:
+                AtcoderRetrieveSolvedProblemsCredentials {
+                    username_and_password,
+                },
+            cookie_storage,
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            shell,
+        } = args;
+
+        let contest = CaseConverted::<LowerCase>::new(contest);
+
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
+
+        let tasks_page = retrieve_tasks_page(&mut sess, username_and_password, &contest)?;
+
+        // Problems can be submitted to only after the contest starts.
+        let from_second = tasks_page
+            .extract_contest_duration()
+            .map(|(start, _)| start.timestamp())
+            .unwrap_or(0);
+
+        let accepted =
+            atcoder_problems::retrieve_accepted_problem_ids(&mut sess, &user, from_second)?;
+
+        let problems = tasks_page
+            .extract_task_indexes_and_urls()?
+            .into_iter()
+            .map(|(index, url)| {
+                let screen_name = url
+                    .path_segments()
+                    .and_then(Iterator::last)
+                    .with_context(|| "Empty URL")?
+                    .to_owned();
+                Ok(RetrieveSolvedProblemsOutcomeProblem {
+                    accepted: accepted.contains(&screen_name),
+                    index,
+                    url,
+                    screen_name,
+                })
+            })
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(RetrieveSolvedProblemsOutcome { problems })
+    }
+}
+
 impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
     type Output = SubmitOutcome;
 
@@ -513,16 +754,29 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
             watch_submission,
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
+            watch_timeout,
+            refuse_if_accepted,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         let (contest, url) = match target {
             ProblemInContest::Index { contest, problem } => {
                 let contest = CaseConverted::<LowerCase>::new(contest);
 
-                let tasks_page = retrieve_tasks_page(&mut sess, username_and_password, &contest)?;
+                let tasks_page =
+                    retrieve_tasks_page(&mut sess, &mut *username_and_password, &contest)?;
 
                 let (_, url) = tasks_page
                     .extract_task_indexes_and_urls()?
@@ -538,11 +792,22 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
             }
         };
 
-        let problem_screen_name =
-            static_regex!(r"\A/contests/[a-zA-Z0-9_\-]+/tasks/([a-zA-Z0-9_\-]+)/?\z$")
-                .captures(url.path())
-                .map(|cs| cs[1].to_owned())
-                .with_context(|| "Could not extract screen name of the problem")?;
+        if !check_logged_in(&mut sess)? {
+            login(&mut sess, username_and_password)?;
+        }
+
+        let problem_screen_name = task_screen_name(&url)?;
+
+        if refuse_if_accepted {
+            if let Some(SubmissionSummary { detail, .. }) =
+                retrieve_latest_submission(&mut sess, &contest, &problem_screen_name, true)?
+            {
+                return Err(AlreadyAccepted {
+                    submission_url: detail,
+                }
+                .into());
+            }
+        }
 
         let csrf_token = sess
             .get(url)
@@ -564,6 +829,10 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
             .send()?
             .ensure_status(&[200, 302])?;
 
+        if let Some(login_url) = res.login_redirection() {
+            return Err(NotLoggedIn { login_url }.into());
+        }
+
         if res.status() == 302 {
             let loc = res.location_url()?;
 
@@ -580,7 +849,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
                 };
 
                 if watch_submission {
-                    watch_submissions(sess, &contest, &submission_summaries)?;
+                    watch_submissions(sess, &contest, &submission_summaries, watch_timeout)?;
                 }
 
                 Ok(outcome)
@@ -594,6 +863,104 @@ impl<S: Shell> Exec<Submit<Self, S>> for Atcoder<'_> {
     }
 }
 
+impl<S: Shell> Exec<RetrieveSubmissions<Self, S>> for Atcoder<'_> {
+    type Output = RetrieveSubmissionsOutcome;
+
+    fn exec(args: RetrieveSubmissions<Self, S>) -> eyre::Result<RetrieveSubmissionsOutcome> {
+        let RetrieveSubmissions {
+            target: AtcoderRetrieveSubmissionsTarget { contest, problems },
+            credentials:
+                AtcoderRetrieveSubmissionsCredentials {
+                    username_and_password,
+                },
+            cookie_storage,
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            shell,
+        } = args;
+
+        let contest = CaseConverted::<LowerCase>::new(contest);
+
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
+
+        if !check_logged_in(&mut sess)? {
+            login(&mut sess, &mut *username_and_password)?;
+        }
+
+        let tasks = retrieve_tasks_page(&mut sess, &mut *username_and_password, &contest)?
+            .extract_task_indexes_and_urls()?;
+
+        let names_by_id = sess
+            .get(url!("/contests/{}/submit", contest))
+            .colorize_status_code(&[200], (), ..)
+            .send()?
+            .ensure_status(&[200])?
+            .html()?
+            .extract_langs()?;
+
+        let mut problems =
+            problems.map(|ps| ps.iter().map(|p| p.to_uppercase()).collect::<BTreeSet<_>>());
+
+        let mut submissions = vec![];
+
+        for (index, url) in tasks {
+            if let Some(problems) = &mut problems {
+                if !problems.remove(&index.to_uppercase()) {
+                    continue;
+                }
+            }
+
+            let screen_name = task_screen_name(&url)?;
+
+            let SubmissionSummary {
+                language, detail, ..
+            } = match retrieve_latest_submission(&mut sess, &contest, &screen_name, false)? {
+                Some(summary) => summary,
+                None => continue,
+            };
+
+            let code = sess
+                .get(detail.clone())
+                .colorize_status_code(&[200], (), ..)
+                .send()?
+                .ensure_status(&[200])?
+                .html()?
+                .extract_submission_code()?;
+
+            let language_id = names_by_id
+                .iter()
+                .find(|(_, name)| **name == language)
+                .map(|(id, _)| id.clone());
+
+            submissions.push(RetrieveSubmissionsOutcomeSubmission {
+                problem_index: index,
+                problem_screen_name: Some(screen_name),
+                url: detail,
+                language_id,
+                language_name: language,
+                code,
+            });
+        }
+
+        if let Some(problems) = problems {
+            if !problems.is_empty() {
+                bail!("No such problem indices: {:?}", problems);
+            }
+        }
+
+        Ok(RetrieveSubmissionsOutcome { submissions })
+    }
+}
+
 impl<S: Shell> Exec<WatchSubmissions<Self, S>> for Atcoder<'_> {
     type Output = ();
 
@@ -606,12 +973,23 @@ impl<S: Shell> Exec<WatchSubmissions<Self, S>> for Atcoder<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
+            watch_timeout,
             mut shell,
         } = args;
 
         let contest = CaseConverted::<LowerCase>::new(contest);
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), &mut shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            &mut shell,
+        )?;
 
         let (summaries, _) =
             retrieve_submission_summaries(&mut sess, &contest, 1, username_and_password)?;
@@ -621,7 +999,7 @@ impl<S: Shell> Exec<WatchSubmissions<Self, S>> for Atcoder<'_> {
         });
 
         if any_incomplete {
-            watch_submissions(sess, &contest, &summaries)?;
+            watch_submissions(sess, &contest, &summaries, watch_timeout)?;
         } else {
             let content = AnsiColored::new(|w| print_submissions(w, &summaries))?;
             shell.print_ansi(content.get())?;
@@ -659,6 +1037,27 @@ pub struct AtcoderRetrieveSampleTestCasesCredentials<'closures> {
 #[derive(Debug)]
 pub struct AtcoderRetrieveFullTestCasesCredentials {
     pub dropbox_access_token: String,
+    pub dropbox_refresh: Option<AtcoderDropboxRefresh>,
+}
+
+/// Renews `dropbox_access_token` when Dropbox rejects it.
+#[allow(clippy::type_complexity)]
+pub struct AtcoderDropboxRefresh {
+    pub refresh_token: String,
+    pub app_key: String,
+    pub app_secret: Option<String>,
+    /// Called with the new access token, e.g. to save it.
+    pub on_refresh: Box<dyn FnMut(&str) -> eyre::Result<()>>,
+}
+
+impl fmt::Debug for AtcoderDropboxRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtcoderDropboxRefresh")
+            .field("refresh_token", &self.refresh_token)
+            .field("app_key", &self.app_key)
+            .field("app_secret", &self.app_secret)
+            .finish_non_exhaustive()
+    }
 }
 
 #[derive(Debug)]
@@ -682,6 +1081,28 @@ impl AtcoderRetrieveSubmissionSummariesOutcome {
     }
 }
 
+#[derive(Debug)]
+pub struct AtcoderRetrieveSolvedProblemsTarget {
+    pub contest: String,
+    /// User name on AtCoder Problems (the same as on AtCoder).
+    pub user: String,
+}
+
+pub struct AtcoderRetrieveSolvedProblemsCredentials<'closures> {
+    pub username_and_password: &'closures mut dyn FnMut() -> eyre::Result<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct AtcoderRetrieveSubmissionsTarget {
+    pub contest: String,
+    /// Indexes of the problems. `None` for all of the problems.
+    pub problems: Option<BTreeSet<String>>,
+}
+
+pub struct AtcoderRetrieveSubmissionsCredentials<'closures> {
+    pub username_and_password: &'closures mut dyn FnMut() -> eyre::Result<(String, String)>,
+}
+
 #[derive(Debug)]
 pub struct AtcoderWatchSubmissionsTarget {
     pub contest: String,
@@ -703,7 +1124,7 @@ fn retrieve_sample_test_cases(
     let problems = match targets.clone() {
         ProblemsInContest::Indexes { contest, problems } => {
             let contest = CaseConverted::<LowerCase>::new(contest);
-            let html = retrieve_tasks_page(&mut sess, username_and_password, &contest)?;
+            let html = retrieve_tasks_page(&mut sess, &mut username_and_password, &contest)?;
 
             let contest_display_name = html
                 .extract_title()?
@@ -774,14 +1195,37 @@ fn retrieve_sample_test_cases(
 
     let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
 
-    for (contest, (contest_display_name, mut indexes_and_urls)) in problems {
-        let test_suites = sess
-            .get(url!("/contests/{}/tasks_print", contest))
-            .colorize_status_code(&[200], (), ..)
-            .send()?
-            .ensure_status(&[200])?
-            .html()?
-            .extract_samples();
+    let total = problems.len();
+
+    for (position, (contest, (contest_display_name, mut indexes_and_urls))) in
+        problems.into_iter().enumerate()
+    {
+        sess.shell().on_progress(Progress::Retrieving {
+            name: &contest,
+            position,
+            total,
+        })?;
+
+        let retrieve_tasks_print = |sess: &mut _| -> eyre::Result<_> {
+            let html = SessionMut::get(sess, url!("/contests/{}/tasks_print", contest))
+                .colorize_status_code(&[200], (), ..)
+                .send()?
+                .ensure_status(&[200])?
+                .html()?;
+            Ok(html)
+        };
+
+        let html = match retrieve_tasks_print(&mut sess) {
+            Err(err) if err.is::<NotLoggedIn>() => {
+                sess.shell().warn(&err)?;
+                login(&mut sess, &mut username_and_password)?;
+                retrieve_tasks_print(&mut sess)?
+            }
+            html => html?,
+        };
+
+        let test_suites = html.extract_samples();
+        let mut statements = html.extract_statements();
 
         if indexes_and_urls.len() > test_suites.len() {
             sess.shell().warn(format!(
@@ -815,13 +1259,18 @@ fn retrieve_sample_test_cases(
 
                                 TestSuite::Batch(BatchTestSuite {
                                     timelimit: None,
+                                    memorylimit: None,
                                     r#match: Match::Lines,
+                                    whitespace: Default::default(),
+                                    encoding: None,
                                     cases: vec![],
                                     extend: vec![],
                                 })
                             }
                         };
 
+                        let statement = statements.remove(&index);
+
                         outcome.problems.push(RetrieveTestCasesOutcomeProblem {
                             contest: Some(contest.clone()),
                             url,
@@ -830,6 +1279,8 @@ fn retrieve_sample_test_cases(
                             display_name,
                             test_suite,
                             text_files: indexmap![],
+                            statement,
+                            full_error: None,
                         });
                     }
                 }
@@ -850,6 +1301,8 @@ fn login(
     mut sess: impl SessionMut,
     mut username_and_password: impl FnMut() -> eyre::Result<(String, String)>,
 ) -> eyre::Result<()> {
+    sess.shell().on_progress(Progress::LoginRequired)?;
+
     while {
         let (username, password) = username_and_password()?;
 
@@ -1003,6 +1456,102 @@ fn retrieve_submission_summaries(
     }
 }
 
+/// The newest submission for the problem, if any.
+///
+/// Not having participated in the contest is the same as having no submissions.
+fn retrieve_latest_submission(
+    mut sess: impl SessionMut,
+    contest: &CaseConverted<LowerCase>,
+    problem_screen_name: &str,
+    accepted: bool,
+) -> eyre::Result<Option<SubmissionSummary>> {
+    let mut url = url!("/contests/{}/submissions/me", contest);
+    url.query_pairs_mut()
+        .append_pair("f.Task", problem_screen_name);
+    if accepted {
+        url.query_pairs_mut().append_pair("f.Status", "AC");
+    }
+
+    let res = sess
+        .get(url)
+        .colorize_status_code(&[200], &[302], ..)
+        .send()?
+        .ensure_status(&[200, 302])?;
+
+    if res.status() == 302 {
+        return Ok(None);
+    }
+
+    let (summaries, _) = res.html()?.extract_submissions()?;
+    Ok(summaries
+        .into_iter()
+        .find(|s| !accepted || matches!(s.status, Verdict::Ac)))
+}
+
+fn task_screen_name(url: &Url) -> eyre::Result<String> {
+    static_regex!(r"\A/contests/[a-zA-Z0-9_\-]+/tasks/([a-zA-Z0-9_\-]+)/?\z$")
+        .captures(url.path())
+        .map(|cs| cs[1].to_owned())
+        .with_context(|| "Could not extract screen name of the problem")
+}
+
+/// <https://github.com/kenkoooo/AtCoderProblems/blob/master/doc/api.md>
+mod atcoder_problems {
+    use crate::web::{ResponseExt as _, SessionMut};
+    use serde::Deserialize;
+    use std::{collections::HashSet, thread, time::Duration};
+    use url::Url;
+
+    /// The API returns at most this number of submissions at once.
+    const MAX_SUBMISSIONS: usize = 500;
+
+    pub(super) fn retrieve_accepted_problem_ids(
+        mut sess: impl SessionMut,
+        user: &str,
+        mut from_second: i64,
+    ) -> eyre::Result<HashSet<String>> {
+        let mut accepted = HashSet::new();
+
+        loop {
+            let mut url = "https://kenkoooo.com/atcoder/atcoder-api/v3/user/submissions"
+                .parse::<Url>()
+                .unwrap();
+            url.query_pairs_mut()
+                .append_pair("user", user)
+                .append_pair("from_second", &from_second.to_string());
+
+            let submissions = sess
+                .get(url)
+                .colorize_status_code(&[200], (), ..)
+                .send()?
+                .ensure_status(&[200])?
+                .json::<Vec<Submission>>()?;
+
+            for submission in &submissions {
+                if submission.result == "AC" {
+                    accepted.insert(submission.problem_id.clone());
+                }
+            }
+
+            match submissions.iter().map(|s| s.epoch_second).max() {
+                Some(last) if submissions.len() >= MAX_SUBMISSIONS => {
+                    from_second = last + 1;
+                    // The API asks to sleep for more than 1 second between the requests.
+                    thread::sleep(Duration::from_secs(1));
+                }
+                _ => break Ok(accepted),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Submission {
+        epoch_second: i64,
+        problem_id: String,
+        result: String,
+    }
+}
+
 fn print_submissions(mut wtr: impl WriteColor, summaries: &[SubmissionSummary]) -> io::Result<()> {
     let task_display_max_width = summaries
         .iter()
@@ -1061,10 +1610,12 @@ fn print_submissions(mut wtr: impl WriteColor, summaries: &[SubmissionSummary])
     Ok(())
 }
 
+/// Watches the submissions until they are judged, or until `watch_timeout` passes.
 fn watch_submissions(
     mut sess: impl SessionMut,
     contest: &CaseConverted<LowerCase>,
     summaries: &[SubmissionSummary],
+    watch_timeout: Option<Duration>,
 ) -> eyre::Result<()> {
     let rt = Runtime::new()?;
     let mut handles = vec![];
@@ -1118,7 +1669,10 @@ fn watch_submissions(
 
             let cookie_header = sess.cookie_header(&"https://atcoder.jp".parse().unwrap());
 
-            handles.push(rt.spawn(async move {
+            let pb_on_timeout = pb.clone();
+            let id_on_timeout = id.clone();
+
+            let poll = async move {
                 let finish_pb = || tokio::task::block_in_place(|| pb.finish_at_current_pos());
 
                 macro_rules! trap(($result:expr $(,)?) => {
@@ -1215,6 +1769,26 @@ fn watch_submissions(
                         break Result::<(), eyre::Error>::Ok(());
                     }
                 }
+            };
+
+            handles.push(rt.spawn(async move {
+                let watch_timeout = match watch_timeout {
+                    Some(watch_timeout) => watch_timeout,
+                    None => return poll.await,
+                };
+                match tokio::time::timeout(watch_timeout, poll).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        tokio::task::block_in_place(|| {
+                            pb_on_timeout.abandon_with_message("Timed out");
+                        });
+                        bail!(
+                            "Gave up watching the submission {} after {}",
+                            id_on_timeout,
+                            humantime::format_duration(watch_timeout),
+                        );
+                    }
+                }
             }));
         } else {
             finish(
@@ -1518,6 +2092,30 @@ impl Html {
         .with_context(|| "Could not extract task indexes and URLs")
     }
 
+    fn extract_statements(&self) -> HashMap<String, String> {
+        self.select(static_selector!(
+            "#main-container > div.row div[class=\"col-sm-12\"]",
+        ))
+        .flat_map(|div| {
+            let title_with_index = div
+                .select(static_selector!(":scope > span"))
+                .flat_map(|r| r.text())
+                .next()?;
+            let index =
+                static_regex!(r"([a-zA-Z0-9]+) - (.+)").captures(title_with_index)?[1].to_owned();
+
+            // In `tasks_print`, there are multiple `#task-statement`s.
+            let statement = div
+                .select(static_selector!(":scope > div[id=\"task-statement\"]"))
+                .exactly_one()
+                .ok()?
+                .html();
+
+            Some((index, statement))
+        })
+        .collect()
+    }
+
     fn extract_samples(&self) -> Vec<eyre::Result<(String, String, eyre::Result<TestSuite>)>> {
         return self
             .select(static_selector!(
@@ -1541,12 +2139,20 @@ impl Html {
                 };
 
                 let test_suite = (|| {
+                    // Left unset if not found, rather than failing.
                     let timelimit = div
                         .select(static_selector!(":scope > p"))
                         .flat_map(|r| r.text())
                         .flat_map(parse_timelimit)
                         .exactly_one()
-                        .map_err(|_| "Could not extract the timelimit")?;
+                        .ok();
+
+                    let memorylimit = div
+                        .select(static_selector!(":scope > p"))
+                        .flat_map(|r| r.text())
+                        .flat_map(parse_memorylimit)
+                        .exactly_one()
+                        .ok();
 
                     // In `tasks_print`, there are multiple `#task-statement`s.
                     let samples = div
@@ -1556,12 +2162,15 @@ impl Html {
                         .and_then(extract_samples)
                         .ok_or("Could not extract the sample cases")?;
 
-                    Ok::<_, &str>(if timelimit == Duration::new(0, 0) {
+                    Ok::<_, &str>(if timelimit == Some(Duration::new(0, 0)) {
                         TestSuite::Unsubmittable
                     } else if let Samples::Batch(r#match, samples) = samples {
                         TestSuite::Batch(BatchTestSuite {
-                            timelimit: Some(timelimit),
+                            timelimit,
+                            memorylimit,
                             r#match,
+                            whitespace: Default::default(),
+                            encoding: None,
                             cases: samples
                                 .into_iter()
                                 .enumerate()
@@ -1570,15 +2179,14 @@ impl Html {
                                     r#in: input.into(),
                                     out: Some(output.into()),
                                     timelimit: None,
+                                    memorylimit: None,
                                     r#match: None,
                                 })
                                 .collect(),
                             extend: vec![],
                         })
                     } else {
-                        TestSuite::Interactive(InteractiveTestSuite {
-                            timelimit: Some(timelimit),
-                        })
+                        TestSuite::Interactive(InteractiveTestSuite { timelimit })
                     })
                 })()
                 .map_err(|e| eyre!("{}: {}", index, e));
@@ -1608,6 +2216,15 @@ impl Html {
             Some(Duration::from_millis(timelimit))
         }
 
+        /// `Memory Limit: 1024 MB` or `メモリ制限: 1024 MB`.
+        fn parse_memorylimit(text: &str) -> Option<ByteSize> {
+            let caps = static_regex!(
+                r"(Memory Limit|メモリ制限)\s*:\s*([0-9]{1,9}(\.[0-9]{1,3})?\s*[KMG]i?B)"
+            )
+            .captures(text)?;
+            caps[2].parse().ok()
+        }
+
         fn extract_samples(task_statement: ElementRef<'_>) -> Option<Samples> {
             // TODO:
             // - https://atcoder.jp/contests/arc019/tasks/arc019_4 (interactive)
@@ -1673,7 +2290,7 @@ impl Html {
             re_input: &'static Regex,
             re_output: &'static Regex,
         ) -> Option<Samples> {
-            #[allow(clippy::blocks_in_if_conditions)]
+            #[allow(clippy::blocks_in_conditions)]
             if task_statement
                 .select(static_selector!("strong"))
                 .flat_map(|r| r.text())
@@ -1838,6 +2455,13 @@ impl Html {
             .with_context(|| "Could not extract the available languages")
     }
 
+    fn extract_submission_code(&self) -> eyre::Result<String> {
+        self.select(static_selector!("#submission-code"))
+            .next()
+            .map(|r| r.text().collect())
+            .with_context(|| "Could not find the code")
+    }
+
     fn extract_submissions(&self) -> eyre::Result<(Vec<SubmissionSummary>, u32)> {
         (|| {
             let num_pages = self
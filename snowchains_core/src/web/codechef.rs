@@ -0,0 +1,571 @@
+use crate::{
+    testsuite::{BatchTestSuite, Match, PartialBatchTestCase, TestSuite},
+    web::{
+        codechef::api::SessionMutExt as _, CookieStorage, Exec, Login, LoginOutcome, Platform,
+        ProblemsInContest, Progress, ResponseExt as _, RetrieveTestCases, RetrieveTestCasesOutcome,
+        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest, Session,
+        SessionMut, Shell,
+    },
+};
+use easy_ext::ext;
+use eyre::{bail, ContextCompat as _};
+use indexmap::indexmap;
+use maplit::btreemap;
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::Infallible,
+    marker::PhantomData,
+    time::Duration,
+};
+use url::Url;
+
+static BASE_URL: Lazy<Url> = lazy_url!("https://www.codechef.com");
+
+/// The contest code for the problems at `/problems/{code}`.
+const PRACTICE: &str = "PRACTICE";
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum CodeChef<'closures> {
+    Infallible(Infallible, PhantomData<fn() -> &'closures ()>),
+}
+
+impl<'closures> Platform for CodeChef<'closures> {
+    type CookieStorage = CookieStorage;
+    type LoginCredentials = CodeChefLoginCredentials<'closures>;
+    type ParticipateTarget = Infallible;
+    type ParticipateCredentials = Infallible;
+    type RetrieveLanguagesTarget = Infallible;
+    type RetrieveLanguagesCredentials = Infallible;
+    type RetrieveTestCasesTargets = ProblemsInContest;
+    type RetrieveTestCasesCredentials = CodeChefRetrieveSampleTestCasesCredentials<'closures>;
+    type RetrieveFullTestCasesCredentials = Infallible;
+    type RetrieveSubmissionSummariesTarget = Infallible;
+    type RetrieveSubmissionSummariesCredentials = Infallible;
+    type RetrieveSolvedProblemsTarget = Infallible;
+    type RetrieveSolvedProblemsCredentials = Infallible;
+    type RetrieveSubmissionsTarget = Infallible;
+    type RetrieveSubmissionsCredentials = Infallible;
+    type WatchSubmissionsTarget = Infallible;
+    type WatchSubmissionsCredentials = Infallible;
+    type SubmitTarget = Infallible;
+    type SubmitCredentials = Infallible;
+}
+
+impl CodeChef<'_> {
+    pub fn exec<A>(args: A) -> eyre::Result<<Self as Exec<A>>::Output>
+    where
+        Self: Exec<A>,
+    {
+        <Self as Exec<_>>::exec(args)
+    }
+}
+
+impl<S: Shell> Exec<Login<Self, S>> for CodeChef<'_> {
+    type Output = LoginOutcome;
+
+    fn exec(args: Login<Self, S>) -> eyre::Result<LoginOutcome> {
+        let Login {
+            credentials:
+                CodeChefLoginCredentials {
+                    username_and_password,
+                },
+            cookie_storage,
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            shell,
+        } = args;
+
+        let sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
+        login(sess, username_and_password)
+    }
+}
+
+impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for CodeChef<'_> {
+    type Output = RetrieveTestCasesOutcome;
+
+    fn exec(args: RetrieveTestCases<Self, S>) -> eyre::Result<RetrieveTestCasesOutcome> {
+        let RetrieveTestCases {
+            targets,
+            credentials:
+                CodeChefRetrieveSampleTestCasesCredentials {
+                    mut username_and_password,
+                },
+            full: _,
+            cookie_storage,
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            shell,
+        } = args;
+
+        let targets = match targets {
+            ProblemsInContest::Indexes { contest, problems } => btreemap!(
+                contest.to_uppercase() => problems.map(|ps| ps.iter().map(|p| p.to_uppercase()).collect()),
+            ),
+            ProblemsInContest::Urls { urls } => {
+                let mut targets: BTreeMap<_, BTreeSet<_>> = btreemap!();
+                for url in urls {
+                    let (contest, problem) = parse_problem_url(&url)?;
+                    targets.entry(contest).or_default().insert(problem);
+                }
+                targets.into_iter().map(|(k, v)| (k, Some(v))).collect()
+            }
+        };
+
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
+        let mut logged_in = false;
+
+        let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
+
+        for (contest_code, problems) in targets {
+            let contest = if contest_code == PRACTICE {
+                None
+            } else {
+                let api::Contest {
+                    name,
+                    problems: contest_problems,
+                } = with_login(
+                    &mut sess,
+                    &mut logged_in,
+                    &mut username_and_password,
+                    |sess| sess.api_contest(&contest_code),
+                )?;
+
+                Some((
+                    RetrieveTestCasesOutcomeProblemContest {
+                        id: contest_code.clone(),
+                        display_name: name,
+                        url: url!("/{}", contest_code),
+                        submissions_url: url!("/{}/status", contest_code),
+                    },
+                    contest_problems,
+                ))
+            };
+
+            let problems = match (problems, &contest) {
+                (Some(problems), _) => problems,
+                (None, Some((_, contest_problems))) => contest_problems.keys().cloned().collect(),
+                (None, None) => bail!("Specify the problems for `{}`", PRACTICE),
+            };
+
+            if let Some((_, contest_problems)) = &contest {
+                let not_found = problems
+                    .iter()
+                    .filter(|p| !contest_problems.contains_key(*p))
+                    .collect::<BTreeSet<_>>();
+                if !not_found.is_empty() {
+                    bail!("No such problem codes: {:?}", not_found);
+                }
+            }
+
+            let total = problems.len();
+
+            for (position, problem_code) in problems.into_iter().enumerate() {
+                sess.shell.on_progress(Progress::Retrieving {
+                    name: &problem_code,
+                    position,
+                    total,
+                })?;
+
+                let api::Problem {
+                    problem_name,
+                    max_timelimit,
+                    body,
+                    problem_components,
+                } = with_login(
+                    &mut sess,
+                    &mut logged_in,
+                    &mut username_and_password,
+                    |sess| sess.api_problem(&contest_code, &problem_code),
+                )?;
+
+                let samples = problem_components
+                    .map(|api::ProblemComponents { sample_test_cases }| sample_test_cases)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|s| !s.is_deleted)
+                    .map(|api::SampleTestCase { input, output, .. }| (input, output))
+                    .collect::<Vec<_>>();
+
+                // Older problems have their samples only in the statements.
+                let samples = if samples.is_empty() {
+                    extract_samples(&body)
+                } else {
+                    samples
+                };
+
+                if samples.is_empty() {
+                    sess.shell()
+                        .warn(format!("{}: Could not find any sample cases", problem_code))?;
+                }
+
+                let test_suite = TestSuite::Batch(BatchTestSuite {
+                    timelimit: max_timelimit.map(Duration::from_secs_f64),
+                    memorylimit: None,
+                    r#match: Match::Lines,
+                    whitespace: Default::default(),
+                    encoding: None,
+                    cases: samples
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (input, output))| PartialBatchTestCase {
+                            name: Some(format!("sample{}", i + 1)),
+                            r#in: normalize(&input).into(),
+                            out: Some(normalize(&output).into()),
+                            timelimit: None,
+                            memorylimit: None,
+                            r#match: None,
+                        })
+                        .collect(),
+                    extend: vec![],
+                });
+
+                let url = if contest.is_some() {
+                    url!("/{}/problems/{}", contest_code, problem_code)
+                } else {
+                    url!("/problems/{}", problem_code)
+                };
+
+                outcome.problems.push(RetrieveTestCasesOutcomeProblem {
+                    contest: contest.as_ref().map(|(c, _)| c.clone()),
+                    index: problem_code.clone(),
+                    url,
+                    screen_name: Some(problem_code),
+                    display_name: problem_name,
+                    test_suite,
+                    text_files: indexmap!(),
+                    statement: Some(body),
+                    full_error: None,
+                });
+            }
+        }
+
+        super::report_retrieved(&mut sess.shell, &outcome)?;
+
+        Ok(outcome)
+    }
+}
+
+pub struct CodeChefLoginCredentials<'closures> {
+    pub username_and_password: &'closures mut dyn FnMut() -> eyre::Result<(String, String)>,
+}
+
+pub struct CodeChefRetrieveSampleTestCasesCredentials<'closures> {
+    pub username_and_password: &'closures mut dyn FnMut() -> eyre::Result<(String, String)>,
+}
+
+/// Parses `/problems/{code}` and `/{contest}/problems/{code}`.
+fn parse_problem_url(url: &Url) -> eyre::Result<(String, String)> {
+    if !matches!(url.domain(), Some("www.codechef.com" | "codechef.com")) {
+        bail!("wrong domain. expected `www.codechef.com`: {}", url);
+    }
+
+    let caps = static_regex!(r"\A(/([A-Za-z0-9_]+))?/problems/([A-Za-z0-9_]+)/?\z")
+        .captures(url.path())
+        .with_context(|| format!("`{}` is not a URL for problem in CodeChef", url))?;
+
+    let contest = caps
+        .get(2)
+        .map(|m| m.as_str().to_uppercase())
+        .unwrap_or_else(|| PRACTICE.to_owned());
+    let problem = caps[3].to_uppercase();
+    Ok((contest, problem))
+}
+
+fn login(
+    mut sess: impl SessionMut,
+    mut username_and_password: impl FnMut() -> eyre::Result<(String, String)>,
+) -> eyre::Result<LoginOutcome> {
+    let url = url!("/login");
+
+    let mut res = sess
+        .get(url.clone())
+        .colorize_status_code(&[200, 302], (), ..)
+        .send()?
+        .ensure_status(&[200, 302])?;
+
+    // The logged-in users are redirected to the top page.
+    if res.status() == 302 {
+        return Ok(LoginOutcome::AlreadyLoggedIn);
+    }
+
+    sess.shell().on_progress(Progress::LoginRequired)?;
+
+    loop {
+        let (username, password) = username_and_password()?;
+
+        let mut payload = res.html()?.extract_login_form()?;
+        payload.insert("name".to_owned(), username);
+        payload.insert("pass".to_owned(), password);
+
+        res = sess
+            .post(url.clone())
+            .form(&payload)
+            .colorize_status_code(&[200, 302], (), ..)
+            .send()?
+            .ensure_status(&[200, 302])?;
+
+        if res.status() == 302 {
+            break Ok(LoginOutcome::Success);
+        }
+
+        sess.shell().warn("Failed to login. Try again")?;
+    }
+}
+
+/// Problems in running contests are only shown to the logged-in users, so logs in on the first
+/// refusal and then tries again.
+fn with_login<S: SessionMut, T>(
+    sess: &mut S,
+    logged_in: &mut bool,
+    mut username_and_password: impl FnMut() -> eyre::Result<(String, String)>,
+    mut f: impl FnMut(&mut S) -> eyre::Result<Result<T, String>>,
+) -> eyre::Result<T> {
+    loop {
+        match f(sess)? {
+            Ok(value) => break Ok(value),
+            Err(message) if *logged_in => bail!("{}", message),
+            Err(_) => {
+                login(&mut *sess, &mut username_and_password)?;
+                *logged_in = true;
+            }
+        }
+    }
+}
+
+#[ext]
+impl Html {
+    fn extract_login_form(&self) -> eyre::Result<HashMap<String, String>> {
+        let form = self
+            .select(static_selector!("form"))
+            .find(|f| {
+                f.select(static_selector!("input[name=\"pass\"]"))
+                    .next()
+                    .is_some()
+            })
+            .with_context(|| "Could not find the login form")?;
+
+        Ok(form
+            .select(static_selector!("input[type=\"hidden\"]"))
+            .flat_map(|input| {
+                let input = input.value();
+                let name = input.attr("name")?.to_owned();
+                let value = input.attr("value").unwrap_or_default().to_owned();
+                Some((name, value))
+            })
+            .collect())
+    }
+}
+
+/// Extracts the samples from a statement.
+///
+/// Newer statements are in Markdown and have fenced code blocks under headings such as
+/// "Sample Input 1". Older ones are in HTML and have `<pre>`s, sometimes with both of the input
+/// and the output in one `<pre>` as `<b>Input:</b> .. <b>Output:</b> ..`.
+fn extract_samples(body: &str) -> Vec<(String, String)> {
+    let mut samples = vec![];
+    let mut pairer = Pairer::default();
+
+    for caps in static_regex!(r"(?m)^(?:#{1,6}|\*\*)([^\n]*)$|^```[^\n]*\n((?s:.*?))^```")
+        .captures_iter(body)
+    {
+        if let Some(heading) = caps.get(1) {
+            pairer.on_heading(heading.as_str());
+        } else if let Some(input_and_output) = pairer.on_block(&caps[2]) {
+            samples.push(input_and_output);
+        }
+    }
+
+    if !samples.is_empty() {
+        return samples;
+    }
+
+    let html = Html::parse_fragment(body);
+    let mut pairer = Pairer::default();
+
+    for elem in html.select(static_selector!(
+        "h1, h2, h3, h4, h5, h6, p, b, strong, pre"
+    )) {
+        if elem.value().name() == "pre" {
+            let text = elem.text().collect::<String>();
+            if let Some(caps) =
+                static_regex!(r"(?is)\A\s*input:?[ \t]*\n(.*?)\n\s*output:?[ \t]*\n(.*)\z")
+                    .captures(&text)
+            {
+                samples.push((caps[1].to_owned(), caps[2].to_owned()));
+            } else if let Some(input_and_output) = pairer.on_block(&text) {
+                samples.push(input_and_output);
+            }
+        } else if !elem.is_in_pre() {
+            pairer.on_heading(&elem.text().collect::<String>());
+        }
+    }
+
+    return samples;
+
+    #[derive(Default)]
+    struct Pairer {
+        next: Option<bool>,
+        input: Option<String>,
+    }
+
+    impl Pairer {
+        fn on_heading(&mut self, heading: &str) {
+            let heading = heading.to_lowercase();
+            self.next = if heading.contains("input") {
+                Some(true)
+            } else if heading.contains("output") {
+                Some(false)
+            } else {
+                None
+            };
+        }
+
+        fn on_block(&mut self, text: &str) -> Option<(String, String)> {
+            match self.next.take()? {
+                true => {
+                    self.input = Some(text.to_owned());
+                    None
+                }
+                false => Some((self.input.take()?, text.to_owned())),
+            }
+        }
+    }
+
+    #[ext]
+    impl ElementRef<'_> {
+        fn is_in_pre(&self) -> bool {
+            self.ancestors()
+                .any(|a| matches!(a.value().as_element(), Some(e) if e.name() == "pre"))
+        }
+    }
+}
+
+fn normalize(text: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    let text = text.trim_start_matches('\n').trim_end();
+    format!("{}\n", text)
+}
+
+mod api {
+    use super::BASE_URL;
+    use crate::web::{ResponseExt as _, SessionMut};
+    use eyre::WrapErr as _;
+    use indexmap::IndexMap;
+    use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+    use url::Url;
+
+    pub(super) trait SessionMutExt: SessionMut {
+        /// `Err(message)` if the contest is not visible.
+        fn api_contest(&mut self, contest: &str) -> eyre::Result<Result<Contest, String>> {
+            get(self, url!("/api/contests/{}", contest))
+        }
+
+        /// `Err(message)` if the problem is not visible.
+        fn api_problem(
+            &mut self,
+            contest: &str,
+            problem: &str,
+        ) -> eyre::Result<Result<Problem, String>> {
+            get(self, url!("/api/contests/{}/problems/{}", contest, problem))
+        }
+    }
+
+    impl<S: SessionMut> SessionMutExt for S {}
+
+    /// The API always responds with 200 and tells the result with `status`.
+    fn get<T: DeserializeOwned>(
+        sess: &mut impl SessionMut,
+        url: Url,
+    ) -> eyre::Result<Result<T, String>> {
+        let res = sess
+            .get(url.clone())
+            .colorize_status_code(&[200], (), ..)
+            .send()?
+            .ensure_status(&[200])?
+            .json::<serde_json::Value>()?;
+
+        if res["status"] != "success" {
+            let message = res["message"]
+                .as_str()
+                .unwrap_or("the request was refused")
+                .to_owned();
+            return Ok(Err(format!("{}: {}", url, message)));
+        }
+        serde_json::from_value(res)
+            .map(Ok)
+            .with_context(|| format!("Could not parse the response from {}", url))
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Contest {
+        pub(super) name: String,
+        /// By problem codes.
+        #[serde(default)]
+        pub(super) problems: IndexMap<String, serde::de::IgnoredAny>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct Problem {
+        pub(super) problem_name: String,
+        /// In seconds.
+        #[serde(default, deserialize_with = "deserialize_seconds")]
+        pub(super) max_timelimit: Option<f64>,
+        #[serde(default)]
+        pub(super) body: String,
+        #[serde(default, rename = "problemComponents")]
+        pub(super) problem_components: Option<ProblemComponents>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct ProblemComponents {
+        #[serde(default, rename = "sampleTestCases")]
+        pub(super) sample_test_cases: Vec<SampleTestCase>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct SampleTestCase {
+        pub(super) input: String,
+        pub(super) output: String,
+        #[serde(default, rename = "isDeleted")]
+        pub(super) is_deleted: bool,
+    }
+
+    /// Either `"1"`, `"1.5"`, or `1.5`.
+    fn deserialize_seconds<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            String(String),
+        }
+
+        Ok(match Option::<Repr>::deserialize(deserializer)? {
+            Some(Repr::Number(secs)) => Some(secs),
+            Some(Repr::String(secs)) => secs.trim().parse().ok(),
+            None => None,
+        })
+    }
+}
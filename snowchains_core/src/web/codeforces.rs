@@ -1,11 +1,11 @@
 use crate::{
-    testsuite::{BatchTestSuite, Match, PartialBatchTestCase, TestSuite},
+    testsuite::{BatchTestSuite, ByteSize, Match, PartialBatchTestCase, TestSuite},
     web::{
         codeforces::api::SessionMutExt as _, CookieStorage, Exec, Login, LoginOutcome, Participate,
-        ParticipateOutcome, Platform, ProblemInContest, ProblemsInContest, ResponseExt as _,
-        RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveTestCases, RetrieveTestCasesOutcome,
-        RetrieveTestCasesOutcomeProblem, RetrieveTestCasesOutcomeProblemContest, Session,
-        SessionMut, Shell, Submit, SubmitOutcome,
+        ParticipateOutcome, Platform, ProblemInContest, ProblemsInContest, Progress,
+        ResponseExt as _, RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveTestCases,
+        RetrieveTestCasesOutcome, RetrieveTestCasesOutcomeProblem,
+        RetrieveTestCasesOutcomeProblemContest, Session, SessionMut, Shell, Submit, SubmitOutcome,
     },
 };
 use easy_ext::ext;
@@ -47,6 +47,10 @@ impl<'closures> Platform for Codeforces<'closures> {
     type RetrieveFullTestCasesCredentials = Infallible;
     type RetrieveSubmissionSummariesTarget = Infallible;
     type RetrieveSubmissionSummariesCredentials = Infallible;
+    type RetrieveSolvedProblemsTarget = Infallible;
+    type RetrieveSolvedProblemsCredentials = Infallible;
+    type RetrieveSubmissionsTarget = Infallible;
+    type RetrieveSubmissionsCredentials = Infallible;
     type WatchSubmissionsTarget = Infallible;
     type WatchSubmissionsCredentials = Infallible;
     type SubmitTarget = ProblemInContest;
@@ -73,10 +77,20 @@ impl<S: Shell> Exec<Login<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
         let (outcome, _) = login(sess, username_and_password)?;
         Ok(outcome)
     }
@@ -94,11 +108,21 @@ impl<S: Shell> Exec<Participate<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
         let contest = parse_contest_id(&contest)?;
-        let sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
         let (outcome, _, _) = participate(sess, username_and_password, contest)?;
         Ok(outcome)
     }
@@ -116,12 +140,22 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Codeforces<'_> {
                 },
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
         let contest = parse_contest_id(&contest)?;
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         participate(&mut sess, username_and_password, contest)?;
 
@@ -150,6 +184,9 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
             full: _,
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
@@ -167,10 +204,25 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
             }
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
         let mut outcome = RetrieveTestCasesOutcome { problems: vec![] };
 
-        for (contest, problems) in targets {
+        let total = targets.len();
+
+        for (position, (contest, problems)) in targets.into_iter().enumerate() {
+            sess.shell.on_progress(Progress::Retrieving {
+                name: &contest.to_string(),
+                position,
+                total,
+            })?;
+
             let (_, contest_name, _) = participate(&mut sess, &mut username_and_password, contest)?;
 
             let mut problem_indices = problems.map(|ps| {
@@ -183,12 +235,12 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
             let contest = &RetrieveTestCasesOutcomeProblemContest {
                 id: contest.to_string(),
                 display_name: contest_name,
-                url: url!("/contest/{}", contest),
-                submissions_url: url!("/contest/{}/my", contest),
+                url: url!("/{}/{}", contest_kind(contest), contest),
+                submissions_url: url!("/{}/{}/my", contest_kind(contest), contest),
             };
 
             outcome.problems.extend(
-                sess.get(url!("/contest/{}", contest.id))
+                sess.get(contest.url.clone())
                     .colorize_status_code(&[200], (), ..)
                     .send()?
                     .ensure_status(&[200])?
@@ -202,12 +254,14 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
                             }
                         }
 
-                        let test_suite = sess
+                        let html = sess
                             .get(url.clone())
                             .colorize_status_code(&[200], (), ..)
                             .send()?
-                            .html()?
-                            .extract_test_cases()?;
+                            .html()?;
+
+                        let test_suite = html.extract_test_cases()?;
+                        let statement = html.extract_statement();
 
                         Ok(Some(RetrieveTestCasesOutcomeProblem {
                             contest: Some(contest.clone()),
@@ -217,6 +271,8 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
                             display_name,
                             test_suite,
                             text_files: indexmap!(),
+                            statement,
+                            full_error: None,
                         }))
                     })
                     .flat_map(Result::transpose)
@@ -230,6 +286,8 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Codeforces<'_> {
             }
         }
 
+        super::report_retrieved(&mut sess.shell, &outcome)?;
+
         Ok(outcome)
     }
 }
@@ -251,6 +309,11 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
             watch_submission,
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
+            watch_timeout: _,
+            refuse_if_accepted: _,
             mut shell,
         } = args;
 
@@ -263,7 +326,14 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
             ProblemInContest::Url { url } => parse_problem_url(&url)?,
         };
 
-        let mut sess = Session::new(timeout, Some(cookie_storage), shell)?;
+        let mut sess = Session::new(
+            timeout,
+            retry,
+            min_interval,
+            proxy,
+            Some(cookie_storage),
+            shell,
+        )?;
 
         let (_, _, handle) = participate(&mut sess, username_and_password, contest_id)?;
 
@@ -308,7 +378,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Codeforces<'_> {
                 sess.api_contest_status(&api_key, &api_secret, contest_id, &handle, 1, Some(1))?;
 
             let submission = submissions
-                .get(0)
+                .first()
                 .with_context(|| "Recieved no submission")?;
 
             let submission_url = url!("/contest/{}/submission/{}", contest_id, submission.id);
@@ -368,12 +438,12 @@ fn parse_problem_url(url: &Url) -> eyre::Result<(u64, String)> {
         bail!("wrong domain. expected `codeforces.com`: {}", url);
     }
 
-    let caps = static_regex!(r"\A/contest/([0-9]{1,5})/problem/(.*)\z")
+    let caps = static_regex!(r"\A/(contest|gym)/([0-9]{1,6})/problem/(.*)\z")
         .captures(url.path())
         .with_context(|| format!("`{}` is not a URL for problem in Codeforces", url))?;
 
-    let contest_id = caps[1].parse().expect("from `[0-9]{1,5}`");
-    let problem_index = caps[2].to_owned();
+    let contest_id = caps[2].parse().expect("from `[0-9]{1,6}`");
+    let problem_index = caps[3].to_owned();
     Ok((contest_id, problem_index))
 }
 
@@ -394,6 +464,8 @@ fn login(
         return Ok((LoginOutcome::AlreadyLoggedIn, handle));
     }
 
+    sess.shell().on_progress(Progress::LoginRequired)?;
+
     return loop {
         let (handle_or_email, password) = username_and_password()?;
 
@@ -464,6 +536,15 @@ fn is_gym(contest_id: u64) -> bool {
     contest_id >= 100_000
 }
 
+/// The first path segment of the pages of the contest.
+fn contest_kind(contest_id: u64) -> &'static str {
+    if is_gym(contest_id) {
+        "gym"
+    } else {
+        "contest"
+    }
+}
+
 #[ext]
 impl Html {
     fn extract_hidden_values(&self, form: &Selector) -> eyre::Result<HashMap<String, String>> {
@@ -543,6 +624,12 @@ impl Html {
         .with_context(|| "Could not extract problem names")
     }
 
+    fn extract_statement(&self) -> Option<String> {
+        self.select(static_selector!("#pageContent div.problem-statement"))
+            .next()
+            .map(|r| r.html())
+    }
+
     fn extract_test_cases(&self) -> eyre::Result<TestSuite> {
         let timelimit = self
             .select(static_selector!("#pageContent div.time-limit"))
@@ -557,8 +644,17 @@ impl Html {
                 Some(Duration::new(secs, nanos))
             })
             .exactly_one()
-            .ok()
-            .with_context(|| "Could not extract the timelimit")?;
+            .ok();
+
+        let memorylimit = self
+            .select(static_selector!("#pageContent div.memory-limit"))
+            .flat_map(|r| r.text())
+            .flat_map(|text| {
+                let caps = lazy_regex!(r#"\A([0-9]{1,9}) megabytes?\z"#).captures(text)?;
+                format!("{}MiB", &caps[1]).parse::<ByteSize>().ok()
+            })
+            .exactly_one()
+            .ok();
 
         let input_file_text = self
             .select(static_selector!("#pageContent div.input-file"))
@@ -613,12 +709,16 @@ impl Html {
                 out: Some(out.into()),
                 r#match: None,
                 timelimit: None,
+                memorylimit: None,
             })
             .collect();
 
         return Ok(TestSuite::Batch(BatchTestSuite {
-            timelimit: Some(timelimit),
+            timelimit,
+            memorylimit,
             r#match,
+            whitespace: Default::default(),
+            encoding: None,
             cases,
             extend: vec![],
         }));
@@ -630,6 +730,16 @@ impl Html {
                     match node.value() {
                         Node::Text(t) => ret += t,
                         Node::Element(e) if e.name() == "br" => ret.push('\n'),
+                        // Newer problems put each line of the examples in
+                        // `<div class="test-example-line">`, with no line feeds between them.
+                        Node::Element(e) if e.name() == "div" => {
+                            if let Some(div) = ElementRef::wrap(node) {
+                                ret += &div.fold_text_and_br();
+                                if !ret.ends_with('\n') {
+                                    ret.push('\n');
+                                }
+                            }
+                        }
                         _ => {}
                     }
                     ret
@@ -638,6 +748,7 @@ impl Html {
         }
     }
 
+    #[allow(dead_code)]
     fn extract_meta_x_csrf_token(&self) -> eyre::Result<String> {
         self.select(static_selector!("meta[name=\"X-Csrf-Token\"]"))
             .next()
@@ -662,6 +773,7 @@ mod api {
     /// "Represents a Codeforces user."
     ///
     /// <https://codeforces.com/apiHelp/objects#User>
+    #[allow(dead_code)]
     #[derive(Debug, Deserialize)]
     pub(super) struct User {
         // __rest: (),
@@ -728,6 +840,7 @@ mod api {
     /// "Enum: FAILED, OK, PARTIAL, COMPILATION_ERROR, RUNTIME_ERROR, WRONG_ANSWER, PRESENTATION_ERROR, TIME_LIMIT_EXCEEDED, MEMORY_LIMIT_EXCEEDED, IDLENESS_LIMIT_EXCEEDED, SECURITY_VIOLATED, CRASHED, INPUT_PREPARATION_CRASHED, CHALLENGED, SKIPPED, TESTING, REJECTED. Can be absent."
     ///
     /// <https://codeforces.com/apiHelp/objects#Submission>
+    #[allow(dead_code)]
     #[derive(Debug, PartialEq, strum::Display, Deserialize)]
     #[strum(serialize_all = "shouty_snake_case")]
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -1,10 +1,10 @@
 use crate::{
     testsuite::{
-        BatchTestSuite, InteractiveTestSuite, Match, PartialBatchTestCase, PositiveFinite,
-        TestSuite,
+        BatchTestSuite, ByteSize, InteractiveTestSuite, Match, PartialBatchTestCase,
+        PositiveFinite, TestSuite,
     },
     web::{
-        yukicoder::api::SessionMutExt as _, Exec, Platform, ResponseExt as _,
+        yukicoder::api::SessionMutExt as _, Exec, Platform, Progress, ResponseExt as _,
         RetrieveFullTestCases, RetrieveLanguages, RetrieveLanguagesOutcome, RetrieveTestCases,
         RetrieveTestCasesOutcome, RetrieveTestCasesOutcomeProblem,
         RetrieveTestCasesOutcomeProblemContest, RetrieveTestCasesOutcomeProblemTextFiles, Session,
@@ -14,7 +14,7 @@ use crate::{
 use easy_ext::ext;
 use either::Either;
 use eyre::{bail, Context as _, ContextCompat as _};
-use indexmap::indexmap;
+use indexmap::{indexmap, IndexMap};
 use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use scraper::{ElementRef, Html, Node};
@@ -47,6 +47,10 @@ impl Platform for Yukicoder {
     type RetrieveFullTestCasesCredentials = YukicoderRetrieveFullTestCasesCredentials;
     type RetrieveSubmissionSummariesTarget = Infallible;
     type RetrieveSubmissionSummariesCredentials = Infallible;
+    type RetrieveSolvedProblemsTarget = Infallible;
+    type RetrieveSolvedProblemsCredentials = Infallible;
+    type RetrieveSubmissionsTarget = Infallible;
+    type RetrieveSubmissionsCredentials = Infallible;
     type WatchSubmissionsTarget = Infallible;
     type WatchSubmissionsCredentials = Infallible;
     type SubmitTarget = YukicoderSubmitTarget;
@@ -62,10 +66,13 @@ impl<S: Shell> Exec<RetrieveLanguages<Self, S>> for Yukicoder {
             credentials: (),
             cookie_storage: (),
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
-        let names_by_id = Session::new(timeout, None, shell)?
+        let names_by_id = Session::new(timeout, retry, min_interval, proxy, None, shell)?
             .get_available_language()?
             .into_iter()
             .map(|api::Language { id, name, ver }| (id, format!("{} ({})", name, ver)))
@@ -85,17 +92,25 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
             full,
             cookie_storage: (),
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell,
         } = args;
 
-        let mut sess = Session::new(timeout, None, shell)?;
+        let mut sess = Session::new(timeout, retry, min_interval, proxy, None, shell)?;
 
         let mut outcome = retrieve_samples(&mut sess, targets)?;
 
         if let Some(RetrieveFullTestCases {
             credentials: YukicoderRetrieveFullTestCasesCredentials { api_key },
+            jobs,
+            timeout: full_timeout,
         }) = full
         {
+            // Lists the files of all the problems first, so that they are downloaded at once.
+            let mut file_names = vec![];
+
             for outcome_problem in &mut outcome.problems {
                 let problem_id = outcome_problem
                     .screen_name
@@ -104,59 +119,100 @@ impl<S: Shell> Exec<RetrieveTestCases<Self, S>> for Yukicoder {
                     .parse()
                     .expect("should be integer");
 
-                let in_file_names =
-                    sess.get_test_case_files_by_problem_id(&api_key, problem_id, api::Which::In)?;
+                let mut list = |which| -> _ {
+                    sess.get_test_case_files_by_problem_id(&api_key, problem_id, which)
+                };
+
+                match list(api::Which::In).and_then(|ins| Ok((ins, list(api::Which::Out)?))) {
+                    Ok((ins, outs)) => file_names.push(Some((problem_id, ins, outs))),
+                    Err(err) => {
+                        sess.shell
+                            .warn(format!("{}: {:#}", outcome_problem.index, err))?;
+                        outcome_problem.full_error = Some(format!("{:#}", err));
+                        file_names.push(None);
+                    }
+                }
+            }
 
-                let in_contents = super::download_with_progress(
-                    sess.shell.progress_draw_target(),
-                    in_file_names
-                        .iter()
-                        .map(|file_name| {
+            let mut dl_targets = vec![];
+            for (outcome_problem, file_names) in outcome.problems.iter().zip_eq(&file_names) {
+                if let Some((problem_id, ins, outs)) = file_names {
+                    for (which, names) in [(api::Which::In, ins), (api::Which::Out, outs)] {
+                        for file_name in names {
                             let req = sess.get_test_case_file_by_problem_id(
                                 &api_key,
-                                problem_id,
-                                api::Which::In,
+                                *problem_id,
+                                which,
                                 file_name,
                             )?;
-                            Ok((format!("in/{}", file_name), req))
-                        })
-                        .collect::<Result<_, url::ParseError>>()?,
-                )?;
+                            let name = format!("{}/{}/{}", outcome_problem.index, which, file_name);
+                            dl_targets.push((name, req));
+                        }
+                    }
+                }
+            }
 
-                let out_file_names =
-                    sess.get_test_case_files_by_problem_id(&api_key, problem_id, api::Which::Out)?;
+            let mut contents = super::download_with_progress(
+                sess.shell.progress_draw_target(),
+                jobs,
+                full_timeout,
+                sess.retry,
+                sess.min_interval,
+                &sess.async_client,
+                dl_targets,
+            )?
+            .into_iter();
+
+            for (outcome_problem, file_names) in outcome.problems.iter_mut().zip_eq(file_names) {
+                let (in_file_names, out_file_names) = match file_names {
+                    Some((_, ins, outs)) => (ins, outs),
+                    None => continue,
+                };
 
-                let out_contents = super::download_with_progress(
-                    sess.shell.progress_draw_target(),
-                    out_file_names
-                        .iter()
-                        .map(|file_name| {
-                            let req = sess.get_test_case_file_by_problem_id(
-                                &api_key,
-                                problem_id,
-                                api::Which::Out,
-                                file_name,
-                            )?;
-                            Ok((format!("out/{}", file_name), req))
+                let in_contents = contents
+                    .by_ref()
+                    .take(in_file_names.len())
+                    .collect::<Vec<_>>();
+                let out_contents = contents
+                    .by_ref()
+                    .take(out_file_names.len())
+                    .collect::<Vec<_>>();
+
+                let text_files = (|| -> eyre::Result<IndexMap<_, _>> {
+                    let mut text_files = in_file_names
+                        .into_iter()
+                        .zip_eq(in_contents)
+                        .map(|(name, r#in)| {
+                            let r#in = r#in?;
+                            Ok((
+                                name,
+                                RetrieveTestCasesOutcomeProblemTextFiles { r#in, out: None },
+                            ))
                         })
-                        .collect::<Result<_, url::ParseError>>()?,
-                )?;
-
-                for (name, r#in) in in_file_names.into_iter().zip_eq(in_contents) {
-                    outcome_problem.text_files.insert(
-                        name,
-                        RetrieveTestCasesOutcomeProblemTextFiles { r#in, out: None },
-                    );
-                }
+                        .collect::<eyre::Result<IndexMap<_, _>>>()?;
 
-                for (name, out) in out_file_names.into_iter().zip_eq(out_contents) {
-                    if let Some(text_files) = outcome_problem.text_files.get_mut(&name) {
-                        text_files.out = Some(out);
+                    for (name, out) in out_file_names.into_iter().zip_eq(out_contents) {
+                        let out = out?;
+                        if let Some(text_files) = text_files.get_mut(&name) {
+                            text_files.out = Some(out);
+                        }
+                    }
+                    Ok(text_files)
+                })();
+
+                match text_files {
+                    Ok(text_files) => outcome_problem.text_files.extend(text_files),
+                    Err(err) => {
+                        sess.shell
+                            .warn(format!("{}: {:#}", outcome_problem.index, err))?;
+                        outcome_problem.full_error = Some(format!("{:#}", err));
                     }
                 }
             }
         }
 
+        super::report_retrieved(&mut sess.shell, &outcome)?;
+
         Ok(outcome)
     }
 }
@@ -173,6 +229,11 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
             watch_submission,
             cookie_storage: (),
             timeout,
+            retry,
+            min_interval,
+            proxy,
+            watch_timeout: _,
+            refuse_if_accepted: _,
             mut shell,
         } = args;
 
@@ -180,7 +241,7 @@ impl<S: Shell> Exec<Submit<Self, S>> for Yukicoder {
             shell.warn("`watch_submissions` in yukicoder is not yet supported")?;
         }
 
-        let mut sess = Session::new(timeout, None, shell)?;
+        let mut sess = Session::new(timeout, retry, min_interval, proxy, None, shell)?;
 
         let problem_id = match target.parse()? {
             Either::Left(url) => match parse_problem_url(&url)? {
@@ -320,10 +381,16 @@ fn retrieve_samples(
 
     match targets {
         YukicoderRetrieveTestCasesTargets::ProblemNos(problem_nos) => {
-            for problem_no in &problem_nos {
+            for (position, problem_no) in problem_nos.iter().enumerate() {
+                sess.shell().on_progress(Progress::Retrieving {
+                    name: problem_no,
+                    position,
+                    total: problem_nos.len(),
+                })?;
+
                 let problem_no = parse_problem_no(problem_no)?;
 
-                let (url, test_suite) = retrieve_samples(&mut sess, problem_no)?;
+                let (url, test_suite, statement) = retrieve_samples(&mut sess, problem_no)?;
                 let api::Problem {
                     problem_id, title, ..
                 } = sess.get_problem_by_problem_no(problem_no)?;
@@ -336,6 +403,8 @@ fn retrieve_samples(
                     display_name: title.clone(),
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
+                    full_error: None,
                 });
             }
         }
@@ -360,6 +429,12 @@ fn retrieve_samples(
                 })
                 .transpose()?;
 
+            sess.shell().on_progress(Progress::Retrieving {
+                name: &contest_id.to_string(),
+                position: 0,
+                total: 1,
+            })?;
+
             let api::Contest {
                 name,
                 problem_id_list,
@@ -389,7 +464,7 @@ fn retrieve_samples(
                 }
 
                 let api::Problem { no, title, .. } = sess.get_problem_by_problem_id(problem_id)?;
-                let (url, test_suite) = retrieve_samples(&mut sess, no)?;
+                let (url, test_suite, statement) = retrieve_samples(&mut sess, no)?;
 
                 outcome.problems.push(RetrieveTestCasesOutcomeProblem {
                     contest: Some(contest.clone()),
@@ -399,6 +474,8 @@ fn retrieve_samples(
                     display_name: title,
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
+                    full_error: None,
                 });
             }
 
@@ -409,7 +486,15 @@ fn retrieve_samples(
             }
         }
         YukicoderRetrieveTestCasesTargets::Urls(urls) => {
-            for url in urls {
+            let total = urls.len();
+
+            for (position, url) in urls.into_iter().enumerate() {
+                sess.shell().on_progress(Progress::Retrieving {
+                    name: url.as_str(),
+                    position,
+                    total,
+                })?;
+
                 let api::Problem {
                     no,
                     problem_id,
@@ -419,7 +504,7 @@ fn retrieve_samples(
                     Either::Right(problem_id) => sess.get_problem_by_problem_id(problem_id)?,
                 };
 
-                let (_, test_suite) = retrieve_samples(&mut sess, no)?;
+                let (_, test_suite, statement) = retrieve_samples(&mut sess, no)?;
 
                 outcome.problems.push(RetrieveTestCasesOutcomeProblem {
                     contest: None,
@@ -429,6 +514,8 @@ fn retrieve_samples(
                     display_name: title.clone(),
                     test_suite,
                     text_files: indexmap!(),
+                    statement,
+                    full_error: None,
                 });
             }
         }
@@ -439,18 +526,23 @@ fn retrieve_samples(
     fn retrieve_samples(
         mut sess: impl SessionMut,
         problem_no: u64,
-    ) -> eyre::Result<(Url, TestSuite)> {
+    ) -> eyre::Result<(Url, TestSuite, Option<String>)> {
         let url = url!("/problems/no/{}", problem_no);
 
-        let test_suite = sess
+        let html = sess
             .get(url.clone())
             .colorize_status_code(&[200], (), ..)
             .send()?
             .ensure_status(&[200])?
-            .html()?
-            .extract_samples()?;
+            .html()?;
+
+        let test_suite = html.extract_samples()?;
+        let statement = html
+            .select(static_selector!("#content"))
+            .next()
+            .map(|r| r.html());
 
-        Ok((url, test_suite))
+        Ok((url, test_suite, statement))
     }
 }
 
@@ -461,14 +553,14 @@ impl Html {
             .select(static_selector!("#content > div"))
             .flat_map(|r| r.text())
             .find_map(|text| {
-                let timelimit = {
-                    let caps = static_regex!(r"([0-9]{1,3})\.([0-9]{3})秒").captures(text)?;
+                let timelimit = static_regex!(r"([0-9]{1,3})\.([0-9]{3})秒")
+                    .captures(text)
+                    .map(|caps| {
+                        let secs = caps[1].parse::<u64>().unwrap();
+                        let millis = caps[2].parse::<u64>().unwrap();
 
-                    let secs = caps[1].parse::<u64>().unwrap();
-                    let millis = caps[2].parse::<u64>().unwrap();
-
-                    Duration::from_millis(1000 * secs + millis)
-                };
+                        Duration::from_millis(1000 * secs + millis)
+                    });
 
                 let kind = if text.contains("標準ジャッジ問題") {
                     Kind::Regular
@@ -498,6 +590,15 @@ impl Html {
             })
             .with_context(|| "Could not parse the page")?;
 
+        let memorylimit = self
+            .select(static_selector!("#content > div"))
+            .flat_map(|r| r.text())
+            .find_map(|text| {
+                let caps =
+                    static_regex!(r"メモリ制限\s*:\s*([0-9]{1,9}\s*[KMG]i?B)").captures(text)?;
+                caps[1].parse::<ByteSize>().ok()
+            });
+
         let test_suite = match kind {
             Kind::Regular | Kind::Special | Kind::Floating { .. } => {
                 let r#match = if let Kind::Floating {
@@ -514,8 +615,11 @@ impl Html {
                 };
 
                 let mut test_suite = BatchTestSuite {
-                    timelimit: Some(timelimit),
+                    timelimit,
+                    memorylimit,
                     r#match,
+                    whitespace: Default::default(),
+                    encoding: None,
                     cases: vec![],
                     extend: vec![],
                 };
@@ -540,6 +644,7 @@ impl Html {
                                 _ => None,
                             },
                             timelimit: None,
+                            memorylimit: None,
                             r#match: None,
                         });
                     } else {
@@ -549,9 +654,7 @@ impl Html {
 
                 TestSuite::Batch(test_suite)
             }
-            Kind::Reactive => TestSuite::Interactive(InteractiveTestSuite {
-                timelimit: Some(timelimit),
-            }),
+            Kind::Reactive => TestSuite::Interactive(InteractiveTestSuite { timelimit }),
         };
 
         return Ok(test_suite);
@@ -680,6 +783,7 @@ mod api {
         /// > Get all problems
         ///
         /// > 公開されているテスト以外のすべての問題を取得します
+        #[allow(dead_code)]
         fn get_all_problems(&mut self) -> eyre::Result<Vec<Problem>> {
             let url = BASE_URL.join("problems").unwrap();
 
@@ -769,6 +873,7 @@ mod api {
 
     impl<S: SessionMut> SessionMutExt for S {}
 
+    #[allow(dead_code)]
     pub(super) trait ReqwestAsyncClientExt {
         /// > Get TestCaseFile by ProblemId
         fn get_test_case_file_by_problem_id(
@@ -807,7 +912,7 @@ mod api {
         //date: chrono::DateTime<chrono::FixedOffset>,
     }
 
-    #[derive(Debug, strum::Display)]
+    #[derive(Debug, Clone, Copy, strum::Display)]
     #[strum(serialize_all = "lowercase")]
     pub(super) enum Which {
         In,
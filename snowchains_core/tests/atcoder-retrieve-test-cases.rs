@@ -112,6 +112,7 @@ fn test(
         }
     }
 
+    #[allow(dead_code)]
     #[derive(Debug)]
     enum Message {
         PrintAnsi(String),
@@ -136,6 +137,9 @@ fn test(
             on_update: Box::new(|_| Ok(())),
         },
         timeout: TIMEOUT,
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell(&mut messages),
     })?;
 
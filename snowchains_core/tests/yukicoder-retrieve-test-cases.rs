@@ -60,6 +60,7 @@ fn test(target: Target) -> eyre::Result<()> {
         }
     }
 
+    #[allow(dead_code)]
     #[derive(Debug)]
     enum Message {
         PrintAnsi(String),
@@ -83,6 +84,9 @@ fn test(target: Target) -> eyre::Result<()> {
         full: None,
         cookie_storage: (),
         timeout: TIMEOUT,
+        retry: Default::default(),
+        min_interval: Default::default(),
+        proxy: None,
         shell: Shell(&mut messages),
     })?;
 
@@ -0,0 +1,159 @@
+use eyre::{bail, ContextCompat as _, WrapErr as _};
+use snowchains_core::{
+    color_spec,
+    testsuite::{PartialBatchTestCase, TestSuite},
+    web::PlatformKind,
+};
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptAddTestcase {
+    /// Path to the input. `-` for stdin
+    #[structopt(long("in"), value_name("PATH"))]
+    pub r#in: PathBuf,
+
+    /// Path to the expected output. `-` for stdin
+    #[structopt(long, value_name("PATH"))]
+    pub out: Option<PathBuf>,
+
+    /// Name of the test case. A number is appended if the name is already used
+    #[structopt(long, value_name("NAME"), default_value("custom"))]
+    pub name: String,
+
+    /// Timelimit for the test case
+    #[structopt(long, value_name("DURATION"))]
+    pub timelimit: Option<humantime::Duration>,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptAddTestcase,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptAddTestcase {
+        r#in,
+        out,
+        name,
+        timelimit,
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    if r#in.as_os_str() == "-" && matches!(&out, Some(p) if p.as_os_str() == "-") {
+        bail!("`--in` and `--out` cannot both be `-`");
+    }
+
+    let (detected_target, workspace) = crate::config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let problem = problem
+        .or(detected_target.problem)
+        .with_context(|| "`problem` was not detected. To specify it, add it to the arguments")?;
+
+    let test_suite_path = workspace
+        .join(".snowchains")
+        .join("tests")
+        .join(service.to_kebab_case_str())
+        .join(contest.as_deref().unwrap_or(""))
+        .join(problem)
+        .with_extension("yml");
+
+    let mut test_suite = crate::judge::read_or_new_batch_suite(&test_suite_path)?;
+
+    let mut read = |path: &Path| -> eyre::Result<String> {
+        if path.as_os_str() != "-" {
+            return crate::fs::read_to_string(cwd.join(path));
+        }
+        shell
+            .read_stdin_to_string()
+            .wrap_err("Could not read stdin")
+    };
+
+    let input = read(&r#in)?;
+    let output = out.as_deref().map(read).transpose()?;
+
+    let is_used = |name: &str| {
+        test_suite
+            .cases
+            .iter()
+            .any(|c| c.name.as_deref() == Some(name))
+    };
+    let name = if is_used(&name) {
+        (2..)
+            .map(|i| format!("{}-{}", name, i))
+            .find(|name| !is_used(name))
+            .expect("should be found")
+    } else {
+        name
+    };
+
+    test_suite.cases.push(PartialBatchTestCase {
+        name: Some(name.clone()),
+        r#in: input.into(),
+        out: output.map(Into::into),
+        timelimit: timelimit.map(Into::into),
+        memorylimit: None,
+        r#match: None,
+    });
+
+    crate::judge::write_test_suite(
+        &mut shell.stderr,
+        &test_suite_path,
+        &TestSuite::Batch(test_suite),
+    )?;
+
+    write!(shell.stderr, "Added {:?} to ", name)?;
+    shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(shell.stderr, "{}", test_suite_path.display())?;
+    shell.stderr.reset()?;
+    writeln!(shell.stderr)?;
+    shell.stderr.flush()?;
+
+    Ok(())
+}
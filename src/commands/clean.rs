@@ -0,0 +1,197 @@
+use crate::config;
+use eyre::{bail, ContextCompat as _, WrapErr as _};
+use snowchains_core::{color_spec, testsuite::ByteSize, web::PlatformKind};
+use std::{
+    collections::BTreeSet,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptClean {
+    /// Cleans the outputs of all the languages instead of the current one
+    #[structopt(long)]
+    pub all: bool,
+
+    /// Does not ask for confirmation
+    #[structopt(short, long)]
+    pub yes: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Language name
+    #[structopt(short, long, value_name("STRING"), conflicts_with("all"))]
+    pub language: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptClean,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptClean {
+        all,
+        yes,
+        config,
+        color: _,
+        service,
+        contest,
+        language,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (detected_target, _) = config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let problem = problem
+        .or(detected_target.problem)
+        .with_context(|| "`problem` was not detected. To specify it, add it to the arguments")?;
+
+    let language = if all {
+        None
+    } else {
+        Some(language.or(detected_target.language).with_context(|| {
+            "`language` was not detected. To specify it, add `--language` to the arguments"
+        })?)
+    };
+
+    let mut srcs = BTreeSet::new();
+    let mut outputs = BTreeSet::new();
+
+    // The outputs may differ between the modes.
+    for mode in [config::Mode::Debug, config::Mode::Release] {
+        let target = config::Target {
+            service,
+            contest: contest.clone(),
+            problem: problem.clone(),
+            mode,
+        };
+
+        let (languages, _, base_dir) =
+            config::languages_for_target(&cwd, config.as_deref(), &target)?;
+
+        if let Some(language) = &language {
+            if !languages.contains_key(language) {
+                bail!("No such language: `{}`", language);
+            }
+        }
+
+        let resolve = |path: &str| {
+            let path = Path::new(path);
+            base_dir.join(path.strip_prefix(".").unwrap_or(path))
+        };
+
+        for (
+            name,
+            config::Language {
+                src,
+                transpile,
+                compile,
+                ..
+            },
+        ) in &languages
+        {
+            // Every `src` is kept, even if another language writes its output there.
+            srcs.insert(resolve(src));
+
+            if language.as_ref().is_some_and(|l| l != name) {
+                continue;
+            }
+
+            for config::Compile { output, .. } in transpile.iter().chain(compile) {
+                let output = resolve(output);
+                let mut state_file_name = output.file_name().unwrap_or_default().to_owned();
+                state_file_name.push(".snowchains-build.json");
+                let state = output.with_file_name(state_file_name);
+                outputs.extend([output, state]);
+            }
+        }
+    }
+
+    let files = outputs
+        .into_iter()
+        .filter(|p| !srcs.contains(p))
+        .filter_map(|p| Some((p.metadata().ok().filter(|m| m.is_file())?.len(), p)))
+        .collect::<Vec<_>>();
+
+    if files.is_empty() {
+        writeln!(shell.stderr, "Nothing to clean")?;
+        shell.stderr.flush()?;
+        return Ok(());
+    }
+
+    for (size, path) in &files {
+        shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(shell.stderr, "{}", path.display())?;
+        shell.stderr.reset()?;
+        writeln!(
+            shell.stderr,
+            " ({})",
+            ByteSize::from_bytes(*size).to_human_readable()
+        )?;
+    }
+    shell.stderr.flush()?;
+
+    if !yes {
+        let reply = shell.read_reply("Remove them? [y/N] ")?;
+        if !matches!(reply.trim(), "y" | "Y" | "yes") {
+            writeln!(shell.stderr, "Cancelled")?;
+            shell.stderr.flush()?;
+            return Ok(());
+        }
+    }
+
+    for (_, path) in &files {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Could not remove `{}`", path.display()))?;
+    }
+
+    let freed = files.iter().map(|(size, _)| size).sum();
+
+    writeln!(
+        shell.stderr,
+        "Removed {} file(s), {}",
+        files.len(),
+        ByteSize::from_bytes(freed).to_human_readable(),
+    )?;
+    shell.stderr.flush()?;
+    Ok(())
+}
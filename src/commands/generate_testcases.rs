@@ -0,0 +1,255 @@
+use eyre::{ContextCompat as _, WrapErr as _};
+use snowchains_core::{
+    color_spec,
+    testsuite::{PartialBatchTestCase, TestSuite},
+    web::PlatformKind,
+};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptGenerateTestcases {
+    /// Bash command that prints an input. The seed is given as `$1`
+    #[structopt(long, value_name("CMD"))]
+    pub generator: String,
+
+    /// Bash command that prints the expected output for the input. If omitted, the test cases
+    /// have no expected outputs
+    #[structopt(long, value_name("CMD"))]
+    pub brute: Option<String>,
+
+    /// Number of the test cases
+    #[structopt(short("n"), long, value_name("COUNT"), default_value("10"))]
+    pub count: u64,
+
+    /// First seed
+    #[structopt(long, value_name("INTEGER"), default_value("0"))]
+    pub seed: u64,
+
+    /// Replaces the test cases generated before instead of adding to them
+    #[structopt(long)]
+    pub overwrite: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptGenerateTestcases,
+    ctx: crate::Context<impl Sized, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptGenerateTestcases {
+        generator,
+        brute,
+        count,
+        seed,
+        overwrite,
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (detected_target, workspace) = crate::config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let problem = problem
+        .or(detected_target.problem)
+        .with_context(|| "`problem` was not detected. To specify it, add it to the arguments")?;
+
+    let test_suite_path = workspace
+        .join(".snowchains")
+        .join("tests")
+        .join(service.to_kebab_case_str())
+        .join(contest.as_deref().unwrap_or(""))
+        .join(problem)
+        .with_extension("yml");
+
+    let mut test_suite = crate::judge::read_or_new_batch_suite(&test_suite_path)?;
+
+    let generated_index = |case: &PartialBatchTestCase| -> Option<u64> {
+        case.name.as_deref()?.strip_prefix("gen-")?.parse().ok()
+    };
+
+    if overwrite {
+        test_suite.cases.retain(|c| generated_index(c).is_none());
+    }
+
+    let first_index = test_suite
+        .cases
+        .iter()
+        .flat_map(generated_index)
+        .max()
+        .map_or(1, |i| i + 1);
+
+    let encoding = test_suite.encoding.unwrap_or_default();
+
+    for i in 0..count {
+        let seed = seed + i;
+
+        let input = crate::judge::bash_output(&generator, Some(seed), "", &workspace, encoding)
+            .with_context(|| format!("The generator failed with seed {}", seed))?;
+
+        let output = brute
+            .as_ref()
+            .map(|brute| {
+                crate::judge::bash_output(brute, None, &input, &workspace, encoding)
+                    .with_context(|| format!("The brute-force solution failed with seed {}", seed))
+            })
+            .transpose()?;
+
+        test_suite.cases.push(PartialBatchTestCase {
+            name: Some(format!("gen-{:04}", first_index + i)),
+            r#in: input.into(),
+            out: output.map(Into::into),
+            timelimit: None,
+            memorylimit: None,
+            r#match: None,
+        });
+    }
+
+    crate::judge::write_test_suite(
+        &mut shell.stderr,
+        &test_suite_path,
+        &TestSuite::Batch(test_suite),
+    )?;
+
+    write!(shell.stderr, "Generated {} test case(s) in ", count)?;
+    shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+    write!(shell.stderr, "{}", test_suite_path.display())?;
+    shell.stderr.reset()?;
+    writeln!(shell.stderr)?;
+    shell.stderr.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[test]
+    fn generate_testcases() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        let test_suite_path = dir
+            .path()
+            .join(".snowchains")
+            .join("tests")
+            .join("atcoder")
+            .join("abc")
+            .join("a.yml");
+
+        fs::create_dir_all(test_suite_path.parent().unwrap())?;
+        fs::write(
+            &test_suite_path,
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+  - name: sample
+    in: "1 1\n"
+    out: "2\n"
+  - name: gen-0001
+    in: "0 0\n"
+    out: "0\n"
+"#,
+        )?;
+
+        let generate = |args: &[&str]| -> eyre::Result<Vec<(String, String, String)>> {
+            let (result, _, _) = crate::testing::run(
+                dir.path(),
+                &[
+                    &["generate-testcases", "--generator", "echo \"$1\" 1"],
+                    args,
+                    &["a"],
+                ]
+                .concat(),
+            );
+            result?;
+
+            let test_suite = fs::read_to_string(&test_suite_path)?;
+            let test_suite = serde_yaml::from_str::<serde_yaml::Value>(&test_suite)?;
+            assert_eq!("2s", test_suite["timelimit"]);
+
+            Ok(test_suite["cases"]
+                .as_sequence()
+                .unwrap()
+                .iter()
+                .map(|case| {
+                    let field = |k: &str| case[k].as_str().unwrap_or("~").to_owned();
+                    (field("name"), field("in"), field("out"))
+                })
+                .collect())
+        };
+        let case = |name: &str, input: &str, output: &str| {
+            (name.to_owned(), input.to_owned(), output.to_owned())
+        };
+
+        // Added after the existing ones.
+        assert_eq!(
+            vec![
+                case("sample", "1 1\n", "2\n"),
+                case("gen-0001", "0 0\n", "0\n"),
+                case("gen-0002", "3 1\n", "4\n"),
+                case("gen-0003", "4 1\n", "5\n"),
+            ],
+            generate(&[
+                "--brute",
+                "read a b; echo $((a + b))",
+                "-n",
+                "2",
+                "--seed",
+                "3",
+            ])?,
+        );
+
+        // Only the generated ones are replaced.
+        assert_eq!(
+            vec![
+                case("sample", "1 1\n", "2\n"),
+                case("gen-0001", "5 1\n", "~")
+            ],
+            generate(&["-n", "1", "--seed", "5", "--overwrite"])?,
+        );
+        Ok(())
+    }
+}
@@ -72,6 +72,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::result_large_err)]
     fn run_resolve_default_config_dhall() -> eyre::Result<()> {
         // https://docs.rs/dhall/0.6.0/src/dhall/semantics/resolve/cache.rs.html#15-35
 
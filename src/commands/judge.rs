@@ -1,10 +1,21 @@
 use crate::config;
+use eyre::{bail, WrapErr as _};
 use human_size::Size;
-use snowchains_core::web::PlatformKind;
-use std::path::PathBuf;
+use snowchains_core::{
+    judge::{DiffStyle, Jobs, JudgeEventWriter, Palette},
+    testsuite::{MatchOverride, PartialBatchTestCase, PositiveFinite},
+    web::PlatformKind,
+};
+use std::{
+    io::{self, BufRead},
+    mem,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
 use structopt::StructOpt;
 use strum::VariantNames as _;
-use termcolor::WriteColor;
+use termcolor::{NoColor, WriteColor};
 
 #[derive(StructOpt, Debug)]
 pub struct OptJudge {
@@ -12,14 +23,128 @@ pub struct OptJudge {
     #[structopt(long)]
     pub release: bool,
 
-    /// Test for only the test cases
-    #[structopt(long, value_name("NAME"))]
+    /// Builds even if the source code and the command have not changed since the last build
+    #[structopt(long)]
+    pub force_compile: bool,
+
+    /// Test for only the test cases. Names may contain wildcards (e.g. `random-*`)
+    #[structopt(long, visible_alias("case"), value_name("NAME"))]
     pub testcases: Option<Vec<String>>,
 
+    /// Test for only the test cases that failed last time. Tests all if there is no record
+    #[structopt(long, conflicts_with("testcases"))]
+    pub only_failed: bool,
+
+    /// Tests for only a test case with the input, instead of the ones in the test suite. The
+    /// settings of the test suite such as `match` are still used. `-` for stdin
+    #[structopt(
+        long("in"),
+        value_name("PATH"),
+        conflicts_with_all(&["testcases", "only-failed"])
+    )]
+    pub r#in: Option<PathBuf>,
+
+    /// Expected output for `--in`. `-` for stdin
+    #[structopt(long, value_name("PATH"), requires("in"))]
+    pub out: Option<PathBuf>,
+
     /// Display limit
     #[structopt(long, value_name("SIZE"), default_value("4KiB"))]
     pub display_limit: Size,
 
+    /// Display limit for stderr [default: the value of `--display-limit`]
+    #[structopt(long, value_name("SIZE"))]
+    pub stderr_display_limit: Option<Size>,
+
+    /// Describes why each test case failed
+    #[structopt(long)]
+    pub explain: bool,
+
+    /// How the outputs of wrong answers are shown
+    #[structopt(
+        long,
+        value_name("STYLE"),
+        possible_values(DiffStyle::VARIANTS),
+        default_value("raw")
+    )]
+    pub diff: DiffStyle,
+
+    /// Reports when the program did not read all of the input. Reading through a buffer may hide
+    /// unread input
+    #[structopt(long)]
+    pub track_stdin: bool,
+
+    /// Fails when the program runs more threads than this at once. Only supported on Linux
+    #[structopt(long, value_name("COUNT"))]
+    pub max_threads: Option<usize>,
+
+    /// Reports the peak memory usage of each test case. Only supported on Linux
+    #[structopt(long)]
+    pub measure_memory: bool,
+
+    /// Only measures the elapsed times, without comparing the outputs
+    #[structopt(long)]
+    pub time_only: bool,
+
+    /// Ignores the timelimits of the test cases. The program is only stopped by Ctrl-C
+    #[structopt(long)]
+    pub no_timelimit: bool,
+
+    /// Prints the stdout and the stderr of the program as they are written. Requires exactly one
+    /// test case to run
+    #[structopt(long)]
+    pub verbose: bool,
+
+    /// Lists the test cases with the sizes of the inputs and the expected outputs and the
+    /// timelimits, without building or running anything
+    #[structopt(long)]
+    pub list_cases: bool,
+
+    /// Compares as floating-point numbers with the absolute error, overriding the test suite
+    #[structopt(long, value_name("FLOAT"))]
+    pub float_abs: Option<PositiveFinite<f64>>,
+
+    /// Compares as floating-point numbers with the relative error, overriding the test suite
+    #[structopt(long, value_name("FLOAT"))]
+    pub float_rel: Option<PositiveFinite<f64>>,
+
+    /// Ignores the order of lines, overriding the test suite
+    #[structopt(long)]
+    pub unordered: bool,
+
+    /// Ignores letter case, overriding the test suite
+    #[structopt(long)]
+    pub case_insensitive: bool,
+
+    /// Uses the preset in `matchPresets`, overriding the test suite
+    #[structopt(long, value_name("NAME"))]
+    pub preset: Option<String>,
+
+    /// Runs the command with Bash for each failed test case after the summary. `$INPUT`,
+    /// `$EXPECTED_OUTPUT`, and `$ACTUAL_OUTPUT` are paths to temporary files, and `$TEST_CASE_NAME`
+    /// and `$VERDICT` are also set
+    #[structopt(long, value_name("CMD"))]
+    pub on_failure: Option<String>,
+
+    /// Colors of the verdicts: `default`, `colorblind`, or `<role>=<color>` overrides following
+    /// them (e.g. `colorblind,failure=magenta`). The roles are `success`, `warning`, and `failure`
+    #[structopt(long, value_name("PALETTE"), default_value("default"))]
+    pub palette: Palette,
+
+    /// How many test cases are run at once: a positive integer or `auto` for the number of the
+    /// CPUs
+    #[structopt(short, long, value_name("COUNT"), default_value("auto"))]
+    pub jobs: Jobs,
+
+    /// Writes JSON data to the file (`-` for stdout, in which case the report is printed to stderr
+    /// instead)
+    #[structopt(long, value_name("PATH"))]
+    pub json: Option<PathBuf>,
+
+    /// Writes JSON-lines progress events to the file (`-` for stdout)
+    #[structopt(long, value_name("PATH"))]
+    pub events: Option<PathBuf>,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -55,12 +180,36 @@ pub struct OptJudge {
 
 pub(crate) fn run(
     opt: OptJudge,
-    ctx: crate::Context<impl Sized, impl WriteColor, impl WriteColor>,
+    ctx: crate::Context<impl BufRead, impl WriteColor + Send, impl WriteColor>,
 ) -> eyre::Result<()> {
     let OptJudge {
         release,
+        force_compile,
         testcases,
+        only_failed,
+        r#in,
+        out,
         display_limit,
+        stderr_display_limit,
+        explain,
+        diff,
+        track_stdin,
+        max_threads,
+        measure_memory,
+        time_only,
+        no_timelimit,
+        verbose,
+        list_cases,
+        float_abs,
+        float_rel,
+        unordered,
+        case_insensitive,
+        preset,
+        on_failure,
+        palette,
+        jobs,
+        json,
+        events,
         config,
         color: _,
         service,
@@ -69,13 +218,44 @@ pub(crate) fn run(
         problem,
     } = opt;
 
-    let crate::Context { cwd, shell } = ctx;
+    let crate::Context { cwd, mut shell } = ctx;
+
+    if matches!(&r#in, Some(p) if p.as_os_str() == "-")
+        && matches!(&out, Some(p) if p.as_os_str() == "-")
+    {
+        bail!(
+            "`--in` and `--out` cannot both read stdin. Write the expected output to a file and \
+             give it as `--out <PATH>`",
+        );
+    }
+
+    let mut read = |path: &Path| -> eyre::Result<String> {
+        if path.as_os_str() != "-" {
+            return crate::fs::read_to_string(cwd.join(path));
+        }
+        shell
+            .read_stdin_to_string()
+            .wrap_err("Could not read stdin")
+    };
+
+    let ad_hoc_case = r#in
+        .map(|r#in| -> eyre::Result<_> {
+            Ok(PartialBatchTestCase {
+                name: Some("custom".to_owned()),
+                r#in: read(&r#in)?.into(),
+                out: out.as_deref().map(&mut read).transpose()?.map(Into::into),
+                timelimit: None,
+                memorylimit: None,
+                r#match: None,
+            })
+        })
+        .transpose()?;
 
     let progress_draw_target = shell.progress_draw_target();
 
     let crate::shell::Shell {
         stdout,
-        stderr,
+        mut stderr,
         stdin_process_redirection,
         stdout_process_redirection,
         stderr_process_redirection,
@@ -95,6 +275,11 @@ pub(crate) fn run(
             compile,
             run,
             languageId: _,
+            template: _,
+            runner,
+            timelimitMultiplier: timelimit_multiplier,
+            submitTransform: _,
+            format,
         },
         base_dir,
     ) = config::target_and_language(
@@ -109,26 +294,278 @@ pub(crate) fn run(
         } else {
             config::Mode::Debug
         },
+        &mut stderr,
     )?;
 
     let test_case_names = testcases.map(|ss| ss.into_iter().collect());
 
-    crate::judge::judge(crate::judge::Args {
-        stdout,
-        stderr,
-        stdin_process_redirection,
-        stdout_process_redirection,
-        stderr_process_redirection,
-        progress_draw_target,
-        base_dir,
-        service,
-        contest,
-        problem,
-        src,
-        transpile,
-        compile,
-        run,
-        test_case_names,
-        display_limit,
+    let match_override = MatchOverride {
+        relative_error: float_rel,
+        absolute_error: float_abs,
+        unordered,
+        case_insensitive,
+    };
+
+    let match_presets = {
+        let (cwd, config) = (cwd.clone(), config.clone());
+        Box::new(move || config::match_presets(&cwd, config.as_deref()))
+    };
+
+    let is_stdout = |path: &Option<PathBuf>| matches!(path, Some(p) if p.as_os_str() == "-");
+    if is_stdout(&json) && is_stdout(&events) {
+        bail!("`--json` and `--events` cannot both be `-`");
+    }
+    let print_report_to_stderr = is_stdout(&json) || is_stdout(&events);
+    let json = json.map(|path| {
+        if path.as_os_str() == "-" {
+            path
+        } else {
+            cwd.join(path)
+        }
+    });
+
+    let events_to_stdout = is_stdout(&events);
+    let events = events
+        .filter(|_| !events_to_stdout)
+        .map(|path| -> eyre::Result<_> {
+            Ok(JudgeEventWriter::new(crate::fs::create(cwd.join(path))?))
+        })
+        .transpose()?;
+
+    let judge = move |stdout, events| {
+        crate::judge::judge(crate::judge::Args {
+            stdout,
+            stderr,
+            stdin_process_redirection,
+            stdout_process_redirection,
+            stderr_process_redirection,
+            progress_draw_target,
+            base_dir,
+            service,
+            contest,
+            problem,
+            src,
+            format,
+            transpile,
+            compile,
+            force_compile,
+            run,
+            runner: runner.unwrap_or_default(),
+            timelimit_multiplier,
+            test_case_names,
+            only_failed,
+            ad_hoc_case,
+            match_override,
+            preset,
+            match_presets,
+            display_limit,
+            stderr_display_limit,
+            explain,
+            diff,
+            track_stdin,
+            max_threads,
+            measure_memory,
+            time_only,
+            no_timelimit,
+            verbose,
+            list_cases,
+            on_failure,
+            palette,
+            jobs,
+            json,
+            events,
+            print_report_to_stderr,
+        })
+    };
+
+    thread::scope(|scope| {
+        // The events are sent to this thread together with the other outputs, so that they are
+        // written to the stdout of the shell in order.
+        let (stdout, events, writer): (Box<dyn WriteColor + '_>, _, _) = if events_to_stdout {
+            let (tx, rx) = mpsc::channel::<Vec<u8>>();
+            let mut stdout = stdout;
+            let writer = scope.spawn(move || -> io::Result<()> {
+                for bytes in rx {
+                    stdout.write_all(&bytes)?;
+                    stdout.flush()?;
+                }
+                Ok(())
+            });
+            (
+                Box::new(NoColor::new(ChannelWriter::new(tx.clone()))),
+                Some(JudgeEventWriter::new(ChannelWriter::new(tx))),
+                Some(writer),
+            )
+        } else {
+            (Box::new(stdout), events, None)
+        };
+
+        let result = judge(stdout, events);
+
+        if let Some(writer) = writer {
+            writer.join().unwrap()?;
+        }
+        result
     })
 }
+
+/// Sends the written bytes to a channel on each flush.
+struct ChannelWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+    buf: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn new(tx: mpsc::Sender<Vec<u8>>) -> Self {
+        Self { tx, buf: vec![] }
+    }
+}
+
+impl io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.tx
+                .send(mem::take(&mut self.buf))
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the stdout is closed"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ChannelWriter {
+    fn drop(&mut self) {
+        let _ = io::Write::flush(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[test]
+    fn ad_hoc_case_uses_match_of_test_suite() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        fs::write(dir.path().join("a.sh"), "read a b; echo \"$a  $b\"\n")?;
+        fs::write(dir.path().join("in.txt"), "1 2\n")?;
+        fs::write(dir.path().join("out.txt"), "1 2\n")?;
+
+        let judge = || {
+            let (result, stdout, _) = crate::testing::run(
+                dir.path(),
+                &["judge", "--in", "in.txt", "--out", "out.txt", "a"],
+            );
+            (result.map_err(|e| e.to_string()), stdout)
+        };
+
+        // `Lines` without a test suite.
+        let (result, stdout) = judge();
+        assert!(result.is_err());
+        assert!(stdout.contains("(\"custom\") Wrong Answer"), "{}", stdout);
+
+        let test_suite_dir = dir
+            .path()
+            .join(".snowchains")
+            .join("tests")
+            .join("atcoder")
+            .join("abc");
+        fs::create_dir_all(&test_suite_dir)?;
+        fs::write(
+            test_suite_dir.join("a.yml"),
+            "type: Batch\ntimelimit: 2s\nmatch: SplitWhitespace\n\ncases: []\n",
+        )?;
+
+        let (result, stdout) = judge();
+        assert_eq!(Ok(()), result);
+        assert!(stdout.contains("(\"custom\") Accepted"), "{}", stdout);
+        Ok(())
+    }
+
+    #[test]
+    fn in_and_out_from_stdin() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+
+        let (result, _, _) =
+            crate::testing::run(dir.path(), &["judge", "--in", "-", "--out", "-", "a"]);
+        assert_eq!(
+            "`--in` and `--out` cannot both read stdin. Write the expected output to a file and \
+             give it as `--out <PATH>`",
+            result.unwrap_err().to_string(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn events_to_stdout() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        fs::write(dir.path().join("a.sh"), "cat\n")?;
+        fs::write(dir.path().join("in.txt"), "1\n")?;
+
+        let (result, stdout, stderr) = crate::testing::run(
+            dir.path(),
+            &["judge", "--in", "in.txt", "--events", "-", "a"],
+        );
+        result?;
+
+        let events = stdout
+            .lines()
+            .map(|line| Ok(serde_json::from_str::<serde_json::Value>(line)?["event"].clone()))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        assert_eq!(["case-start", "case-done", "summary"], &*events);
+        assert!(stderr.contains("(\"custom\") Accepted"), "{}", stderr);
+
+        let (result, _, _) = crate::testing::run(
+            dir.path(),
+            &[
+                "judge", "--in", "in.txt", "--events", "-", "--json", "-", "a",
+            ],
+        );
+        assert_eq!(
+            "`--json` and `--events` cannot both be `-`",
+            result.unwrap_err().to_string(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn on_failure_with_input_file() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        let suite_dir = dir.path().join(".snowchains/tests/atcoder/abc");
+        fs::create_dir_all(&suite_dir)?;
+        fs::write(dir.path().join("a.sh"), "cat\n")?;
+        fs::write(suite_dir.join("a.in"), "1\n")?;
+        fs::write(
+            suite_dir.join("a.yml"),
+            "type: Batch\nmatch: Exact\ncases:\n  - in:\n      file: a.in\n    out: \"2\\n\"\n",
+        )?;
+
+        let (result, _, _) = crate::testing::run(
+            dir.path(),
+            &["judge", "--on-failure", "cp \"$INPUT\" ./input.txt", "a"],
+        );
+        assert_eq!("1/1 tests failed", result.unwrap_err().to_string());
+        assert_eq!("1\n", fs::read_to_string(dir.path().join("input.txt"))?);
+        Ok(())
+    }
+
+    #[test]
+    fn verbose_timelimit_exceeded() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        let suite_dir = dir.path().join(".snowchains/tests/atcoder/abc");
+        fs::create_dir_all(&suite_dir)?;
+        fs::write(dir.path().join("a.sh"), "echo 1; sleep 10\n")?;
+        fs::write(
+            suite_dir.join("a.yml"),
+            "type: Batch\ntimelimit: 200ms\nmatch: Exact\ncases:\n  - in: \"\"\n    out: \"1\\n\"\n",
+        )?;
+
+        let (result, stdout, _) = crate::testing::run(dir.path(), &["judge", "--verbose", "a"]);
+        assert_eq!("1/1 tests failed", result.unwrap_err().to_string());
+        assert!(stdout.contains("Timelimit Exceeded (200 ms)"), "{}", stdout);
+        Ok(())
+    }
+}
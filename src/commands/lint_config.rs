@@ -0,0 +1,53 @@
+use eyre::bail;
+use snowchains_core::color_spec;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptLintConfig {
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+}
+
+pub(crate) fn run(
+    opt: OptLintConfig,
+    ctx: crate::Context<impl Sized, impl Sized, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptLintConfig { config, color: _ } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (path, problems) = crate::config::verify(&cwd, config.as_deref())?;
+
+    for message in &problems {
+        shell.stderr.set_color(color_spec!(Bold, Fg(Color::Red)))?;
+        write!(shell.stderr, "error:")?;
+        shell.stderr.reset()?;
+        writeln!(shell.stderr, " {}: {}", path.display(), message)?;
+    }
+
+    if !problems.is_empty() {
+        shell.stderr.flush()?;
+        bail!(
+            "Found {} problem{} in `{}`",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            path.display(),
+        );
+    }
+
+    writeln!(shell.stderr, "`{}` is valid", path.display())?;
+    shell.stderr.flush()?;
+    Ok(())
+}
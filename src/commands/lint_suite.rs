@@ -0,0 +1,360 @@
+use eyre::{bail, ContextCompat as _};
+use snowchains_core::{
+    color_spec,
+    testsuite::{BatchTestSuite, InteractiveTestSuite, TestSuite},
+    web::PlatformKind,
+};
+use std::{collections::HashSet, io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptLintSuite {
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptLintSuite,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptLintSuite {
+        config,
+        color: _,
+        service,
+        contest,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (detected_target, workspace) = crate::config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let problem = problem
+        .or(detected_target.problem)
+        .with_context(|| "`problem` was not detected. To specify it, add it to the arguments")?;
+
+    let test_suite_dir = workspace
+        .join(".snowchains")
+        .join("tests")
+        .join(service.to_kebab_case_str())
+        .join(contest.as_deref().unwrap_or(""));
+    let test_suite_path = test_suite_dir.join(problem).with_extension("yml");
+
+    let content = crate::fs::read_to_string(&test_suite_path)?;
+
+    let mut problems = vec![];
+
+    match serde_yaml::from_str::<TestSuite>(&content) {
+        Ok(TestSuite::Batch(mut test_suite)) => {
+            if let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+                const KEYS: &[&str] = &[
+                    "type",
+                    "timelimit",
+                    "memorylimit",
+                    "match",
+                    "whitespace",
+                    "encoding",
+                    "cases",
+                    "extend",
+                ];
+                const CASE_KEYS: &[&str] =
+                    &["name", "in", "out", "timelimit", "memorylimit", "match"];
+
+                let unknown_keys = |value: &serde_yaml::Value, known: &[&str]| {
+                    value
+                        .as_mapping()
+                        .into_iter()
+                        .flat_map(|m| m.iter().map(|(k, _)| k))
+                        .flat_map(|k| k.as_str())
+                        .filter(|k| !known.contains(k))
+                        .map(ToOwned::to_owned)
+                        .collect::<Vec<_>>()
+                };
+
+                for key in unknown_keys(&value, KEYS) {
+                    problems.push((None, format!("Unknown key: `{}`", key)));
+                }
+                let cases = value.get("cases").and_then(serde_yaml::Value::as_sequence);
+                for (i, case) in cases.into_iter().flatten().enumerate() {
+                    for key in unknown_keys(case, CASE_KEYS) {
+                        problems.push((None, format!("Unknown key in case #{}: `{}`", i + 1, key)));
+                    }
+                }
+            }
+
+            let mut names = HashSet::new();
+            for name in test_suite.cases.iter().flat_map(|c| &c.name) {
+                if !names.insert(name) {
+                    problems.push((None, format!("Duplicated name: {:?}", name)));
+                }
+            }
+
+            let presets_resolved = test_suite.presets().next().is_none()
+                || crate::config::match_presets(&cwd, config.as_deref())
+                    .and_then(|presets| test_suite.resolve_presets(&presets))
+                    .map_err(|err| problems.push((None, format!("{:#}", err))))
+                    .is_ok();
+
+            if presets_resolved {
+                match test_suite
+                    .load_test_cases(&test_suite_dir, None::<HashSet<String>>, |_| Ok(vec![]))
+                {
+                    Ok(test_cases) => {
+                        for (i, test_case) in test_cases.iter().enumerate() {
+                            if let Err(err) = test_case.encoding.encode(&test_case.input) {
+                                let name = test_case.name.as_deref().unwrap_or("");
+                                problems.push((None, format!("#{} ({:?}): {}", i + 1, name, err)));
+                            }
+                        }
+                    }
+                    Err(err) => problems.push((None, format!("{:#}", err))),
+                }
+            }
+        }
+        Ok(TestSuite::Interactive(_)) | Ok(TestSuite::Unsubmittable) => {}
+        Err(err) => {
+            // Errors through internally tagged enums lose their positions.
+            let err = serde_yaml::from_str::<serde_yaml::Value>(&content)
+                .ok()
+                .and_then(|value| {
+                    match value.get("type")?.as_str()? {
+                        "Batch" => serde_yaml::from_str::<BatchTestSuite>(&content).err(),
+                        "Interactive" => {
+                            serde_yaml::from_str::<InteractiveTestSuite>(&content).err()
+                        }
+                        _ => None,
+                    }
+                    .filter(|err| err.location().is_some())
+                })
+                .unwrap_or(err);
+
+            let location = err.location().map(|l| (l.line(), l.column()));
+            problems.push((location, err.to_string()));
+        }
+    }
+
+    for (location, message) in &problems {
+        shell.stderr.set_color(color_spec!(Bold, Fg(Color::Red)))?;
+        write!(shell.stderr, "error:")?;
+        shell.stderr.reset()?;
+
+        write!(shell.stderr, " {}", test_suite_path.display())?;
+        if let Some((line, column)) = location {
+            write!(shell.stderr, ":{}:{}", line, column)?;
+        }
+        writeln!(shell.stderr, ": {}", message)?;
+
+        if let Some((line, column)) = *location {
+            if let Some(text) = content.lines().nth(line.saturating_sub(1)) {
+                let gutter = line.to_string().len();
+
+                shell.stderr.set_color(color_spec!(Bold, Fg(Color::Blue)))?;
+                writeln!(shell.stderr, "{:gutter$} |", "", gutter = gutter)?;
+                write!(shell.stderr, "{} |", line)?;
+                shell.stderr.reset()?;
+                writeln!(shell.stderr, " {}", text)?;
+                shell.stderr.set_color(color_spec!(Bold, Fg(Color::Blue)))?;
+                write!(shell.stderr, "{:gutter$} |", "", gutter = gutter)?;
+                shell.stderr.reset()?;
+                shell.stderr.set_color(color_spec!(Bold, Fg(Color::Red)))?;
+                writeln!(shell.stderr, " {:>column$}", "^", column = column)?;
+                shell.stderr.reset()?;
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        shell.stderr.flush()?;
+        bail!(
+            "Found {} problem{} in `{}`",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            test_suite_path.display(),
+        );
+    }
+
+    writeln!(shell.stderr, "`{}` is valid", test_suite_path.display())?;
+    shell.stderr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    fn lint(test_suite: &str) -> eyre::Result<(Result<(), String>, String)> {
+        let dir = crate::testing::project()?;
+        let test_suite_dir = dir
+            .path()
+            .join(".snowchains")
+            .join("tests")
+            .join("atcoder")
+            .join("abc");
+        fs::create_dir_all(&test_suite_dir)?;
+        fs::write(test_suite_dir.join("a.yml"), test_suite)?;
+
+        let (result, _, stderr) = crate::testing::run(dir.path(), &["lint-suite", "a"]);
+        let path = test_suite_dir.join("a.yml").display().to_string();
+        Ok((
+            result.map_err(|e| e.to_string().replace(&path, "a.yml")),
+            stderr.replace(&path, "a.yml"),
+        ))
+    }
+
+    #[test]
+    fn valid() -> eyre::Result<()> {
+        let (result, stderr) = lint(
+            r#"type: Batch
+timelimit: 2s
+match:
+  Preset: exact
+whitespace:
+  trim_trailing: true
+
+cases:
+  - name: sample1
+    in: "1\n"
+    out: "1\n"
+"#,
+        )?;
+        assert_eq!(Ok(()), result);
+        assert_eq!("`a.yml` is valid\n", stderr);
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_error() -> eyre::Result<()> {
+        let (result, stderr) = lint("type: Batch\ntimelimit: 2s\nmatch: Lines\ncases: 1\n")?;
+        assert_eq!(Err("Found 1 problem in `a.yml`".to_owned()), result);
+        assert!(stderr.starts_with("error: a.yml:4:"), "{}", stderr);
+        assert!(stderr.contains("4 | cases: 1\n"), "{}", stderr);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_keys_and_duplicated_names() -> eyre::Result<()> {
+        let (result, stderr) = lint(
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+timelimt: 1s
+
+cases:
+  - name: sample1
+    in: "1\n"
+    out: "1\n"
+  - name: sample1
+    in: "2\n"
+    output: "2\n"
+"#,
+        )?;
+        assert_eq!(Err("Found 3 problems in `a.yml`".to_owned()), result);
+        assert_eq!(
+            "error: a.yml: Unknown key: `timelimt`\n\
+             error: a.yml: Unknown key in case #2: `output`\n\
+             error: a.yml: Duplicated name: \"sample1\"\n",
+            stderr,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_such_preset() -> eyre::Result<()> {
+        let (result, stderr) = lint(
+            r#"type: Batch
+timelimit: 2s
+match:
+  Preset: geometry
+
+cases: []
+"#,
+        )?;
+        assert_eq!(Err("Found 1 problem in `a.yml`".to_owned()), result);
+        assert_eq!(
+            "error: a.yml: No such preset: `geometry` (expected one of [exact])\n",
+            stderr,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file() -> eyre::Result<()> {
+        let (result, stderr) = lint(
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+  - name: large
+    in:
+      file: large.in
+    out: "1\n"
+"#,
+        )?;
+        assert_eq!(Err("Found 1 problem in `a.yml`".to_owned()), result);
+        assert!(
+            stderr.starts_with("error: a.yml: Could not load `in` of the test case \"large\""),
+            "{}",
+            stderr,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unrepresentable_input() -> eyre::Result<()> {
+        let (result, stderr) = lint(
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+encoding: Shift_JIS
+
+cases:
+  - name: emoji
+    in: "\U0001F600\n"
+    out: "1\n"
+"#,
+        )?;
+        assert_eq!(Err("Found 1 problem in `a.yml`".to_owned()), result);
+        assert_eq!(
+            "error: a.yml: #1 (\"emoji\"): the text is not representable in Shift_JIS\n",
+            stderr,
+        );
+        Ok(())
+    }
+}
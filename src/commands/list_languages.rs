@@ -0,0 +1,130 @@
+use crate::config;
+use snowchains_core::{color_spec, web::PlatformKind};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+use unicode_width::UnicodeWidthStr as _;
+
+#[derive(StructOpt, Debug)]
+pub struct OptListLanguages {
+    /// Evaluates the languages in `Release` mode
+    #[structopt(long)]
+    pub release: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptListLanguages,
+    ctx: crate::Context<impl Sized, impl WriteColor, impl Sized>,
+) -> eyre::Result<()> {
+    let OptListLanguages {
+        release,
+        config,
+        color: _,
+        service,
+        contest,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (service, languages, detected_language) = config::languages_for_detected_target(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        if release {
+            config::Mode::Release
+        } else {
+            config::Mode::Debug
+        },
+    )?;
+
+    let yes_or_dash = |b: bool| if b { "yes" } else { "-" };
+
+    let language_id_title = format!("Language ID ({})", service.to_kebab_case_str());
+
+    let rows = itertools::chain(
+        vec![[
+            "".to_owned(),
+            "Name".to_owned(),
+            "Transpile".to_owned(),
+            "Compile".to_owned(),
+            "Src".to_owned(),
+            language_id_title,
+        ]],
+        languages.iter().map(|(name, language)| {
+            [
+                if detected_language.as_ref() == Some(name) {
+                    "*".to_owned()
+                } else {
+                    "".to_owned()
+                },
+                name.clone(),
+                yes_or_dash(language.transpile.is_some()).to_owned(),
+                yes_or_dash(language.compile.is_some()).to_owned(),
+                language.src.clone(),
+                language
+                    .languageId
+                    .clone()
+                    .unwrap_or_else(|| "-".to_owned()),
+            ]
+        }),
+    )
+    .collect::<Vec<_>>();
+
+    let widths = (0..6)
+        .map(|i| rows.iter().map(|r| r[i].width()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    for (i, row) in rows.iter().enumerate() {
+        let is_title = i == 0;
+        let is_detected = !is_title && row[0] == "*";
+
+        if is_title {
+            shell.stdout.set_color(color_spec!(Bold))?;
+        } else if is_detected {
+            shell
+                .stdout
+                .set_color(color_spec!(Bold, Fg(Color::Green)))?;
+        }
+
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(s, w)| format!("{}{}", s, " ".repeat(w - s.width())))
+            .collect::<Vec<_>>()
+            .join("  ");
+        write!(shell.stdout, "{}", line.trim_end())?;
+
+        shell.stdout.reset()?;
+        writeln!(shell.stdout)?;
+    }
+
+    shell.stdout.flush()?;
+    Ok(())
+}
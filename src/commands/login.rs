@@ -1,7 +1,6 @@
 use serde::Serialize;
 use snowchains_core::web::{
-    Atcoder, AtcoderLoginCredentials, Codeforces, CodeforcesLoginCredentials, CookieStorage, Login,
-    PlatformKind,
+    Atcoder, AtcoderLoginCredentials, Codeforces, CodeforcesLoginCredentials, Login, PlatformKind,
 };
 use std::{
     cell::RefCell,
@@ -51,11 +50,14 @@ pub(crate) fn run(
         service,
     } = opt;
 
-    let crate::Context { cwd: _, mut shell } = ctx;
+    let crate::Context { cwd, mut shell } = ctx;
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, None)?;
+    let min_interval = crate::config::min_interval(&cwd, None)?;
+    let proxy = crate::config::proxy(&cwd, None)?;
 
     let outcome = match service {
         PlatformKind::Atcoder => {
@@ -71,6 +73,9 @@ pub(crate) fn run(
                 credentials,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell: &shell,
             })
         }
@@ -86,6 +91,9 @@ pub(crate) fn run(
                 credentials,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell: &shell,
             })
         }
@@ -1,10 +1,20 @@
+pub(crate) mod add_testcase;
+pub(crate) mod clean;
+pub(crate) mod generate_testcases;
 pub(crate) mod init;
 pub(crate) mod judge;
+pub(crate) mod lint_config;
+pub(crate) mod lint_suite;
+pub(crate) mod list_languages;
 pub(crate) mod login;
+pub(crate) mod new;
 pub(crate) mod participate;
+pub(crate) mod restore;
 pub(crate) mod retrieve_languages;
+pub(crate) mod retrieve_solved_problems;
 pub(crate) mod retrieve_submission_summaries;
 pub(crate) mod retrieve_testcases;
+pub(crate) mod stress_test;
 pub(crate) mod submit;
 pub(crate) mod watch_submissions;
 pub(crate) mod xtask;
@@ -0,0 +1,201 @@
+use crate::{commands::retrieve_testcases::OptRetrieveTestcases, config};
+use eyre::{bail, ensure, ContextCompat as _, WrapErr as _};
+use snowchains_core::web::PlatformKind;
+use std::{
+    env,
+    io::BufRead,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::WriteColor;
+
+#[derive(StructOpt, Debug)]
+pub struct OptNew {
+    /// Overwrites the existing source file
+    #[structopt(short, long)]
+    pub force: bool,
+
+    /// Also retrieves the test cases
+    #[structopt(short, long)]
+    pub download: bool,
+
+    /// Opens the source file with `$VISUAL` or `$EDITOR`, and prints the path. An existing file is
+    /// kept unless `--force` is given
+    #[structopt(short, long)]
+    pub edit: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Language name
+    #[structopt(short, long, value_name("STRING"))]
+    pub language: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: String,
+}
+
+pub(crate) fn run(
+    opt: OptNew,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptNew {
+        force,
+        download,
+        edit,
+        config,
+        color,
+        service,
+        contest,
+        language,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (
+        config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        config::Language { src, template, .. },
+        base_dir,
+    ) = config::target_and_language(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        Some(&problem),
+        language.as_deref(),
+        config::Mode::Debug,
+        &mut shell.stderr,
+    )?;
+
+    let path = base_dir.join(src.strip_prefix("./").unwrap_or(&src));
+
+    if force || !path.exists() {
+        crate::fs::write(&path, template_content(&base_dir, template)?, true)?;
+
+        writeln!(shell.stderr, "Wrote `{}`", path.display())?;
+        shell.stderr.flush()?;
+    } else if !edit {
+        bail!("`{}` exists. Use `--force` to overwrite", path.display(),);
+    }
+
+    if edit {
+        writeln!(shell.stdout, "{}", path.display())?;
+        shell.stdout.flush()?;
+    }
+
+    if download {
+        crate::commands::retrieve_testcases::run(
+            OptRetrieveTestcases {
+                full: false,
+                jobs: None,
+                statements: false,
+                json: false,
+                summary: None,
+                config,
+                color,
+                service: Some(service),
+                contest,
+                problems: Some(vec![problem]),
+                problems_from_file: None,
+            },
+            crate::Context {
+                cwd: cwd.clone(),
+                shell,
+            },
+        )?;
+    }
+
+    if edit {
+        let editor = ["VISUAL", "EDITOR"]
+            .iter()
+            .find_map(|name| env::var(name).ok().filter(|v| !v.trim().is_empty()))
+            .with_context(|| "Neither `$VISUAL` nor `$EDITOR` is set")?;
+
+        let mut args = editor.split_whitespace();
+        let program = args.next().expect("should not be empty");
+
+        let status = Command::new(program)
+            .args(args)
+            .arg(&path)
+            .current_dir(&cwd)
+            .status()
+            .with_context(|| format!("Could not execute `{}`", editor))?;
+
+        ensure!(status.success(), "`{}` failed ({})", editor, status);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn template_content(
+    base_dir: &Path,
+    template: Option<config::Template>,
+) -> eyre::Result<String> {
+    match template {
+        Some(config::Template::Path(template)) => crate::fs::read_to_string(
+            base_dir.join(template.strip_prefix("./").unwrap_or(&template)),
+        ),
+        Some(config::Template::Content(content)) => Ok(content),
+        None => Ok("".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[test]
+    fn new() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        let path = dir.path().join("a.sh");
+
+        let (result, _, stderr) = crate::testing::run(dir.path(), &["new", "a"]);
+        result?;
+        assert_eq!(format!("Wrote `{}`\n", path.display()), stderr);
+        assert_eq!("# A\n", fs::read_to_string(&path)?);
+
+        fs::write(&path, "echo 42\n")?;
+
+        let (result, _, _) = crate::testing::run(dir.path(), &["new", "a"]);
+        assert_eq!(
+            format!("`{}` exists. Use `--force` to overwrite", path.display()),
+            result.unwrap_err().to_string(),
+        );
+        assert_eq!("echo 42\n", fs::read_to_string(&path)?);
+
+        let (result, _, _) = crate::testing::run(dir.path(), &["new", "--force", "a"]);
+        result?;
+        assert_eq!("# A\n", fs::read_to_string(&path)?);
+        Ok(())
+    }
+}
@@ -1,7 +1,6 @@
 use serde::Serialize;
 use snowchains_core::web::{
-    Atcoder, AtcoderParticipateCredentials, AtcoderParticipateTarget, CookieStorage, Participate,
-    PlatformKind,
+    Atcoder, AtcoderParticipateCredentials, AtcoderParticipateTarget, Participate, PlatformKind,
 };
 use std::{
     cell::RefCell,
@@ -59,10 +58,13 @@ pub(crate) fn run(
         contest,
     } = opt;
 
-    let crate::Context { cwd: _, mut shell } = ctx;
+    let crate::Context { cwd, mut shell } = ctx;
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, None)?;
+    let min_interval = crate::config::min_interval(&cwd, None)?;
+    let proxy = crate::config::proxy(&cwd, None)?;
 
     let kind = {
         let shell = RefCell::new(&mut shell);
@@ -80,6 +82,9 @@ pub(crate) fn run(
             credentials,
             cookie_storage,
             timeout,
+            retry,
+            min_interval,
+            proxy,
             shell: &shell,
         })?
     };
@@ -0,0 +1,192 @@
+use crate::config;
+use eyre::{bail, ContextCompat as _};
+use snowchains_core::{
+    color_spec,
+    web::{
+        Atcoder, AtcoderRetrieveSubmissionsCredentials, AtcoderRetrieveSubmissionsTarget,
+        PlatformKind, RetrieveSubmissions, RetrieveSubmissionsOutcomeSubmission,
+    },
+};
+use std::{cell::RefCell, io::BufRead, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptRestore {
+    /// Overwrites existing files
+    #[structopt(long)]
+    pub force: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(short, long, value_name("SERVICE"), possible_value("atcoder"))]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Problem indexes (e.g. "a", "b", "c")
+    #[structopt(short, long, value_name("STRING"))]
+    pub problems: Option<Vec<String>>,
+
+    /// Reads more problems from a file, one per line. Blank lines and `#` comments are ignored
+    #[structopt(long, value_name("PATH"))]
+    pub problems_from_file: Option<PathBuf>,
+}
+
+pub(crate) fn run(
+    opt: OptRestore,
+    ctx: crate::Context<impl BufRead, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptRestore {
+        force,
+        config,
+        color: _,
+        service,
+        contest,
+        problems,
+        problems_from_file,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let problems = match problems_from_file {
+        Some(path) => {
+            let mut problems = problems.unwrap_or_default();
+            problems.extend(crate::web::read_problems_file(&cwd.join(path))?);
+            Some(problems)
+        }
+        None => problems,
+    };
+
+    let (detected_target, _) = config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
+    let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
+
+    let (contest, outcome) = match service {
+        PlatformKind::Atcoder => {
+            let shell = RefCell::new(&mut shell);
+
+            let contest = contest.with_context(|| "`contest` is required for AtCoder")?;
+
+            let target = AtcoderRetrieveSubmissionsTarget {
+                contest: contest.clone(),
+                problems: problems.map(|ps| ps.into_iter().collect()),
+            };
+
+            let credentials = AtcoderRetrieveSubmissionsCredentials {
+                username_and_password: &mut crate::web::credentials::atcoder_username_and_password(
+                    &shell,
+                ),
+            };
+
+            let outcome = Atcoder::exec(RetrieveSubmissions {
+                target,
+                credentials,
+                cookie_storage,
+                timeout,
+                retry,
+                min_interval,
+                proxy,
+                shell: &shell,
+            })?;
+
+            (contest, outcome)
+        }
+        PlatformKind::Codeforces | PlatformKind::Yukicoder => {
+            bail!("`restore` is not supported for {}", service);
+        }
+    };
+
+    if outcome.submissions.is_empty() {
+        shell.warn("No submissions found")?;
+    }
+
+    for RetrieveSubmissionsOutcomeSubmission {
+        problem_index,
+        language_id,
+        language_name,
+        code,
+        ..
+    } in outcome.submissions
+    {
+        let target = config::Target {
+            service,
+            contest: Some(contest.clone()),
+            problem: problem_index.clone(),
+            mode: config::Mode::Release,
+        };
+
+        let (languages, detected_language, base_dir) =
+            config::languages_for_target(&cwd, config.as_deref(), &target)?;
+
+        let language = language_id.as_ref().and_then(|language_id| {
+            let mut candidates = languages
+                .iter()
+                .filter(|(_, l)| l.languageId.as_ref() == Some(language_id));
+            let first = candidates.clone().next();
+            candidates
+                .find(|(name, _)| Some(*name) == detected_language.as_ref())
+                .or(first)
+                .map(|(_, l)| l)
+        });
+
+        let language = if let Some(language) = language {
+            language
+        } else {
+            shell.warn(format!(
+                "{}: No language in the config has the `languageId` of {:?}. Skipping",
+                problem_index, language_name,
+            ))?;
+            continue;
+        };
+
+        let path = base_dir.join(&language.src);
+
+        if path.exists() && !force {
+            shell.warn(format!(
+                "{}: `{}` already exists. Add `--force` to overwrite it",
+                problem_index,
+                path.display(),
+            ))?;
+            continue;
+        }
+
+        crate::fs::write(&path, code, true)?;
+
+        write!(shell.stderr, "{}: Restored to ", problem_index)?;
+        shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(shell.stderr, "{}", path.display())?;
+        shell.stderr.reset()?;
+        writeln!(shell.stderr)?;
+        shell.stderr.flush()?;
+    }
+
+    Ok(())
+}
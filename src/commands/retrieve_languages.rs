@@ -1,8 +1,8 @@
 use eyre::ContextCompat as _;
 use snowchains_core::web::{
     Atcoder, AtcoderRetrieveLanguagesCredentials, AtcoderRetrieveLanguagesTarget, Codeforces,
-    CodeforcesRetrieveLanguagesCredentials, CodeforcesRetrieveLanguagesTarget, CookieStorage,
-    PlatformKind, RetrieveLanguages, Yukicoder,
+    CodeforcesRetrieveLanguagesCredentials, CodeforcesRetrieveLanguagesTarget, PlatformKind,
+    RetrieveLanguages, Yukicoder,
 };
 use std::{
     cell::RefCell,
@@ -19,6 +19,10 @@ pub struct OptRetrieveLanguages {
     #[structopt(long)]
     pub json: bool,
 
+    /// Prints the result as `languageId` fields to paste into `snowchains.dhall`
+    #[structopt(long, conflicts_with("json"))]
+    pub dhall: bool,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -55,6 +59,7 @@ pub(crate) fn run(
 ) -> eyre::Result<()> {
     let OptRetrieveLanguages {
         json,
+        dhall,
         config,
         color: _,
         service,
@@ -76,9 +81,12 @@ pub(crate) fn run(
     let contest = contest.or(detected_target.contest);
     let problem = problem.or(detected_target.problem);
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
 
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
 
     let outcome =
         match service {
@@ -99,6 +107,9 @@ pub(crate) fn run(
                     credentials,
                     cookie_storage,
                     timeout,
+                    retry,
+                    min_interval,
+                    proxy,
                     shell: &shell,
                 })
             }
@@ -119,6 +130,9 @@ pub(crate) fn run(
                     credentials,
                     cookie_storage,
                     timeout,
+                    retry,
+                    min_interval,
+                    proxy,
                     shell: &shell,
                 })
             }
@@ -127,12 +141,26 @@ pub(crate) fn run(
                 credentials: (),
                 cookie_storage: (),
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell: &mut shell,
             }),
         }?;
 
     if json {
         writeln!(shell.stdout, "{}", outcome.to_json())
+    } else if dhall {
+        let fields = outcome
+            .names_by_id
+            .keys()
+            .map(|id| format!(", languageId = Some {:?}", id))
+            .collect::<Vec<_>>();
+        let width = fields.iter().map(String::len).max().unwrap_or(0);
+        for (field, name) in fields.iter().zip(outcome.names_by_id.values()) {
+            writeln!(shell.stdout, "{:width$} -- {}", field, name, width = width)?;
+        }
+        Ok(())
     } else {
         write!(shell.stdout, "{}", outcome.to_table())
     }?;
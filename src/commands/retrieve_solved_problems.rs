@@ -0,0 +1,146 @@
+use eyre::{bail, Context as _, ContextCompat as _};
+use snowchains_core::web::{
+    Atcoder, AtcoderRetrieveSolvedProblemsCredentials, AtcoderRetrieveSolvedProblemsTarget,
+    PlatformKind, RetrieveSolvedProblems, RetrieveSolvedProblemsOutcome,
+};
+use std::{
+    cell::RefCell,
+    io::{BufRead, Write},
+    path::PathBuf,
+    time::SystemTime,
+};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::WriteColor;
+
+#[derive(StructOpt, Debug)]
+pub struct OptRetrieveSolvedProblems {
+    /// User name on AtCoder
+    #[structopt(short, long, value_name("NAME"))]
+    pub user: String,
+
+    /// Reuses the result of the last query within this duration
+    #[structopt(long, value_name("DURATION"), default_value("10m"))]
+    pub ttl: humantime::Duration,
+
+    /// Prints the result as JSON
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(short, long, value_name("SERVICE"), possible_value("atcoder"))]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptRetrieveSolvedProblems,
+    ctx: crate::Context<impl BufRead, impl Write, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptRetrieveSolvedProblems {
+        user,
+        ttl,
+        json,
+        config,
+        color: _,
+        service,
+        contest,
+    } = opt;
+
+    let crate::Context { cwd, mut shell } = ctx;
+
+    let (detected_target, _) = crate::config::detect_target(&cwd, config.as_deref())?;
+
+    let service = service
+        .map(Ok)
+        .or_else(|| detected_target.parse_service().transpose())
+        .with_context(|| {
+            "`service` was not detected. To specify it, add `--service` to the arguments"
+        })??;
+
+    let contest = contest.or(detected_target.contest);
+
+    let outcome = match service {
+        PlatformKind::Atcoder => {
+            let contest = contest.with_context(|| "`contest` is required for AtCoder")?;
+
+            let cache_path = dirs_next::cache_dir()
+                .with_context(|| "Could not find the cache directory")?
+                .join("snowchains")
+                .join("solved-problems")
+                .join("atcoder")
+                .join(&contest)
+                .join(format!("{}.json", user));
+
+            let is_fresh = crate::fs::metadata(&cache_path)
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .is_some_and(|elapsed| elapsed < *ttl);
+
+            if is_fresh {
+                crate::fs::read_json::<RetrieveSolvedProblemsOutcome, _>(&cache_path)?
+            } else {
+                let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
+                let timeout = Some(crate::web::SESSION_TIMEOUT);
+                let retry = crate::config::retry(&cwd, config.as_deref())?;
+                let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+                let proxy = crate::config::proxy(&cwd, config.as_deref())?;
+
+                let shell = RefCell::new(&mut shell);
+
+                let target = AtcoderRetrieveSolvedProblemsTarget { contest, user };
+
+                let credentials = AtcoderRetrieveSolvedProblemsCredentials {
+                    username_and_password:
+                        &mut crate::web::credentials::atcoder_username_and_password(&shell),
+                };
+
+                let outcome = Atcoder::exec(RetrieveSolvedProblems {
+                    target,
+                    credentials,
+                    cookie_storage,
+                    timeout,
+                    retry,
+                    min_interval,
+                    proxy,
+                    shell: &shell,
+                })?;
+
+                crate::fs::write_json(&cache_path, &outcome, true)
+                    .with_context(|| "Could not cache the result")?;
+                outcome
+            }
+        }
+        PlatformKind::Codeforces | PlatformKind::Yukicoder => {
+            bail!(
+                "`retrieve solved-problems` is not supported for {}",
+                service
+            );
+        }
+    };
+
+    if json {
+        writeln!(shell.stdout, "{}", outcome.to_json())
+    } else {
+        write!(shell.stdout, "{}", outcome.to_table())
+    }?;
+
+    shell.stdout.flush()?;
+    Ok(())
+}
@@ -1,8 +1,7 @@
 use eyre::ContextCompat as _;
 use snowchains_core::web::{
     Atcoder, AtcoderRetrieveSubmissionSummariesCredentials,
-    AtcoderRetrieveSubmissionSummariesTarget, CookieStorage, PlatformKind,
-    RetrieveSubmissionSummaries,
+    AtcoderRetrieveSubmissionSummariesTarget, PlatformKind, RetrieveSubmissionSummaries,
 };
 use std::{
     cell::RefCell,
@@ -60,8 +59,11 @@ pub(crate) fn run(
 
     let contest = contest.or(detected_target.contest);
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
 
     match service {
         PlatformKind::Atcoder => {
@@ -82,6 +84,9 @@ pub(crate) fn run(
                     credentials,
                     cookie_storage,
                     timeout,
+                    retry,
+                    min_interval,
+                    proxy,
                     shell: &shell,
                 })?
             };
@@ -1,21 +1,22 @@
 use crate::web::CaseConversions;
-use eyre::{Context as _, ContextCompat as _};
+use eyre::{ensure, Context as _, ContextCompat as _};
+use globset::GlobBuilder;
 use maplit::btreeset;
 use serde::Serialize;
 use snowchains_core::{
     color_spec,
     testsuite::{Additional, BatchTestSuite, TestSuite},
     web::{
-        Atcoder, AtcoderRetrieveFullTestCasesCredentials,
-        AtcoderRetrieveSampleTestCasesCredentials, Codeforces,
-        CodeforcesRetrieveSampleTestCasesCredentials, CookieStorage, PlatformKind,
-        ProblemsInContest, RetrieveFullTestCases, RetrieveTestCases, Yukicoder,
+        Atcoder, AtcoderRetrieveSampleTestCasesCredentials, Codeforces,
+        CodeforcesRetrieveSampleTestCasesCredentials, PlatformKind, ProblemsInContest,
+        RetrieveFullTestCases, RetrieveTestCases, Yukicoder,
         YukicoderRetrieveFullTestCasesCredentials, YukicoderRetrieveTestCasesTargets,
     },
 };
 use std::{
     cell::RefCell,
     io::{BufRead, Write},
+    num::NonZeroUsize,
     path::PathBuf,
 };
 use structopt::StructOpt;
@@ -29,10 +30,23 @@ pub struct OptRetrieveTestcases {
     #[structopt(long)]
     pub full: bool,
 
+    /// Maximum number of files downloaded at once with `--full` [default: the number of CPUs]
+    #[structopt(short, long, value_name("COUNT"))]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Also saves the problem statements as HTML files
+    #[structopt(long)]
+    pub statements: bool,
+
     /// Prints JSON data
     #[structopt(long)]
     pub json: bool,
 
+    /// Also writes the JSON data to a file, including problems whose full test cases could not be
+    /// retrieved
+    #[structopt(long, value_name("PATH"))]
+    pub summary: Option<PathBuf>,
+
     /// Path to `snowchains.dhall`
     #[structopt(long)]
     pub config: Option<PathBuf>,
@@ -58,9 +72,13 @@ pub struct OptRetrieveTestcases {
     #[structopt(short, long, value_name("STRING"))]
     pub contest: Option<String>,
 
-    /// Problem indexes (e.g. "a", "b", "c")
+    /// Problem indexes (e.g. "a", "b", "c") or glob patterns (e.g. "*c")
     #[structopt(short, long, value_name("STRING"))]
     pub problems: Option<Vec<String>>,
+
+    /// Reads more problems from a file, one per line. Blank lines and `#` comments are ignored
+    #[structopt(long, value_name("PATH"))]
+    pub problems_from_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize)]
@@ -90,6 +108,11 @@ struct OutcomeProblem {
     screen_name: Option<String>,
     display_name: String,
     test_suite: OutcomeProblemTestSuite,
+    num_cases: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    statement_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,16 +127,29 @@ pub(crate) fn run(
 ) -> eyre::Result<()> {
     let OptRetrieveTestcases {
         full,
+        jobs,
+        statements,
         json,
+        summary,
         config,
         color: _,
         service,
         contest,
         problems,
+        problems_from_file,
     } = opt;
 
     let crate::Context { cwd, mut shell } = ctx;
 
+    let problems = match problems_from_file {
+        Some(path) => {
+            let mut problems = problems.unwrap_or_default();
+            problems.extend(crate::web::read_problems_file(&cwd.join(path))?);
+            Some(problems)
+        }
+        None => problems,
+    };
+
     let (detected_target, workspace) = crate::config::detect_target(&cwd, config.as_deref())?;
 
     let service = service
@@ -131,11 +167,33 @@ pub(crate) fn run(
         (problems, _) => Some(problems.iter().cloned().collect()),
     };
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let globs = problems
+        .as_ref()
+        .filter(|ps| ps.iter().any(|p| p.contains(&['*', '?', '[', '{'][..])))
+        .map(|ps| {
+            ps.iter()
+                .map(|p| {
+                    let glob = GlobBuilder::new(p)
+                        .case_insensitive(true)
+                        .build()
+                        .with_context(|| format!("Invalid glob pattern: {:?}", p))?;
+                    Ok((p.clone(), glob.compile_matcher()))
+                })
+                .collect::<eyre::Result<Vec<_>>>()
+        })
+        .transpose()?;
+
+    let problems = if globs.is_some() { None } else { problems };
+
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
 
-    let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let timeouts = crate::config::timeouts(&cwd, config.as_deref())?;
+    let timeout = Some(timeouts.retrieve);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
 
-    let outcome = match service {
+    let mut outcome = match service {
         PlatformKind::Atcoder => {
             let shell = RefCell::new(&mut shell);
 
@@ -154,9 +212,9 @@ pub(crate) fn run(
 
             let full = if full {
                 Some(RetrieveFullTestCases {
-                    credentials: AtcoderRetrieveFullTestCasesCredentials {
-                        dropbox_access_token: crate::web::credentials::dropbox_access_token()?,
-                    },
+                    credentials: crate::web::credentials::dropbox_credentials()?,
+                    jobs,
+                    timeout: Some(timeouts.retrieve_full),
                 })
             } else {
                 None
@@ -168,6 +226,9 @@ pub(crate) fn run(
                 full,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell: &shell,
             })
         }
@@ -192,6 +253,9 @@ pub(crate) fn run(
                 full: None,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell: &shell,
             })
         }
@@ -199,6 +263,10 @@ pub(crate) fn run(
             let targets = if let Some(contest) = &contest {
                 YukicoderRetrieveTestCasesTargets::Contest(contest.clone(), problems)
             } else {
+                ensure!(
+                    globs.is_none(),
+                    "Glob patterns for yukicoder require `contest`",
+                );
                 let nos = problems
                     .with_context(|| "`contest` or `problem`s are required for yukicoder")?
                     .iter()
@@ -213,6 +281,8 @@ pub(crate) fn run(
                     credentials: YukicoderRetrieveFullTestCasesCredentials {
                         api_key: crate::web::credentials::yukicoder_api_key(&mut shell)?,
                     },
+                    jobs,
+                    timeout: Some(timeouts.retrieve_full),
                 })
             } else {
                 None
@@ -226,15 +296,30 @@ pub(crate) fn run(
                 full,
                 cookie_storage: (),
                 timeout,
+                retry,
+                min_interval,
+                proxy,
                 shell,
             })
         }
     }?;
 
+    if let Some(globs) = &globs {
+        for (pattern, glob) in globs {
+            if !outcome.problems.iter().any(|p| glob.is_match(&p.index)) {
+                shell.warn(format!("No problems matched `{}`", pattern))?;
+            }
+        }
+
+        outcome
+            .problems
+            .retain(|p| globs.iter().any(|(_, glob)| glob.is_match(&p.index)));
+    }
+
     let mut acc = Outcome {
         contest: outcome
             .problems
-            .get(0)
+            .first()
             .and_then(
                 |snowchains_core::web::RetrieveTestCasesOutcomeProblem { contest, .. }| {
                     contest.as_ref()
@@ -264,6 +349,8 @@ pub(crate) fn run(
         display_name,
         mut test_suite,
         text_files,
+        statement,
+        full_error,
         ..
     } in outcome.problems
     {
@@ -287,7 +374,7 @@ pub(crate) fn run(
         for (name, snowchains_core::web::RetrieveTestCasesOutcomeProblemTextFiles { r#in, out }) in
             &text_files
         {
-            crate::fs::write(txt_path("in", name), &r#in, true)?;
+            crate::fs::write(txt_path("in", name), r#in, true)?;
             if let Some(out) = out {
                 crate::fs::write(txt_path("out", name), out, true)?;
             }
@@ -330,14 +417,17 @@ pub(crate) fn run(
 
         write!(shell.stderr, " (")?;
 
+        let num_cases = match &test_suite {
+            TestSuite::Batch(BatchTestSuite { cases, .. }) => cases.len() + text_files.len(),
+            TestSuite::Interactive(_) | TestSuite::Unsubmittable => 0,
+        };
+
         let (msg, color) = match &test_suite {
-            TestSuite::Batch(BatchTestSuite { cases, .. }) => {
-                match cases.len() + text_files.len() {
-                    0 => ("no test cases".to_owned(), Color::Yellow),
-                    1 => ("1 test case".to_owned(), Color::Green),
-                    n => (format!("{} test cases", n), Color::Green),
-                }
-            }
+            TestSuite::Batch(_) => match num_cases {
+                0 => ("no test cases".to_owned(), Color::Yellow),
+                1 => ("1 test case".to_owned(), Color::Green),
+                n => (format!("{} test cases", n), Color::Green),
+            },
             TestSuite::Interactive(_) => ("interactive problem".to_owned(), Color::Yellow),
             TestSuite::Unsubmittable => ("unsubmittable problem".to_owned(), Color::Yellow),
         };
@@ -349,6 +439,74 @@ pub(crate) fn run(
         writeln!(shell.stderr, ")")?;
         shell.stderr.flush()?;
 
+        if let TestSuite::Batch(BatchTestSuite {
+            memorylimit: None, ..
+        }) = &test_suite
+        {
+            shell.warn(format!(
+                "{}: Could not find the memory limit. It is not enforced for this problem",
+                index.original,
+            ))?;
+        }
+
+        let statement_path = if let Some(statement) = statement.filter(|_| statements) {
+            let statement_path = workspace
+                .join(".snowchains")
+                .join("statements")
+                .join(service.to_kebab_case_str())
+                .join(contest.as_deref().unwrap_or(""))
+                .join(&index.kebab)
+                .with_extension("html");
+
+            // `<base>` keeps the relative links and images pointing to the service.
+            let html = format!(
+                "<!DOCTYPE html>\n\
+                 <html>\n\
+                 <head>\n\
+                 <meta charset=\"utf-8\">\n\
+                 <base href=\"{}\">\n\
+                 <title>{}</title>\n\
+                 </head>\n\
+                 <body>\n\
+                 {}\n\
+                 </body>\n\
+                 </html>\n",
+                escape_html(url.as_str()),
+                escape_html(&display_name),
+                statement,
+            );
+
+            crate::fs::write(&statement_path, html, true)?;
+
+            shell.stderr.set_color(color_spec!(Bold))?;
+            write!(shell.stderr, "{}:", index.original)?;
+            shell.stderr.reset()?;
+
+            write!(shell.stderr, " Saved the statement to ")?;
+
+            shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+            write!(shell.stderr, "{}", statement_path.display())?;
+            shell.stderr.reset()?;
+
+            writeln!(shell.stderr)?;
+            shell.stderr.flush()?;
+
+            Some(
+                statement_path
+                    .into_os_string()
+                    .into_string()
+                    .expect("should be UTF-8"),
+            )
+        } else {
+            if statements {
+                shell.warn(format!(
+                    "{}: Could not find the problem statement",
+                    index.original,
+                ))?;
+            }
+            None
+        };
+
         acc.problems.push(OutcomeProblem {
             index,
             url,
@@ -361,6 +519,9 @@ pub(crate) fn run(
                     .expect("should be UTF-8"),
                 content: test_suite,
             },
+            num_cases,
+            statement_path,
+            full_error,
         });
     }
 
@@ -369,5 +530,24 @@ pub(crate) fn run(
         shell.stdout.flush()?;
     }
 
+    if let Some(summary) = summary {
+        let summary = cwd.join(summary);
+        crate::fs::write(&summary, acc.to_json(), true)?;
+
+        write!(shell.stderr, "Wrote the summary to ")?;
+        shell.stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(shell.stderr, "{}", summary.display())?;
+        shell.stderr.reset()?;
+        writeln!(shell.stderr)?;
+        shell.stderr.flush()?;
+    }
+
     Ok(())
 }
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,363 @@
+use crate::config;
+use eyre::{bail, WrapErr as _};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use snowchains_core::{
+    color_spec,
+    judge::{BuildKind, JudgeOptions, Palette, PrettyOptions, Verdict},
+    testsuite::{BatchTestSuite, PartialBatchTestCase, TestSuite},
+    web::PlatformKind,
+};
+use std::{collections::HashSet, path::PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames as _;
+use termcolor::{Color, WriteColor};
+
+#[derive(StructOpt, Debug)]
+pub struct OptStressTest {
+    /// Bash command that prints a random input. The seed is given as `$1`
+    #[structopt(long, value_name("CMD"))]
+    pub generator: String,
+
+    /// Bash command that solves the input correctly, however slow it may be
+    #[structopt(long, value_name("CMD"))]
+    pub brute: String,
+
+    /// First seed
+    #[structopt(long, value_name("INTEGER"), default_value("0"))]
+    pub seed: u64,
+
+    /// Maximum number of the iterations
+    #[structopt(long, value_name("COUNT"), default_value("1000"))]
+    pub iterations: u64,
+
+    /// Build in `Release` mode
+    #[structopt(long)]
+    pub release: bool,
+
+    /// Colors of the verdicts. See `judge --help`
+    #[structopt(long, value_name("PALETTE"), default_value("default"))]
+    pub palette: Palette,
+
+    /// Path to `snowchains.dhall`
+    #[structopt(long)]
+    pub config: Option<PathBuf>,
+
+    /// Coloring
+    #[structopt(
+        long,
+        possible_values(crate::ColorChoice::VARIANTS),
+        default_value("auto")
+    )]
+    pub color: crate::ColorChoice,
+
+    /// Platform
+    #[structopt(
+        short,
+        long,
+        value_name("SERVICE"),
+        possible_values(PlatformKind::KEBAB_CASE_VARIANTS)
+    )]
+    pub service: Option<PlatformKind>,
+
+    /// Contest ID
+    #[structopt(short, long, value_name("STRING"))]
+    pub contest: Option<String>,
+
+    /// Language name
+    #[structopt(short, long, value_name("STRING"))]
+    pub language: Option<String>,
+
+    /// Problem index (e.g. "a", "b", "c")
+    pub problem: Option<String>,
+}
+
+pub(crate) fn run(
+    opt: OptStressTest,
+    ctx: crate::Context<impl Sized, impl WriteColor, impl WriteColor>,
+) -> eyre::Result<()> {
+    let OptStressTest {
+        generator,
+        brute,
+        seed,
+        iterations,
+        release,
+        palette,
+        config,
+        color: _,
+        service,
+        contest,
+        language,
+        problem,
+    } = opt;
+
+    let crate::Context { cwd, shell } = ctx;
+
+    let progress_draw_target = shell.progress_draw_target();
+
+    let crate::shell::Shell {
+        mut stdout,
+        mut stderr,
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+        ..
+    } = shell;
+
+    let (
+        config::Target {
+            service,
+            contest,
+            problem,
+            ..
+        },
+        config::Language {
+            src,
+            transpile,
+            compile,
+            run,
+            runner,
+            timelimitMultiplier: timelimit_multiplier,
+            ..
+        },
+        base_dir,
+    ) = config::target_and_language(
+        &cwd,
+        config.as_deref(),
+        service,
+        contest.as_deref(),
+        problem.as_deref(),
+        language.as_deref(),
+        if release {
+            config::Mode::Release
+        } else {
+            config::Mode::Debug
+        },
+        &mut stderr,
+    )?;
+
+    let test_suite_dir = base_dir
+        .join(".snowchains")
+        .join("tests")
+        .join(service.to_kebab_case_str())
+        .join(contest.as_deref().unwrap_or(""));
+    let test_suite_path = test_suite_dir.join(&problem).with_extension("yml");
+
+    // The counterexample is added to this one, so that the presets are kept as they are.
+    let mut test_suite = crate::judge::read_or_new_batch_suite(&test_suite_path)?;
+
+    let mut resolved = BatchTestSuite {
+        cases: vec![],
+        extend: vec![],
+        r#match: test_suite.r#match.clone(),
+        ..test_suite
+    };
+    if resolved.presets().next().is_some() {
+        resolved.resolve_presets(&config::match_presets(&cwd, config.as_deref())?)?;
+    }
+    let encoding = resolved.encoding.unwrap_or_default();
+
+    let redirections = (
+        stdin_process_redirection,
+        stdout_process_redirection,
+        stderr_process_redirection,
+    );
+
+    for (action, kind) in &[
+        (transpile, BuildKind::Transpile),
+        (compile, BuildKind::Compile),
+    ] {
+        if let Some(action) = action {
+            crate::judge::build(
+                &mut stderr,
+                &base_dir,
+                &src,
+                action,
+                redirections,
+                *kind,
+                false,
+                None,
+            )?;
+            writeln!(stderr)?;
+        }
+    }
+
+    let (cmd, tempfile) =
+        crate::judge::command_expression(run, base_dir.clone(), &runner.unwrap_or_default())?;
+
+    let pb = ProgressBar::new(iterations);
+    pb.set_draw_target(progress_draw_target);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{prefix:.bold} {wide_bar} {pos}/{len} {msg}")
+            .progress_chars("##-"),
+    );
+    pb.set_prefix("Stress-testing");
+
+    for seed in (seed..).take(iterations as _) {
+        pb.set_message(&format!("(seed {})", seed));
+
+        let input = crate::judge::bash_output(&generator, Some(seed), "", &base_dir, encoding)
+            .with_context(|| format!("The generator failed with seed {}", seed))?;
+        let output = crate::judge::bash_output(&brute, None, &input, &base_dir, encoding)
+            .with_context(|| format!("The brute-force solution failed with seed {}", seed))?;
+
+        let test_case = PartialBatchTestCase {
+            name: Some(format!("stress-{}", seed)),
+            r#in: input.into(),
+            out: Some(output.into()),
+            timelimit: None,
+            memorylimit: None,
+            r#match: None,
+        };
+
+        resolved.cases = vec![test_case.clone()];
+        let mut test_cases =
+            resolved.load_test_cases(&test_suite_dir, None::<HashSet<String>>, |_| Ok(vec![]))?;
+        if let Some(multiplier) = timelimit_multiplier {
+            crate::judge::multiply_timelimits(&mut test_cases, multiplier)?;
+        }
+
+        let outcome = snowchains_core::judge::judge(
+            ProgressDrawTarget::hidden(),
+            tokio::signal::ctrl_c,
+            &cmd,
+            &test_cases,
+            &JudgeOptions {
+                palette,
+                ..Default::default()
+            },
+        )?;
+
+        pb.inc(1);
+
+        if outcome
+            .verdicts
+            .iter()
+            .all(|v| matches!(v, Verdict::Accepted { .. }))
+        {
+            continue;
+        }
+
+        pb.finish_and_clear();
+
+        outcome.print_pretty(
+            &mut stdout,
+            &PrettyOptions {
+                palette,
+                ..Default::default()
+            },
+        )?;
+
+        let name = test_case.name.clone().unwrap_or_default();
+        test_suite.cases.push(test_case);
+        crate::judge::write_test_suite(
+            &mut stderr,
+            &test_suite_path,
+            &TestSuite::Batch(test_suite),
+        )?;
+
+        write!(stderr, "Saved the counterexample as {:?} in ", name)?;
+        stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(stderr, "{}", test_suite_path.display())?;
+        stderr.reset()?;
+        writeln!(stderr)?;
+        stderr.flush()?;
+
+        if let Some(tempfile) = tempfile {
+            tempfile.close()?;
+        }
+        bail!("Found a counterexample with seed {}", seed);
+    }
+
+    pb.finish_and_clear();
+
+    if let Some(tempfile) = tempfile {
+        tempfile.close()?;
+    }
+
+    writeln!(
+        stderr,
+        "No counterexamples found in {} iterations",
+        iterations
+    )?;
+    stderr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[test]
+    fn saves_first_counterexample() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+
+        // Wrong only for `a = 3`.
+        fs::write(
+            dir.path().join("a.sh"),
+            "read a b; if [ \"$a\" = 3 ]; then echo 0; else echo $((a + b)); fi\n",
+        )?;
+
+        let (result, _, stderr) = crate::testing::run(
+            dir.path(),
+            &[
+                "stress-test",
+                "--generator",
+                "echo \"$1\" 1",
+                "--brute",
+                "read a b; echo $((a + b))",
+                "--iterations",
+                "10",
+                "a",
+            ],
+        );
+
+        assert_eq!(
+            "Found a counterexample with seed 3",
+            result.unwrap_err().to_string(),
+        );
+        assert!(stderr.contains("Saved the counterexample as \"stress-3\""));
+
+        let test_suite = fs::read_to_string(
+            dir.path()
+                .join(".snowchains")
+                .join("tests")
+                .join("atcoder")
+                .join("abc")
+                .join("a.yml"),
+        )?;
+        let test_suite = serde_yaml::from_str::<serde_yaml::Value>(&test_suite)?;
+        let cases = test_suite["cases"].as_sequence().unwrap();
+
+        assert_eq!(1, cases.len());
+        assert_eq!("stress-3", cases[0]["name"]);
+        assert_eq!("3 1\n", cases[0]["in"]);
+        assert_eq!("4\n", cases[0]["out"]);
+        Ok(())
+    }
+
+    #[test]
+    fn no_counterexample() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        fs::write(dir.path().join("a.sh"), "read a b; echo $((a + b))\n")?;
+
+        let (result, _, stderr) = crate::testing::run(
+            dir.path(),
+            &[
+                "stress-test",
+                "--generator",
+                "echo \"$1\" 1",
+                "--brute",
+                "read a b; echo $((a + b))",
+                "--iterations",
+                "5",
+                "a",
+            ],
+        );
+
+        result?;
+        assert!(stderr.contains("No counterexamples found in 5 iterations"));
+        assert!(!dir.path().join(".snowchains").exists());
+        Ok(())
+    }
+}
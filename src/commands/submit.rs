@@ -1,15 +1,18 @@
 use crate::config;
 use eyre::{bail, ContextCompat as _};
 use human_size::Size;
-use snowchains_core::web::{
-    Atcoder, AtcoderSubmitCredentials, Codeforces, CodeforcesSubmitCredentials, CookieStorage,
-    PlatformKind, ProblemInContest, Submit, Yukicoder, YukicoderSubmitCredentials,
-    YukicoderSubmitTarget,
+use snowchains_core::{
+    color_spec,
+    web::{
+        AlreadyAccepted, Atcoder, AtcoderSubmitCredentials, Codeforces,
+        CodeforcesSubmitCredentials, PlatformKind, ProblemInContest, Submit, Yukicoder,
+        YukicoderSubmitCredentials, YukicoderSubmitTarget,
+    },
 };
 use std::{cell::RefCell, env, io::BufRead, iter, path::PathBuf};
 use structopt::StructOpt;
 use strum::VariantNames as _;
-use termcolor::WriteColor;
+use termcolor::{Color, WriteColor};
 
 #[derive(StructOpt, Debug)]
 pub struct OptSubmit {
@@ -17,6 +20,10 @@ pub struct OptSubmit {
     #[structopt(long)]
     pub no_watch: bool,
 
+    /// Gives up watching the submission after the duration (e.g. "10m")
+    #[structopt(long, value_name("DURATION"), conflicts_with("no-watch"))]
+    pub watch_timeout: Option<humantime::Duration>,
+
     /// Do not `compile` the code
     #[structopt(long)]
     pub no_judge: bool,
@@ -29,6 +36,18 @@ pub struct OptSubmit {
     #[structopt(long)]
     pub json: bool,
 
+    /// Opens the submission in a browser. `$SNOWCHAINS_BROWSER` is used if set
+    #[structopt(long)]
+    pub open: bool,
+
+    /// Submits even if the problem has already been accepted
+    #[structopt(long)]
+    pub force: bool,
+
+    /// Prints what would be submitted instead of submitting it
+    #[structopt(long, conflicts_with_all(&["json", "open"]))]
+    pub dry_run: bool,
+
     /// Test for only the test cases
     #[structopt(long, value_name("NAME"))]
     pub testcases: Option<Vec<String>>,
@@ -76,9 +95,13 @@ pub(crate) fn run(
 ) -> eyre::Result<()> {
     let OptSubmit {
         no_watch,
+        watch_timeout,
         no_judge,
         debug,
         json,
+        open,
+        force,
+        dry_run,
         testcases,
         display_limit,
         config,
@@ -104,6 +127,11 @@ pub(crate) fn run(
             compile: _,
             run: _,
             languageId: language_id,
+            template: _,
+            runner: _,
+            timelimitMultiplier: _,
+            submitTransform: submit_transform,
+            format,
         },
         base_dir,
     ) = config::target_and_language(
@@ -118,9 +146,23 @@ pub(crate) fn run(
         } else {
             config::Mode::Release
         },
+        &mut shell.stderr,
     )?;
 
-    let code = crate::fs::read_to_string(base_dir.join(&src))?;
+    if let Some(format) = &format {
+        crate::judge::format(
+            &mut shell.stderr,
+            &base_dir,
+            format,
+            (
+                shell.stdin_process_redirection,
+                shell.stdout_process_redirection,
+                shell.stderr_process_redirection,
+            ),
+        )?;
+    }
+
+    let mut code = crate::fs::read_to_string(base_dir.join(&src))?;
     let language_id = language_id.with_context(|| "Missing `languageId`")?;
 
     if no_judge {
@@ -144,11 +186,11 @@ pub(crate) fn run(
             } else {
                 vec![]
             })
-            .args(&["--display-limit", &display_limit.to_string()])
+            .args(["--display-limit", &display_limit.to_string()])
             .arg("--config")
             .arg(base_dir.join("snowchains.dhall"))
-            .args(&["--color", &color.to_string()])
-            .args(&["-s", service.to_kebab_case_str()])
+            .args(["--color", &color.to_string()])
+            .args(["-s", service.to_kebab_case_str()])
             .args(if let Some(contest) = &contest {
                 vec!["-c".to_owned(), contest.clone()]
             } else {
@@ -167,11 +209,46 @@ pub(crate) fn run(
         }
     }
 
+    if let Some(submit_transform) = submit_transform {
+        let (transformed, stderr) =
+            crate::judge::submit_transform(submit_transform, &code, base_dir.clone())?;
+        if !stderr.is_empty() {
+            write!(shell.stderr, "{}", stderr)?;
+            shell.stderr.flush()?;
+        }
+        code = transformed;
+    }
+
+    if dry_run {
+        let target = match service {
+            PlatformKind::Atcoder => format!(
+                "https://atcoder.jp/contests/{}/submit",
+                contest.with_context(|| "`contest` is required for AtCoder")?,
+            ),
+            PlatformKind::Codeforces => format!(
+                "https://codeforces.com/contest/{}/submit",
+                contest.with_context(|| "`contest` is required for Codeforces")?,
+            ),
+            PlatformKind::Yukicoder => match &contest {
+                Some(contest) => format!(
+                    "https://yukicoder.me/contests/{} (problem {:?})",
+                    contest, problem,
+                ),
+                None => format!("https://yukicoder.me/problems/no/{}", problem),
+            },
+        };
+        return print_dry_run(&mut shell.stderr, &target, &language_id, &code);
+    }
+
     let watch_submission = !no_watch;
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
 
-    let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let timeout = Some(config::timeouts(&cwd, config.as_deref())?.submit);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
+    let watch_timeout = watch_timeout.map(Into::into);
 
     let outcome = match service {
         PlatformKind::Atcoder => {
@@ -196,6 +273,11 @@ pub(crate) fn run(
                 watch_submission,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
+                watch_timeout,
+                refuse_if_accepted: !force,
                 shell: &shell,
             })
         }
@@ -225,6 +307,11 @@ pub(crate) fn run(
                 watch_submission,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
+                watch_timeout,
+                refuse_if_accepted: !force,
                 shell: &shell,
             })
         }
@@ -249,15 +336,77 @@ pub(crate) fn run(
                 watch_submission,
                 cookie_storage: (),
                 timeout,
+                retry,
+                min_interval,
+                proxy,
+                watch_timeout,
+                refuse_if_accepted: !force,
                 shell,
             })
         }
-    }?;
+    }
+    .map_err(|err| {
+        if err.is::<AlreadyAccepted>() {
+            err.wrap_err(r#"Add "--force" to submit"#)
+        } else {
+            err
+        }
+    })?;
 
     if json {
         write!(shell.stdout, "{}", outcome.to_json())?;
         shell.stdout.flush()?;
     }
 
+    if open {
+        crate::web::open_in_browser(&outcome.submission_url, &mut shell.stderr)?;
+    }
+
     Ok(())
 }
+
+fn print_dry_run(
+    mut stderr: impl WriteColor,
+    target: &str,
+    language_id: &str,
+    code: &str,
+) -> eyre::Result<()> {
+    const HEAD_AND_TAIL: usize = 5;
+
+    stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+    write!(stderr, "Dry run.")?;
+    stderr.reset()?;
+    writeln!(stderr, " Nothing is submitted.")?;
+
+    writeln!(stderr, "Target:      {}", target)?;
+    writeln!(stderr, "Language ID: {}", language_id)?;
+    writeln!(stderr, "Size:        {} bytes", code.len())?;
+    writeln!(stderr, "Code:")?;
+
+    let lines = code.lines().collect::<Vec<_>>();
+    let width = lines.len().to_string().len().max(3);
+
+    for (i, line) in lines.iter().enumerate() {
+        if lines.len() > 2 * HEAD_AND_TAIL && i == HEAD_AND_TAIL {
+            stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+            write!(stderr, "{:>width$} |", "...", width = width)?;
+            stderr.reset()?;
+            writeln!(
+                stderr,
+                " ({} lines omitted)",
+                lines.len() - 2 * HEAD_AND_TAIL,
+            )?;
+        }
+        if lines.len() > 2 * HEAD_AND_TAIL
+            && (HEAD_AND_TAIL..lines.len() - HEAD_AND_TAIL).contains(&i)
+        {
+            continue;
+        }
+        stderr.set_color(color_spec!(Fg(Color::Cyan)))?;
+        write!(stderr, "{:>width$} |", i + 1, width = width)?;
+        stderr.reset()?;
+        writeln!(stderr, " {}", line)?;
+    }
+
+    stderr.flush().map_err(Into::into)
+}
@@ -1,7 +1,7 @@
 use eyre::ContextCompat as _;
 use snowchains_core::web::{
-    Atcoder, AtcoderWatchSubmissionsCredentials, AtcoderWatchSubmissionsTarget, CookieStorage,
-    PlatformKind, WatchSubmissions,
+    Atcoder, AtcoderWatchSubmissionsCredentials, AtcoderWatchSubmissionsTarget, PlatformKind,
+    WatchSubmissions,
 };
 use std::{cell::RefCell, io::BufRead, path::PathBuf};
 use structopt::StructOpt;
@@ -29,6 +29,10 @@ pub struct OptWatchSubmissions {
     /// Contest ID
     #[structopt(short, long, value_name("STRING"))]
     pub contest: Option<String>,
+
+    /// Gives up watching after the duration (e.g. "10m")
+    #[structopt(long, value_name("DURATION"))]
+    pub watch_timeout: Option<humantime::Duration>,
 }
 
 pub(crate) fn run(
@@ -40,6 +44,7 @@ pub(crate) fn run(
         color: _,
         service,
         contest,
+        watch_timeout,
     } = opt;
 
     let crate::Context { cwd, mut shell } = ctx;
@@ -54,8 +59,11 @@ pub(crate) fn run(
         })??;
     let contest = contest.or(detected_target.contest);
 
-    let cookie_storage = CookieStorage::with_jsonl(crate::web::credentials::cookie_store_path()?)?;
+    let cookie_storage = crate::web::credentials::cookie_storage(&mut shell)?;
     let timeout = Some(crate::web::SESSION_TIMEOUT);
+    let retry = crate::config::retry(&cwd, config.as_deref())?;
+    let min_interval = crate::config::min_interval(&cwd, config.as_deref())?;
+    let proxy = crate::config::proxy(&cwd, config.as_deref())?;
 
     match service {
         PlatformKind::Atcoder => {
@@ -76,6 +84,10 @@ pub(crate) fn run(
                 credentials,
                 cookie_storage,
                 timeout,
+                retry,
+                min_interval,
+                proxy,
+                watch_timeout: watch_timeout.map(Into::into),
                 shell: &shell,
             })
         }
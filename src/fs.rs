@@ -1,6 +1,9 @@
 use eyre::Context as _;
 use serde::{de::DeserializeOwned, Serialize};
-use std::{fs::Metadata, path::Path};
+use std::{
+    fs::{File, Metadata},
+    path::Path,
+};
 
 pub(crate) fn metadata(path: impl AsRef<Path>) -> eyre::Result<Metadata> {
     let path = path.as_ref();
@@ -8,6 +11,16 @@ pub(crate) fn metadata(path: impl AsRef<Path>) -> eyre::Result<Metadata> {
         .with_context(|| format!("Could not get the metadata of `{}`", path.display()))
 }
 
+pub(crate) fn create(path: impl AsRef<Path>) -> eyre::Result<File> {
+    let path = path.as_ref();
+    File::create(path).with_context(|| format!("Could not open `{}`", path.display()))
+}
+
+pub(crate) fn read(path: impl AsRef<Path>) -> eyre::Result<Vec<u8>> {
+    let path = path.as_ref();
+    std::fs::read(path).with_context(|| format!("Could not read `{}`", path.display()))
+}
+
 pub(crate) fn read_to_string(path: impl AsRef<Path>) -> eyre::Result<String> {
     let path = path.as_ref();
     std::fs::read_to_string(path).with_context(|| format!("Could not read `{}`", path.display()))
@@ -42,6 +55,13 @@ pub(crate) fn write(
         .with_context(|| format!("Could not write `{}`", path.as_ref().display()))
 }
 
+pub(crate) fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> eyre::Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    std::fs::copy(from, to)
+        .with_context(|| format!("Could not copy `{}` to `{}`", from.display(), to.display()))?;
+    Ok(())
+}
+
 pub(crate) fn write_json(
     path: impl AsRef<Path>,
     value: impl Serialize,
@@ -54,3 +74,8 @@ pub(crate) fn create_dir_all(path: impl AsRef<Path>) -> eyre::Result<()> {
     std::fs::create_dir_all(&path)
         .with_context(|| format!("Could not create `{}`", path.as_ref().display()))
 }
+
+pub(crate) fn remove_file(path: impl AsRef<Path>) -> eyre::Result<()> {
+    std::fs::remove_file(&path)
+        .with_context(|| format!("Could not remove `{}`", path.as_ref().display()))
+}
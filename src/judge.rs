@@ -1,22 +1,39 @@
 use crate::config;
 use az::SaturatingAs as _;
-use eyre::bail;
+use eyre::{bail, WrapErr as _};
+use globset::{GlobBuilder, GlobMatcher};
 use human_size::{Byte, Size};
 use indicatif::ProgressDrawTarget;
 use itertools::Itertools as _;
 use maplit::btreemap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use snowchains_core::{
-    color_spec, judge::CommandExpression, testsuite::TestSuite, web::PlatformKind,
+    color_spec,
+    judge::{
+        BuildKind, CommandExpression, DiffStyle, Jobs, JudgeEvent, JudgeEventWriter, JudgeOptions,
+        JudgeOutcome, Palette, PrettyOptions, Verdict,
+    },
+    testsuite::{
+        BatchTestCase, BatchTestSuite, DeterministicExpectedOutput, ExpectedOutput,
+        InteractiveTestSuite, Match, MatchOverride, PartialBatchTestCase, TestSuite, TextEncoding,
+    },
+    web::PlatformKind,
 };
 use std::{
-    collections::HashSet,
-    ffi::OsStr,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    env,
+    ffi::{OsStr, OsString},
+    io,
     io::Write as _,
     iter, mem,
-    ops::Deref,
     path::{Path, PathBuf},
     process::Stdio,
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
 };
+use tempfile::NamedTempFile;
 use termcolor::{Color, WriteColor};
 
 pub(crate) struct Args<W1, W2> {
@@ -31,16 +48,43 @@ pub(crate) struct Args<W1, W2> {
     pub(crate) contest: Option<String>,
     pub(crate) problem: String,
     pub(crate) src: String,
+    pub(crate) format: Option<config::Format>,
     pub(crate) transpile: Option<config::Compile>,
     pub(crate) compile: Option<config::Compile>,
+    pub(crate) force_compile: bool,
     pub(crate) run: config::Command,
+    pub(crate) runner: Vec<String>,
+    pub(crate) timelimit_multiplier: Option<f64>,
     pub(crate) test_case_names: Option<HashSet<String>>,
+    pub(crate) only_failed: bool,
+    /// Replaces the test cases in the test suite.
+    pub(crate) ad_hoc_case: Option<PartialBatchTestCase>,
+    pub(crate) match_override: MatchOverride,
+    pub(crate) preset: Option<String>,
+    pub(crate) match_presets: Box<dyn FnOnce() -> eyre::Result<BTreeMap<String, Match>>>,
     pub(crate) display_limit: Size,
+    pub(crate) stderr_display_limit: Option<Size>,
+    pub(crate) explain: bool,
+    pub(crate) diff: DiffStyle,
+    pub(crate) track_stdin: bool,
+    pub(crate) max_threads: Option<usize>,
+    pub(crate) measure_memory: bool,
+    pub(crate) time_only: bool,
+    pub(crate) no_timelimit: bool,
+    pub(crate) verbose: bool,
+    pub(crate) list_cases: bool,
+    pub(crate) on_failure: Option<String>,
+    pub(crate) palette: Palette,
+    pub(crate) jobs: Jobs,
+    /// `-` for stdout.
+    pub(crate) json: Option<PathBuf>,
+    pub(crate) events: Option<JudgeEventWriter>,
+    pub(crate) print_report_to_stderr: bool,
 }
 
 pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> eyre::Result<()> {
     let Args {
-        stdout,
+        mut stdout,
         mut stderr,
         stdin_process_redirection,
         stdout_process_redirection,
@@ -51,29 +95,196 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> eyre::Resul
         contest,
         problem,
         src,
+        format,
         transpile,
         compile,
+        force_compile,
         run,
+        runner,
+        timelimit_multiplier,
         test_case_names,
+        only_failed,
+        ad_hoc_case,
+        match_override,
+        preset,
+        match_presets,
         display_limit,
+        stderr_display_limit,
+        explain,
+        diff,
+        track_stdin,
+        max_threads,
+        measure_memory,
+        time_only,
+        no_timelimit,
+        verbose,
+        list_cases,
+        on_failure,
+        palette,
+        jobs,
+        json,
+        events,
+        print_report_to_stderr,
     } = args;
 
+    let is_ad_hoc = ad_hoc_case.is_some();
+
     let test_suite_dir = base_dir
         .join(".snowchains")
         .join("tests")
         .join(service.to_kebab_case_str())
         .join(contest.as_deref().unwrap_or(""));
-    let test_suite_path = test_suite_dir.join(problem).with_extension("yml");
+    let test_suite_path = test_suite_dir.join(&problem).with_extension("yml");
+
+    let failed_path = base_dir
+        .join(".snowchains")
+        .join("failed")
+        .join(service.to_kebab_case_str())
+        .join(contest.as_deref().unwrap_or(""))
+        .join(&problem)
+        .with_extension("json");
+
+    // The test suite only looks up exact names.
+    let test_case_patterns = match &test_case_names {
+        Some(names) if names.iter().any(|n| n.contains(['*', '?', '[', '{'])) => Some(
+            names
+                .iter()
+                .map(|name| {
+                    let matcher = GlobBuilder::new(name)
+                        .literal_separator(false)
+                        .build()?
+                        .compile_matcher();
+                    Ok((name.clone(), matcher))
+                })
+                .collect::<eyre::Result<Vec<_>>>()?,
+        ),
+        _ => None,
+    };
+    let test_case_names = test_case_names.filter(|_| test_case_patterns.is_none());
+    let is_filtered = test_case_names.is_some() || test_case_patterns.is_some();
+
+    let test_suite = if is_ad_hoc && !test_suite_path.exists() {
+        TestSuite::Batch(BatchTestSuite {
+            timelimit: None,
+            memorylimit: None,
+            r#match: Match::Lines,
+            whitespace: Default::default(),
+            encoding: None,
+            cases: vec![],
+            extend: vec![],
+        })
+    } else {
+        crate::fs::read_yaml(&test_suite_path)?
+    };
 
-    let test_cases = match crate::fs::read_yaml(&test_suite_path)? {
-        TestSuite::Batch(test_sutie) => {
-            test_sutie.load_test_cases(&test_suite_dir, test_case_names, |_| {
+    let mut test_cases = match test_suite {
+        TestSuite::Batch(mut test_suite) => {
+            if let Some(case) = ad_hoc_case {
+                test_suite.cases = vec![case];
+                test_suite.extend = vec![];
+            }
+            if let Some(preset) = preset {
+                test_suite.override_match(Match::Preset(preset));
+            }
+            if test_suite.presets().next().is_some() {
+                test_suite.resolve_presets(&match_presets()?)?;
+            }
+            test_suite.load_test_cases(&test_suite_dir, test_case_names, |_| {
                 unimplemented!("`SystemTestCases` is not impelemented");
             })?
         }
+        TestSuite::Interactive(InteractiveTestSuite { timelimit }) if list_cases => {
+            write!(stdout, "`Interactive` test suite (timelimit: ")?;
+            match timelimit {
+                Some(timelimit) => write!(stdout, "{:?}", timelimit)?,
+                None => write!(stdout, "-")?,
+            }
+            writeln!(stdout, "). The tester is not configurable yet")?;
+            stdout.flush()?;
+            return Ok(());
+        }
+        _ if is_ad_hoc => bail!("`--in` requires a `Batch` test suite"),
         _ => todo!("currently only `Batch` is supported"),
     };
 
+    if let Some(patterns) = &test_case_patterns {
+        let is_match = |case: &BatchTestCase, matcher: &GlobMatcher| {
+            case.name
+                .as_deref()
+                .is_some_and(|name| matcher.is_match(name))
+        };
+
+        let unmatched = patterns
+            .iter()
+            .filter(|(_, m)| !test_cases.iter().any(|c| is_match(c, m)))
+            .map(|(name, _)| name)
+            .collect::<BTreeSet<_>>();
+        if !unmatched.is_empty() {
+            bail!("No such test cases: {:?}", unmatched);
+        }
+
+        test_cases.retain(|c| patterns.iter().any(|(_, m)| is_match(c, m)));
+    }
+
+    if only_failed && failed_path.exists() {
+        let FailedTestCases { names } = crate::fs::read_json(&failed_path)?;
+        // Unnamed test cases cannot be recorded.
+        let failed = test_cases
+            .iter()
+            .filter(|c| !matches!(&c.name, Some(n) if !names.contains(n)))
+            .cloned()
+            .collect::<Vec<_>>();
+        // The recorded test cases may have been renamed or removed.
+        if !failed.is_empty() {
+            test_cases = failed;
+        }
+    }
+
+    if time_only {
+        for test_case in &mut test_cases {
+            test_case.output = ExpectedOutput::Deterministic(DeterministicExpectedOutput::Pass);
+        }
+    } else if match_override != MatchOverride::default() {
+        for test_case in &mut test_cases {
+            test_case.override_match(&match_override);
+        }
+    }
+
+    let multiplied_timelimits = timelimit_multiplier
+        .map(|multiplier| multiply_timelimits(&mut test_cases, multiplier))
+        .transpose()?;
+
+    if list_cases {
+        if let Some(multiplied_timelimits) = multiplied_timelimits {
+            stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+            write!(stderr, "Timelimit:")?;
+            stderr.reset()?;
+            writeln!(stderr, " {}", multiplied_timelimits)?;
+            stderr.flush()?;
+        }
+        snowchains_core::judge::print_test_cases(&mut stdout, &test_cases)?;
+        return Ok(());
+    }
+
+    if verbose && test_cases.len() != 1 {
+        bail!(
+            "`--verbose` requires exactly one test case, but {} were selected. Select one with \
+             `--testcases`",
+            test_cases.len(),
+        );
+    }
+
+    if cfg!(not(target_os = "linux")) && test_cases.iter().any(|c| c.memorylimit.is_some()) {
+        stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+        write!(stderr, "warning:")?;
+        stderr.reset()?;
+        writeln!(
+            stderr,
+            " `memorylimit` is ignored since memory usage cannot be measured on this platform",
+        )?;
+        stderr.flush()?;
+    }
+
     let redirections = (
         stdin_process_redirection,
         stdout_process_redirection,
@@ -82,13 +293,30 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> eyre::Resul
 
     let mut newline = false;
 
-    for (action, msg) in &[(transpile, "Transpiling..."), (compile, "Compiling...")] {
+    if let Some(format) = &format {
+        self::format(&mut stderr, &base_dir, format, redirections)?;
+        newline = true;
+    }
+
+    for (action, kind) in &[
+        (transpile, BuildKind::Transpile),
+        (compile, BuildKind::Compile),
+    ] {
         if let Some(action) = action {
             if mem::replace(&mut newline, true) {
                 writeln!(stderr)?;
             }
 
-            build(&mut stderr, &base_dir, &src, action, redirections, msg)?;
+            build(
+                &mut stderr,
+                &base_dir,
+                &src,
+                action,
+                redirections,
+                *kind,
+                force_compile,
+                events.as_ref(),
+            )?;
         }
     }
 
@@ -101,10 +329,183 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> eyre::Resul
     writeln!(stderr)?;
     stderr.flush()?;
 
-    let (cmd, tempfile) = match run {
+    let (cmd, tempfile) = command_expression(run, base_dir, &runner)?;
+
+    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+    write!(stderr, "Test file:")?;
+    stderr.reset()?;
+    writeln!(stderr, " {}", test_suite_path.display())?;
+
+    if no_timelimit {
+        stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+        write!(stderr, "Timelimit:")?;
+        stderr.reset()?;
+        writeln!(stderr, " no limit (`--no-timelimit`)")?;
+    } else if let Some(multiplied_timelimits) = multiplied_timelimits {
+        stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+        write!(stderr, "Timelimit:")?;
+        stderr.reset()?;
+        writeln!(stderr, " {}", multiplied_timelimits)?;
+    }
+
+    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+    write!(stderr, "Command:")?;
+    stderr.reset()?;
+    writeln!(stderr, " {}", shell_escape_args(&cmd.program, &cmd.args))?;
+
+    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+    write!(stderr, "Working Directory:")?;
+    stderr.reset()?;
+    writeln!(stderr, " {}", cmd.cwd.display())?;
+
+    stderr.flush()?;
+
+    if verbose {
+        writeln!(stderr)?;
+        stderr.flush()?;
+    }
+
+    let outcome = snowchains_core::judge::judge(
+        // The spinner would be broken by the outputs.
+        if verbose {
+            ProgressDrawTarget::hidden()
+        } else {
+            progress_draw_target
+        },
+        tokio::signal::ctrl_c,
+        &cmd,
+        &test_cases,
+        &JudgeOptions {
+            events: events.clone(),
+            track_stdin,
+            max_threads,
+            measure_memory,
+            palette,
+            jobs,
+            live_output: verbose.then(|| Arc::new(Mutex::new(io::stderr())) as _),
+            no_timelimit,
+        },
+    )?;
+
+    if let Some(tempfile) = tempfile {
+        tempfile.close()?;
+    }
+
+    writeln!(stderr)?;
+    stderr.flush()?;
+
+    let pretty_options = PrettyOptions {
+        display_limit: Some(display_limit.into::<Byte>().value().saturating_as()),
+        stderr_display_limit: Some(
+            stderr_display_limit
+                .unwrap_or(display_limit)
+                .into::<Byte>()
+                .value()
+                .saturating_as(),
+        ),
+        explain,
+        palette,
+        diff,
+    };
+    if time_only {
+        if print_report_to_stderr {
+            outcome.print_timings(&mut stderr, &palette)?;
+        } else {
+            outcome.print_timings(&mut stdout, &palette)?;
+        }
+    } else if print_report_to_stderr {
+        outcome.print_pretty(&mut stderr, &pretty_options)?;
+    } else {
+        outcome.print_pretty(&mut stdout, &pretty_options)?;
+    }
+
+    match json {
+        Some(path) if path.as_os_str() == "-" => {
+            writeln!(stdout, "{}", outcome.to_json())?;
+            stdout.flush()?;
+        }
+        Some(path) => crate::fs::write_json(path, outcome.json_report(), true)?,
+        None => {}
+    }
+
+    // The ad-hoc test case is not a part of the test suite.
+    if !(time_only || is_ad_hoc) {
+        let previous = if is_filtered && failed_path.exists() {
+            crate::fs::read_json(&failed_path)?
+        } else {
+            FailedTestCases::default()
+        };
+
+        let FailedTestCases { names } = previous.update(
+            outcome.verdicts.iter().flat_map(|v| {
+                let accepted = matches!(v, Verdict::Accepted { .. });
+                Some((v.test_case_name()?, accepted))
+            }),
+            !is_filtered,
+        );
+
+        if !names.is_empty() {
+            crate::fs::write_json(&failed_path, FailedTestCases { names }, true)?;
+        } else if failed_path.exists() {
+            crate::fs::remove_file(&failed_path)?;
+        }
+    }
+
+    if let Some(on_failure) = on_failure {
+        run_on_failure(
+            &on_failure,
+            &outcome,
+            &cmd.cwd,
+            stdin_process_redirection,
+            stdout_process_redirection,
+            stderr_process_redirection,
+            stderr,
+        )?;
+    }
+
+    outcome.error_on_fail()
+}
+
+/// Multiplies the timelimits of the test cases, returning how they are changed.
+///
+/// The test cases without timelimits are left as they are.
+pub(crate) fn multiply_timelimits(
+    test_cases: &mut [BatchTestCase],
+    multiplier: f64,
+) -> eyre::Result<String> {
+    if !(multiplier.is_finite() && multiplier > 0.0) {
+        bail!("`timelimitMultiplier` must be positive: {}", multiplier);
+    }
+
+    let mut changes = BTreeSet::new();
+    for timelimit in test_cases.iter_mut().flat_map(|c| &mut c.timelimit) {
+        let multiplied = timelimit.mul_f64(multiplier);
+        changes.insert((*timelimit, multiplied));
+        *timelimit = multiplied;
+    }
+
+    Ok(if changes.is_empty() {
+        format!("(none) × {}", multiplier)
+    } else {
+        changes
+            .iter()
+            .map(|(from, to)| format!("{:?} × {} = {:?}", from, multiplier, to))
+            .join(", ")
+    })
+}
+
+/// Converts `run` into a `CommandExpression`, prepending `runner`.
+///
+/// For `Script`s, the returned temporary file has to be kept until the command finishes.
+pub(crate) fn command_expression(
+    run: config::Command,
+    base_dir: PathBuf,
+    runner: &[String],
+) -> eyre::Result<(CommandExpression, Option<NamedTempFile>)> {
+    let (mut cmd, tempfile) = match run {
         config::Command::Args(args) => {
             let cmd = CommandExpression {
-                program: args.get(0).cloned().unwrap_or_default().into(),
+                program: args.first().cloned().unwrap_or_default().into(),
                 args: args.into_iter().skip(1).map(Into::into).collect(),
                 cwd: base_dir,
                 env: btreemap!(),
@@ -135,42 +536,272 @@ pub(crate) fn judge(args: Args<impl WriteColor, impl WriteColor>) -> eyre::Resul
         }
     };
 
-    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
-    write!(stderr, "Test file:")?;
-    stderr.reset()?;
-    writeln!(stderr, " {}", test_suite_path.display())?;
+    if let Some((program, args)) = runner.split_first() {
+        cmd.args = args
+            .iter()
+            .map(Into::into)
+            .chain(iter::once(mem::replace(&mut cmd.program, program.into())))
+            .chain(mem::take(&mut cmd.args))
+            .collect();
+    }
 
-    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
-    write!(stderr, "Command:")?;
-    stderr.reset()?;
-    writeln!(stderr, " {}", shell_escape_args(&cmd.program, &cmd.args))?;
+    Ok((cmd, tempfile))
+}
 
-    stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
-    write!(stderr, "Working Directory:")?;
-    stderr.reset()?;
-    writeln!(stderr, " {}", cmd.cwd.display())?;
+/// Reads the `Batch` test suite at `path` to add test cases to it, or returns an empty one with
+/// `match: Lines` if it does not exist.
+pub(crate) fn read_or_new_batch_suite(path: &Path) -> eyre::Result<BatchTestSuite> {
+    if !path.exists() {
+        return Ok(BatchTestSuite {
+            timelimit: None,
+            memorylimit: None,
+            r#match: Match::Lines,
+            whitespace: Default::default(),
+            encoding: None,
+            cases: vec![],
+            extend: vec![],
+        });
+    }
 
-    stderr.flush()?;
+    match crate::fs::read_yaml(path)? {
+        TestSuite::Batch(test_suite) => Ok(test_suite),
+        _ => bail!("`{}` is not a `Batch` test suite", path.display()),
+    }
+}
 
-    let outcome = snowchains_core::judge::judge(
-        progress_draw_target,
-        tokio::signal::ctrl_c,
-        &cmd,
-        &test_cases,
-    )?;
+/// Writes `test_suite` to `path`. If the file exists, the added test cases are appended to it in
+/// place so that the comments in it are kept.
+pub(crate) fn write_test_suite(
+    mut stderr: impl WriteColor,
+    path: &Path,
+    test_suite: &TestSuite,
+) -> eyre::Result<()> {
+    let original = path
+        .exists()
+        .then(|| crate::fs::read_to_string(path))
+        .transpose()?;
+
+    let yaml = original
+        .as_deref()
+        .and_then(|original| test_suite.append_cases_to_yaml(original));
+
+    let yaml = match (yaml, original) {
+        (Some(yaml), _) => yaml,
+        (None, original) => {
+            if original.is_some_and(|o| o.contains('#')) {
+                crate::shell::warn(
+                    &mut stderr,
+                    format_args!(
+                        "Could not edit `{}` in place. The comments in it are not kept",
+                        path.display(),
+                    ),
+                )?;
+            }
+            test_suite.to_yaml_pretty()
+        }
+    };
+
+    crate::fs::write(path, yaml, true)
+}
+
+/// Runs `cmd` with Bash, giving `seed` as `$1`, and returns the stdout.
+pub(crate) fn bash_output(
+    cmd: &str,
+    seed: Option<u64>,
+    input: &str,
+    cwd: &Path,
+    encoding: TextEncoding,
+) -> eyre::Result<String> {
+    let mut child = std::process::Command::new("bash")
+        .arg("-c")
+        .arg(cmd)
+        .arg("snowchains")
+        .args(seed.map(|s| s.to_string()))
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| "could not run `bash`")?;
+
+    let input = encoding.encode(input)?.into_owned();
+    let mut stdin = child.stdin.take().expect("should be piped");
+    // Writes in another thread so that a command that prints before reading does not block.
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output()?;
+    // The command may exit without reading all of the input.
+    let _ = writer.join().expect("should not panic");
+
+    if !output.status.success() {
+        bail!("`{}` failed ({})", cmd, output.status);
+    }
+
+    match encoding.decode(&output.stdout) {
+        Some(output) => Ok(output),
+        None => bail!(
+            "the output of `{}` was not a valid {} string",
+            cmd,
+            encoding
+        ),
+    }
+}
+
+/// Runs `submitTransform` with `code` as the stdin, and returns the stdout and the stderr.
+pub(crate) fn submit_transform(
+    command: config::Command,
+    code: &str,
+    base_dir: PathBuf,
+) -> eyre::Result<(String, String)> {
+    let (cmd, tempfile) = command_expression(command, base_dir, &[])?;
+
+    let mut child = std::process::Command::new(&cmd.program)
+        .args(&cmd.args)
+        .current_dir(&cmd.cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("could not run {:?}", cmd.program))?;
+
+    let input = code.as_bytes().to_owned();
+    let mut stdin = child.stdin.take().expect("should be piped");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output()?;
+    let _ = writer.join().expect("should not panic");
 
     if let Some(tempfile) = tempfile {
         tempfile.close()?;
     }
 
-    writeln!(stderr)?;
-    stderr.flush()?;
-    outcome.print_pretty(
-        stdout,
-        Some(display_limit.into::<Byte>().value().saturating_as()),
-    )?;
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
 
-    outcome.error_on_fail()
+    if !output.status.success() {
+        bail!(
+            "`submitTransform` failed ({}):\n{}",
+            output.status,
+            stderr.trim_end(),
+        );
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| "the output of `submitTransform` was not a valid UTF-8 string")?;
+    Ok((stdout, stderr))
+}
+
+/// Names of the test cases that failed in the last run, for `--only-failed`.
+#[derive(Default, Debug, PartialEq, Deserialize, Serialize)]
+struct FailedTestCases {
+    names: BTreeSet<String>,
+}
+
+impl FailedTestCases {
+    /// Applies the results of a run, given as pairs of a name and whether it was accepted.
+    ///
+    /// If the run was `complete` (i.e. it was not narrowed with `--testcases`), the record is
+    /// replaced. Otherwise only the test cases that ran are updated.
+    fn update<'a>(
+        self,
+        results: impl IntoIterator<Item = (&'a str, bool)>,
+        complete: bool,
+    ) -> Self {
+        let mut names = if complete {
+            BTreeSet::new()
+        } else {
+            self.names
+        };
+        for (name, accepted) in results {
+            if accepted {
+                names.remove(name);
+            } else {
+                names.insert(name.to_owned());
+            }
+        }
+        Self { names }
+    }
+}
+
+fn run_on_failure(
+    cmd: &str,
+    outcome: &JudgeOutcome,
+    cwd: &Path,
+    stdin_process_redirection: fn() -> Stdio,
+    stdout_process_redirection: fn() -> Stdio,
+    stderr_process_redirection: fn() -> Stdio,
+    mut stderr: impl WriteColor,
+) -> eyre::Result<()> {
+    let failures = outcome
+        .verdicts
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| !matches!(v, Verdict::Accepted { .. }))
+        .collect::<Vec<_>>();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    let tempdir = tempfile::Builder::new()
+        .prefix("snowchains-on-failure-")
+        .tempdir()?;
+
+    for (i, verdict) in failures {
+        let dir = tempdir.path().join((i + 1).to_string());
+
+        let mut env_vars = vec![
+            (
+                "TEST_CASE_NAME",
+                OsString::from(verdict.test_case_name().unwrap_or("")),
+            ),
+            ("VERDICT", verdict.kebab_case_name().into()),
+        ];
+        // Inputs read from files are not held in memory.
+        if let Some((input_file, _)) = verdict.input_file() {
+            let path = dir.join("in.txt");
+            crate::fs::create_dir_all(&dir)?;
+            crate::fs::copy(input_file, &path)?;
+            env_vars.push(("INPUT", path.into()));
+        }
+        let mut write_file = |name: &'static str, file_name, content: &str| -> eyre::Result<_> {
+            let path = dir.join(file_name);
+            crate::fs::write(&path, content, true)?;
+            env_vars.push((name, path.into()));
+            Ok(())
+        };
+        if verdict.input_file().is_none() {
+            write_file("INPUT", "in.txt", verdict.stdin())?;
+        }
+        if let Some(expected) = verdict.expected_stdout() {
+            write_file("EXPECTED_OUTPUT", "expected.txt", expected)?;
+        }
+        if let Some(actual) = verdict.stdout() {
+            write_file("ACTUAL_OUTPUT", "actual.txt", actual)?;
+        }
+
+        stderr.set_color(color_spec!(Bold, Fg(Color::Magenta)))?;
+        write!(stderr, "On failure ({}/{}):", i + 1, outcome.verdicts.len())?;
+        stderr.reset()?;
+        writeln!(stderr, " {}", cmd)?;
+        stderr.flush()?;
+
+        let status = std::process::Command::new("bash")
+            .args(["-c", cmd])
+            .envs(env_vars)
+            .current_dir(cwd)
+            .stdin(stdin_process_redirection())
+            .stdout(stdout_process_redirection())
+            .stderr(stderr_process_redirection())
+            .status()
+            .with_context(|| "could not run `bash`")?;
+
+        if !status.success() {
+            bail!("`--on-failure` command failed ({})", status);
+        }
+    }
+
+    tempdir.close()?;
+    Ok(())
 }
 
 pub(crate) fn transpile(
@@ -192,39 +823,120 @@ pub(crate) fn transpile(
             stdout_process_redirection,
             stderr_process_redirection,
         ),
-        "Transpiling...",
+        BuildKind::Transpile,
+        false,
+        None,
     )
 }
 
+/// Runs `format` in `base_dir`. The command is expected to rewrite `src` in place.
+///
+/// Unless `required` is `True`, a failure is reported as a warning.
 #[allow(clippy::type_complexity)]
-fn build(
+pub(crate) fn format(
+    mut stderr: impl WriteColor,
+    base_dir: &Path,
+    format: &config::Format,
+    redirections: (fn() -> Stdio, fn() -> Stdio, fn() -> Stdio),
+) -> eyre::Result<()> {
+    let config::Format { command, required } = format;
+
+    stderr.set_color(color_spec!(Bold))?;
+    write!(stderr, "Formatting...")?;
+    stderr.reset()?;
+    writeln!(stderr)?;
+    stderr.flush()?;
+
+    let (cmd, tempfile) = command_expression(command.clone(), base_dir.to_owned(), &[])?;
+
+    let (stdin_process_redirection, stdout_process_redirection, stderr_process_redirection) =
+        redirections;
+
+    let result = run_command(
+        &cmd.program,
+        &cmd.args,
+        base_dir,
+        stdin_process_redirection(),
+        stdout_process_redirection(),
+        stderr_process_redirection(),
+        &mut stderr,
+    );
+
+    if let Some(tempfile) = tempfile {
+        tempfile.close()?;
+    }
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) if !required => {
+            crate::shell::warn(stderr, format_args!("Formatting failed: {}", err))?;
+            Ok(())
+        }
+        Err(err) => Err(err.wrap_err("Formatting failed")),
+    }
+}
+
+/// Builds `src` unless `output` was built from the same source code with the same command, which
+/// is recorded in `<output>.snowchains-build.json`.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn build(
     mut stderr: impl WriteColor,
     base_dir: &Path,
     src: &str,
     build_action: &config::Compile,
     redirections: (fn() -> Stdio, fn() -> Stdio, fn() -> Stdio),
-    msg: &'static str,
+    kind: BuildKind,
+    force: bool,
+    events: Option<&JudgeEventWriter>,
 ) -> eyre::Result<()> {
-    let src_modified = {
-        let src = Path::new(&src);
-        let src = base_dir.join(src.strip_prefix(".").unwrap_or(src));
-        crate::fs::metadata(src)?.modified()?
-    };
-
     let config::Compile { command, output } = build_action;
 
+    let src = Path::new(&src);
+    let src = base_dir.join(src.strip_prefix(".").unwrap_or(src));
+
     let output = Path::new(&output);
     let output = base_dir.join(output.strip_prefix(".").unwrap_or(output));
 
+    let state_path = {
+        let mut file_name = output.file_name().unwrap_or_default().to_owned();
+        file_name.push(".snowchains-build.json");
+        output.with_file_name(file_name)
+    };
+    // `force` always builds, so the hash is not needed.
+    let hash = (!force)
+        .then(|| build_hash(&src, command, kind, base_dir))
+        .transpose()?;
+
     let (stdin_process_redirection, stdout_process_redirection, stderr_process_redirection) =
         redirections;
 
-    if output.exists() && crate::fs::metadata(&output)?.modified()? > src_modified {
+    let up_to_date = hash.as_ref().is_some_and(|hash| {
+        output.exists()
+            && state_path.exists()
+            && crate::fs::read_json::<BuildState, _>(&state_path).is_ok_and(|s| s.hash == *hash)
+    });
+
+    if up_to_date {
         writeln!(stderr, "{} is up to date.", output.display())?;
         stderr.flush()?;
+
+        if let Some(events) = events {
+            events.emit(&JudgeEvent::CompileDone {
+                kind,
+                up_to_date: true,
+                elapsed_ms: 0.0,
+            })?;
+        }
     } else {
         stderr.set_color(color_spec!(Bold))?;
-        write!(stderr, "{}", msg)?;
+        write!(
+            stderr,
+            "{}",
+            match kind {
+                BuildKind::Transpile => "Transpiling...",
+                BuildKind::Compile => "Compiling...",
+            },
+        )?;
         stderr.reset()?;
         writeln!(stderr)?;
         stderr.flush()?;
@@ -242,16 +954,12 @@ fn build(
             }
         }
 
-        match command {
-            config::Command::Args(args) => run_command(
-                args.get(0).map(Deref::deref).unwrap_or(""),
-                args.iter().skip(1),
-                base_dir,
-                stdin_process_redirection(),
-                stdout_process_redirection(),
-                stderr_process_redirection(),
-                &mut stderr,
-            )?,
+        let (program, args, tempfile): (OsString, Vec<OsString>, _) = match command {
+            config::Command::Args(args) => (
+                args.first().cloned().unwrap_or_default().into(),
+                args.iter().skip(1).map(Into::into).collect(),
+                None,
+            ),
             config::Command::Script(config::Script {
                 program,
                 extension,
@@ -264,24 +972,134 @@ fn build(
 
                 tempfile.write_all(content.as_ref())?;
 
-                run_command(
-                    program,
-                    &[tempfile.path()],
-                    base_dir,
-                    stdin_process_redirection(),
-                    stdout_process_redirection(),
-                    stderr_process_redirection(),
-                    &mut stderr,
-                )?;
-
-                tempfile.close()?;
+                (program.into(), vec![tempfile.path().into()], Some(tempfile))
             }
+        };
+
+        if let Some(events) = events {
+            events.emit(&JudgeEvent::CompileStart {
+                kind,
+                command: shell_join(&program, &args),
+            })?;
+        }
+
+        let started = Instant::now();
+
+        run_command(
+            program,
+            args,
+            base_dir,
+            stdin_process_redirection(),
+            stdout_process_redirection(),
+            stderr_process_redirection(),
+            &mut stderr,
+        )?;
+
+        if let Some(tempfile) = tempfile {
+            tempfile.close()?;
+        }
+
+        if let Some(hash) = hash {
+            crate::fs::write_json(&state_path, BuildState { hash }, false)?;
+        } else if state_path.exists() {
+            // The record may no longer describe `output`.
+            crate::fs::remove_file(&state_path)?;
+        }
+
+        if let Some(events) = events {
+            events.emit(&JudgeEvent::CompileDone {
+                kind,
+                up_to_date: false,
+                elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            })?;
         }
     }
 
     Ok(())
 }
 
+/// The environment variables that change the outputs of the common compilers.
+static BUILD_ENV_VARS: &[&str] = &[
+    "CC",
+    "CXX",
+    "CFLAGS",
+    "CXXFLAGS",
+    "CPPFLAGS",
+    "LDFLAGS",
+    "CPATH",
+    "C_INCLUDE_PATH",
+    "CPLUS_INCLUDE_PATH",
+    "LIBRARY_PATH",
+    "RUSTFLAGS",
+    "GOFLAGS",
+    "JAVA_HOME",
+    "CLASSPATH",
+];
+
+/// What `build` has built `output` from.
+#[derive(Deserialize, Serialize)]
+struct BuildState {
+    hash: String,
+}
+
+/// Hashes the source code, the arguments, the build variables in [`BUILD_ENV_VARS`], and the
+/// compiler. The compiler is identified by its resolved path, size, and modification time, so that
+/// upgrading it also triggers a rebuild.
+fn build_hash(
+    src: &Path,
+    command: &config::Command,
+    kind: BuildKind,
+    base_dir: &Path,
+) -> eyre::Result<String> {
+    let (program, argv) = match command {
+        config::Command::Args(args) => (
+            args.first().map(|s| &**s).unwrap_or_default(),
+            args.iter().map(|s| &**s).collect::<Vec<_>>(),
+        ),
+        config::Command::Script(config::Script {
+            program,
+            extension,
+            content,
+        }) => (&**program, vec![&**program, &**extension, &**content]),
+    };
+
+    let compiler = which::which_in(program, env::var_os("PATH"), base_dir)
+        .ok()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path)
+                .ok()
+                .map(|m| format!("{} {:?}", m.len(), m.modified().ok()))
+                .unwrap_or_default();
+            (path.into_os_string(), metadata)
+        })
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    let mut update = |field: &[u8]| {
+        hasher.update((field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    };
+
+    update(match kind {
+        BuildKind::Transpile => b"transpile",
+        BuildKind::Compile => b"compile",
+    });
+    update(&(argv.len() as u64).to_le_bytes());
+    for arg in argv {
+        update(arg.as_bytes());
+    }
+    for key in BUILD_ENV_VARS {
+        match env::var_os(key) {
+            Some(value) => update(&[b"=", value.as_encoded_bytes()].concat()),
+            None => update(b""),
+        }
+    }
+    update(compiler.0.as_encoded_bytes());
+    update(compiler.1.as_bytes());
+    update(&crate::fs::read(src)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 fn run_command<S1: AsRef<OsStr>, S2: AsRef<OsStr>, I: IntoIterator<Item = S2>, W: WriteColor>(
     program: S1,
     args: I,
@@ -336,11 +1154,179 @@ fn run_command<S1: AsRef<OsStr>, S2: AsRef<OsStr>, I: IntoIterator<Item = S2>, W
 }
 
 fn shell_escape_args(program: impl AsRef<OsStr>, args: &[impl AsRef<OsStr>]) -> String {
-    format!(
-        "`{}`",
-        iter::once(program.as_ref())
-            .chain(args.iter().map(AsRef::as_ref))
-            .map(|s| shell_escape::unix::escape(s.to_string_lossy()))
-            .format(" "),
-    )
+    format!("`{}`", shell_join(program, args))
+}
+
+fn shell_join(program: impl AsRef<OsStr>, args: &[impl AsRef<OsStr>]) -> String {
+    iter::once(program.as_ref())
+        .chain(args.iter().map(AsRef::as_ref))
+        .map(|s| shell_escape::unix::escape(s.to_string_lossy()))
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FailedTestCases;
+    use crate::config;
+    use maplit::btreeset;
+    use snowchains_core::judge::BuildKind;
+    use std::{fs, process::Stdio};
+    use termcolor::NoColor;
+
+    #[test]
+    fn update_failed_test_cases() {
+        let failed = |names: &[&str]| FailedTestCases {
+            names: names.iter().map(|&s| s.to_owned()).collect(),
+        };
+
+        // The full suite.
+        let record =
+            FailedTestCases::default().update(vec![("a", false), ("b", false), ("c", true)], true);
+        assert_eq!(failed(&["a", "b"]), record);
+
+        // `--testcases a`.
+        let record = record.update(vec![("a", true)], false);
+        assert_eq!(failed(&["b"]), record);
+
+        // `--testcases c`.
+        let record = record.update(vec![("c", false)], false);
+        assert_eq!(btreeset!("b".to_owned(), "c".to_owned()), record.names);
+
+        // `--only-failed`.
+        let record = record.update(vec![("b", true), ("c", false)], true);
+        assert_eq!(failed(&["c"]), record);
+
+        let record = record.update(vec![("c", true)], true);
+        assert_eq!(FailedTestCases::default(), record);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn build_is_cached() -> eyre::Result<()> {
+        let tempdir = tempfile::Builder::new()
+            .prefix("snowchains-judge-test-")
+            .tempdir()?;
+
+        fs::write(tempdir.path().join("a.txt"), "1")?;
+
+        // Appends a line to `log` each time it actually runs.
+        let compile = |flag: &str| config::Compile {
+            command: config::Command::Args(vec![
+                "sh".to_owned(),
+                "-c".to_owned(),
+                "cp ./a.txt ./out && echo \"$0\" >> ./log".to_owned(),
+                flag.to_owned(),
+            ]),
+            output: "./out".to_owned(),
+        };
+
+        let build = |compile: &config::Compile, force| -> eyre::Result<String> {
+            let mut stderr = NoColor::new(vec![]);
+            super::build(
+                &mut stderr,
+                tempdir.path(),
+                "./a.txt",
+                compile,
+                (Stdio::null, Stdio::null, Stdio::null),
+                BuildKind::Compile,
+                force,
+                None,
+            )?;
+            Ok(String::from_utf8(stderr.into_inner())?)
+        };
+        let log = || fs::read_to_string(tempdir.path().join("log"));
+
+        build(&compile("-O0"), false)?;
+        assert_eq!("-O0\n", log()?);
+
+        // Hit.
+        assert!(build(&compile("-O0"), false)?.contains("is up to date."));
+        assert_eq!("-O0\n", log()?);
+
+        // Miss on a flag change.
+        build(&compile("-O2"), false)?;
+        assert_eq!("-O0\n-O2\n", log()?);
+
+        // Miss on a source change.
+        fs::write(tempdir.path().join("a.txt"), "2")?;
+        build(&compile("-O2"), false)?;
+        assert_eq!("-O0\n-O2\n-O2\n", log()?);
+        assert_eq!("2", fs::read_to_string(tempdir.path().join("out"))?);
+
+        // `--force-compile`, which does not leave a record for the next build.
+        build(&compile("-O2"), true)?;
+        assert_eq!("-O0\n-O2\n-O2\n-O2\n", log()?);
+        build(&compile("-O2"), false)?;
+        assert_eq!("-O0\n-O2\n-O2\n-O2\n-O2\n", log()?);
+        assert!(build(&compile("-O2"), false)?.contains("is up to date."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_case_patterns() -> eyre::Result<()> {
+        let dir = crate::testing::project()?;
+        let test_suite_dir = dir
+            .path()
+            .join(".snowchains")
+            .join("tests")
+            .join("atcoder")
+            .join("abc");
+        fs::create_dir_all(&test_suite_dir)?;
+        fs::write(
+            test_suite_dir.join("a.yml"),
+            r#"type: Batch
+timelimit: 2s
+match: Lines
+
+cases:
+  - name: sample1
+    in: "1\n"
+    out: "1\n"
+  - name: sample2
+    in: "2\n"
+    out: "2\n"
+  - name: random-1
+    in: "3\n"
+    out: "3\n"
+  - name: random-10
+    in: "4\n"
+    out: "4\n"
+"#,
+        )?;
+
+        let list_cases = |patterns: &[&str]| -> eyre::Result<Vec<String>> {
+            let (result, stdout, _) = crate::testing::run(
+                dir.path(),
+                &[
+                    &["judge", "--list-cases", "--testcases"],
+                    patterns,
+                    &["--", "a"],
+                ]
+                .concat(),
+            );
+            result?;
+            Ok(stdout
+                .lines()
+                .flat_map(|line| line.split('"').nth(1))
+                .map(ToOwned::to_owned)
+                .collect())
+        };
+
+        assert_eq!(["sample1", "sample2"], &*list_cases(&["sample*"])?);
+        assert_eq!(["random-1"], &*list_cases(&["random-?"])?);
+        assert_eq!(
+            ["sample2", "random-1", "random-10"],
+            &*list_cases(&["random-*", "sample2"])?,
+        );
+        assert_eq!(
+            ["sample1", "random-10"],
+            &*list_cases(&["{sample1,random-1[0-9]}"])?,
+        );
+
+        assert_eq!(
+            r#"No such test cases: {"large-*"}"#,
+            list_cases(&["sample*", "large-*"]).unwrap_err().to_string(),
+        );
+        Ok(())
+    }
 }
@@ -3,13 +3,18 @@ mod config;
 mod fs;
 mod judge;
 pub mod shell;
+#[cfg(test)]
+mod testing;
 mod web;
 
 pub use crate::commands::{
-    init::OptInit, judge::OptJudge, login::OptLogin, participate::OptParticipate,
-    retrieve_languages::OptRetrieveLanguages,
+    add_testcase::OptAddTestcase, clean::OptClean, generate_testcases::OptGenerateTestcases,
+    init::OptInit, judge::OptJudge, lint_config::OptLintConfig, lint_suite::OptLintSuite,
+    list_languages::OptListLanguages, login::OptLogin, new::OptNew, participate::OptParticipate,
+    restore::OptRestore, retrieve_languages::OptRetrieveLanguages,
+    retrieve_solved_problems::OptRetrieveSolvedProblems,
     retrieve_submission_summaries::OptRetrieveSubmissionSummaries,
-    retrieve_testcases::OptRetrieveTestcases, submit::OptSubmit,
+    retrieve_testcases::OptRetrieveTestcases, stress_test::OptStressTest, submit::OptSubmit,
     watch_submissions::OptWatchSubmissions, xtask::OptXtask,
 };
 use std::{env, io::BufRead, path::PathBuf};
@@ -22,6 +27,8 @@ use termcolor::WriteColor;
 
 pub const STACK_SIZE: usize = 128 * 1024 * 1024;
 
+// Parsed once, so the size does not matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, global_setting = AppSettings::DeriveDisplayOrder)]
 pub enum Opt {
@@ -29,6 +36,10 @@ pub enum Opt {
     #[structopt(author, visible_alias("i"))]
     Init(OptInit),
 
+    /// Creates a new source file from the template
+    #[structopt(author, visible_alias("n"))]
+    New(OptNew),
+
     /// Logges in to a service
     #[structopt(author, visible_alias("l"))]
     Login(OptLogin),
@@ -52,10 +63,42 @@ pub enum Opt {
     #[structopt(author, visible_aliases(&["j", "test", "t"]))]
     Judge(OptJudge),
 
+    /// Checks a test suite file
+    #[structopt(author)]
+    LintSuite(OptLintSuite),
+
+    /// Checks the config file by evaluating it for every service and mode
+    #[structopt(author)]
+    LintConfig(OptLintConfig),
+
+    /// Lists the languages in the config file
+    #[structopt(author)]
+    ListLanguages(OptListLanguages),
+
+    /// Adds test cases generated by a program to the test suite
+    #[structopt(author)]
+    GenerateTestcases(OptGenerateTestcases),
+
+    /// Adds a test case to the test suite
+    #[structopt(author)]
+    AddTestcase(OptAddTestcase),
+
+    /// Tests code with random inputs against a brute-force solution
+    #[structopt(author)]
+    StressTest(OptStressTest),
+
+    /// Removes the outputs of `transpile` and `compile`
+    #[structopt(author)]
+    Clean(OptClean),
+
     /// Submits code
     #[structopt(author, visible_alias("s"))]
     Submit(OptSubmit),
 
+    /// Downloads the code of your latest submissions to the `src` of the languages
+    #[structopt(author)]
+    Restore(OptRestore),
+
     /// Runs a custom subcommand written in the config file
     #[structopt(author, visible_alias("x"), setting = AppSettings::TrailingVarArg)]
     Xtask(OptXtask),
@@ -74,6 +117,10 @@ pub enum OptRetrieve {
     /// Retrieves submission summaries
     #[structopt(author, visible_alias("ss"))]
     SubmissionSummaries(OptRetrieveSubmissionSummaries),
+
+    /// Retrieves which problems you have solved, from AtCoder Problems
+    #[structopt(author, visible_alias("sp"))]
+    SolvedProblems(OptRetrieveSolvedProblems),
 }
 
 #[derive(StructOpt, Debug)]
@@ -109,6 +156,7 @@ impl Opt {
     pub fn color(&self) -> crate::ColorChoice {
         match *self {
             Self::Init(OptInit { color, .. })
+            | Self::New(OptNew { color, .. })
             | Self::Login(OptLogin { color, .. })
             | Self::Participate(OptParticipate { color, .. })
             | Self::Retrieve(OptRetrieve::Languages(OptRetrieveLanguages { color, .. }))
@@ -117,10 +165,22 @@ impl Opt {
                 color,
                 ..
             }))
+            | Self::Retrieve(OptRetrieve::SolvedProblems(OptRetrieveSolvedProblems {
+                color,
+                ..
+            }))
             | Self::Download(OptRetrieveTestcases { color, .. })
             | Self::Watch(OptWatch::Submissions(OptWatchSubmissions { color, .. }))
             | Self::Judge(OptJudge { color, .. })
-            | Self::Submit(OptSubmit { color, .. }) => color,
+            | Self::LintSuite(OptLintSuite { color, .. })
+            | Self::LintConfig(OptLintConfig { color, .. })
+            | Self::ListLanguages(OptListLanguages { color, .. })
+            | Self::GenerateTestcases(OptGenerateTestcases { color, .. })
+            | Self::AddTestcase(OptAddTestcase { color, .. })
+            | Self::StressTest(OptStressTest { color, .. })
+            | Self::Clean(OptClean { color, .. })
+            | Self::Submit(OptSubmit { color, .. })
+            | Self::Restore(OptRestore { color, .. }) => color,
             Self::Xtask(_) => crate::ColorChoice::Auto,
         }
     }
@@ -139,12 +199,13 @@ pub struct Context<R, W1, W2> {
     pub shell: crate::shell::Shell<R, W1, W2>,
 }
 
-pub fn run<R: BufRead, W1: WriteColor, W2: WriteColor>(
+pub fn run<R: BufRead, W1: WriteColor + Send, W2: WriteColor>(
     opt: Opt,
     ctx: Context<R, W1, W2>,
 ) -> eyre::Result<()> {
     match opt {
         Opt::Init(opt) => commands::init::run(opt, ctx),
+        Opt::New(opt) => commands::new::run(opt, ctx),
         Opt::Login(opt) => commands::login::run(opt, ctx),
         Opt::Participate(opt) => commands::participate::run(opt, ctx),
         Opt::Retrieve(OptRetrieve::Languages(opt)) => commands::retrieve_languages::run(opt, ctx),
@@ -152,10 +213,21 @@ pub fn run<R: BufRead, W1: WriteColor, W2: WriteColor>(
         Opt::Retrieve(OptRetrieve::SubmissionSummaries(opt)) => {
             commands::retrieve_submission_summaries::run(opt, ctx)
         }
+        Opt::Retrieve(OptRetrieve::SolvedProblems(opt)) => {
+            commands::retrieve_solved_problems::run(opt, ctx)
+        }
         Opt::Download(opt) => commands::retrieve_testcases::run(opt, ctx),
         Opt::Watch(OptWatch::Submissions(opt)) => commands::watch_submissions::run(opt, ctx),
         Opt::Judge(opt) => commands::judge::run(opt, ctx),
+        Opt::LintSuite(opt) => commands::lint_suite::run(opt, ctx),
+        Opt::LintConfig(opt) => commands::lint_config::run(opt, ctx),
+        Opt::ListLanguages(opt) => commands::list_languages::run(opt, ctx),
+        Opt::GenerateTestcases(opt) => commands::generate_testcases::run(opt, ctx),
+        Opt::AddTestcase(opt) => commands::add_testcase::run(opt, ctx),
+        Opt::StressTest(opt) => commands::stress_test::run(opt, ctx),
+        Opt::Clean(opt) => commands::clean::run(opt, ctx),
         Opt::Submit(opt) => commands::submit::run(opt, ctx),
+        Opt::Restore(opt) => commands::restore::run(opt, ctx),
         Opt::Xtask(opt) => commands::xtask::run(opt, ctx),
     }
 }
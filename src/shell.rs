@@ -2,7 +2,7 @@ use indicatif::ProgressDrawTarget;
 use snowchains_core::{color_spec, web::StatusCodeColor};
 use std::{
     env, fmt,
-    io::{self, BufRead, Stdin, StdinLock, Write},
+    io::{self, BufRead, Read as _, Stdin, StdinLock, Write},
     process::Stdio,
 };
 use termcolor::{BufferedStandardStream, Color, WriteColor};
@@ -39,15 +39,18 @@ impl<'a> Shell<StdinLock<'a>, BufferedStandardStream, BufferedStandardStream> {
 
 impl<R, W1, W2: WriteColor> Shell<R, W1, W2> {
     pub(crate) fn warn(&mut self, message: impl fmt::Display) -> io::Result<()> {
-        self.stderr
-            .set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
-        write!(self.stderr, "warning:")?;
-        self.stderr.reset()?;
-        writeln!(self.stderr, " {}", message)?;
-        self.stderr.flush()
+        warn(&mut self.stderr, message)
     }
 }
 
+pub(crate) fn warn(mut stderr: impl WriteColor, message: impl fmt::Display) -> io::Result<()> {
+    stderr.set_color(color_spec!(Bold, Fg(Color::Yellow)))?;
+    write!(stderr, "warning:")?;
+    stderr.reset()?;
+    writeln!(stderr, " {}", message)?;
+    stderr.flush()
+}
+
 impl<R: BufRead, W1, W2: Write> Shell<R, W1, W2> {
     pub(crate) fn read_reply(&mut self, prompt: &'static str) -> io::Result<String> {
         write!(self.stderr, "{}", prompt)?;
@@ -60,6 +63,15 @@ impl<R: BufRead, W1, W2: Write> Shell<R, W1, W2> {
         self.stderr.flush()?;
         self.stdin.read_password()
     }
+
+    pub(crate) fn read_stdin_to_string(&mut self) -> io::Result<String> {
+        let mut content = "".to_owned();
+        match &mut self.stdin {
+            TtyOrPiped::Tty => io::stdin().read_to_string(&mut content),
+            TtyOrPiped::Piped(r) => r.read_to_string(&mut content),
+        }?;
+        Ok(content)
+    }
 }
 
 impl<R, W1, W2> Shell<R, W1, W2> {
@@ -0,0 +1,116 @@
+//! Helpers for the tests of the commands.
+
+use std::{fs, io, iter, path::Path, process::Stdio, thread};
+use structopt::StructOpt as _;
+use tempfile::TempDir;
+use termcolor::NoColor;
+
+static SNOWCHAINS_DHALL: &str = r##"let Target =
+      { service : < Atcoder | Codeforces | Yukicoder >
+      , contest :
+          Optional
+            { lowercase : Text
+            , uppercase : Text
+            , snakeCase : Text
+            , kebabCase : Text
+            , mixedCase : Text
+            , pascalCase : Text
+            }
+      , problem :
+          { lowercase : Text
+          , uppercase : Text
+          , snakeCase : Text
+          , kebabCase : Text
+          , mixedCase : Text
+          , pascalCase : Text
+          }
+      , mode : < Debug | Release >
+      }
+
+let Command = < Args : List Text | Script : { program : Text, extension : Text, content : Text } >
+
+let Compile = { command : Command, output : Text }
+
+in  { detectServiceFromRelativePathSegments = \(_ : List Text) -> Some "atcoder"
+    , detectContestFromRelativePathSegments = \(_ : List Text) -> Some "abc"
+    , detectProblemFromRelativePathSegments = \(_ : List Text) -> None Text
+    , detectLanguageFromRelativePathSegments = \(_ : List Text) -> Some "sh"
+    , languages =
+        \(target : Target) ->
+          toMap
+            { sh =
+              { src = "${target.problem.lowercase}.sh"
+              , transpile = None Compile
+              , compile = None Compile
+              , run = Command.Args [ "bash", "${target.problem.lowercase}.sh" ]
+              , languageId = None Text
+              , template =
+                  Some
+                    ( < Path : Text | Content : Text >.Content
+                        "# ${target.problem.uppercase}\n"
+                    )
+              }
+            }
+    , matchPresets = toMap
+        { exact =
+            < Exact
+            | SplitWhitespace
+            | Lines
+            | Float :
+                { relative_error : Optional Double
+                , absolute_error : Optional Double
+                }
+            | Checker : { cmd : Text, shell : < Bash > }
+            | Transform : { cmd : Text, shell : < Bash > }
+            >.Exact
+        }
+    }
+"##;
+
+/// Creates a directory with `snowchains.dhall` for `atcoder`/`abc`, whose only language `sh`
+/// runs `<problem>.sh` with Bash. The only preset is `exact`.
+pub(crate) fn project() -> io::Result<TempDir> {
+    let dir = tempfile::Builder::new()
+        .prefix("snowchains-tests-")
+        .tempdir()?;
+    fs::write(dir.path().join("snowchains.dhall"), SNOWCHAINS_DHALL)?;
+    Ok(dir)
+}
+
+/// Runs `snowchains <args>` in `cwd` with a large stack as `main` does, and returns the result, the
+/// stdout, and the stderr.
+pub(crate) fn run(cwd: &Path, args: &[&str]) -> (eyre::Result<()>, String, String) {
+    let opt = crate::Opt::from_iter_safe(iter::once("snowchains").chain(args.iter().copied()))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let cwd = cwd.to_owned();
+
+    thread::Builder::new()
+        .stack_size(crate::STACK_SIZE)
+        .spawn(move || {
+            let mut stdout = NoColor::new(vec![]);
+            let mut stderr = NoColor::new(vec![]);
+
+            let result = crate::run(
+                opt,
+                crate::Context {
+                    cwd,
+                    shell: crate::shell::Shell {
+                        stdin: crate::shell::TtyOrPiped::Piped(&b""[..]),
+                        stdout: &mut stdout,
+                        stderr: &mut stderr,
+                        stderr_tty: false,
+                        stdin_process_redirection: Stdio::null,
+                        stdout_process_redirection: Stdio::null,
+                        stderr_process_redirection: Stdio::null,
+                    },
+                },
+            );
+
+            let text =
+                |wtr: NoColor<Vec<u8>>| String::from_utf8_lossy(&wtr.into_inner()).into_owned();
+            (result, text(stdout), text(stderr))
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
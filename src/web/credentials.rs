@@ -1,15 +1,46 @@
-use eyre::{Context as _, ContextCompat as _};
+use eyre::{bail, Context as _, ContextCompat as _};
 use serde::{Deserialize, Serialize};
+use snowchains_core::web::{
+    AtcoderDropboxRefresh, AtcoderRetrieveFullTestCasesCredentials, CookieStorage,
+};
 use std::{
     cell::RefCell,
+    env,
     io::{BufRead, Write},
     path::PathBuf,
 };
 
+/// `cookies.txt` (Netscape format) if it exists, otherwise `cookies.jsonl`.
 pub(crate) fn cookie_store_path() -> eyre::Result<PathBuf> {
     let data_local_dir =
         dirs_next::data_local_dir().with_context(|| "Could not find the local date directory")?;
-    Ok(data_local_dir.join("snowchains").join("cookies.jsonl"))
+    let dir = data_local_dir.join("snowchains");
+    let cookies_txt = dir.join("cookies.txt");
+    Ok(if cookies_txt.exists() {
+        cookies_txt
+    } else {
+        dir.join("cookies.jsonl")
+    })
+}
+
+/// Opens the cookie store, encrypted with `$SNOWCHAINS_COOKIE_KEY` if it is set.
+///
+/// If the variable is not set but the existing store is encrypted, asks for the passphrase.
+pub(crate) fn cookie_storage<R: BufRead, W1, W2: Write>(
+    shell: &mut crate::shell::Shell<R, W1, W2>,
+) -> eyre::Result<CookieStorage> {
+    let path = cookie_store_path()?;
+    if let Some(passphrase) = env::var("SNOWCHAINS_COOKIE_KEY")
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        return CookieStorage::open_encrypted(path, &passphrase);
+    }
+    if CookieStorage::is_encrypted(&path)? {
+        let passphrase = shell.read_password("Passphrase for the cookies: ")?;
+        return CookieStorage::open_encrypted(path, &passphrase);
+    }
+    CookieStorage::open(path)
 }
 
 pub(crate) fn atcoder_username_and_password<'a, R: BufRead, W1, W2: Write>(
@@ -36,17 +67,62 @@ pub(crate) fn username_and_password<'a, R: BufRead, W1, W2: Write>(
     }
 }
 
-pub(crate) fn dropbox_access_token() -> eyre::Result<String> {
+/// Reads `dropbox.json`.
+///
+/// It is either `{ "access_token": .. }` or, so that the short-lived access token is renewed
+/// automatically, `{ "refresh_token": .., "app_key": .. }` with optional `access_token` and
+/// `app_secret`. Renewed access tokens are written back to the file.
+pub(crate) fn dropbox_credentials() -> eyre::Result<AtcoderRetrieveFullTestCasesCredentials> {
     let path = token_path("dropbox.json")?;
 
-    let Dropbox { access_token } = crate::fs::read_json(&path)
+    let dropbox = crate::fs::read_json::<Dropbox, _>(&path)
         .with_context(|| format!("First, save the access token to `{}`", path.display()))?;
 
-    return Ok(access_token);
+    let dropbox_refresh = match (&dropbox.refresh_token, &dropbox.app_key) {
+        (Some(refresh_token), Some(app_key)) => Some(AtcoderDropboxRefresh {
+            refresh_token: refresh_token.clone(),
+            app_key: app_key.clone(),
+            app_secret: dropbox.app_secret.clone(),
+            on_refresh: {
+                let (path, mut dropbox) = (path.clone(), dropbox.clone());
+                Box::new(move |access_token| {
+                    dropbox.access_token = Some(access_token.to_owned());
+                    crate::fs::write_json(&path, &dropbox, false)
+                })
+            },
+        }),
+        (None, None) => None,
+        _ => bail!(
+            "`{}`: `refresh_token` and `app_key` must be given together",
+            path.display(),
+        ),
+    };
+
+    let dropbox_access_token = match (dropbox.access_token, &dropbox_refresh) {
+        (Some(access_token), _) => access_token,
+        // The first request fails with 401 and then the access token is issued.
+        (None, Some(_)) => "".to_owned(),
+        (None, None) => bail!(
+            "`{}`: either `access_token` or `refresh_token` is required",
+            path.display(),
+        ),
+    };
 
-    #[derive(Deserialize)]
+    return Ok(AtcoderRetrieveFullTestCasesCredentials {
+        dropbox_access_token,
+        dropbox_refresh,
+    });
+
+    #[derive(Deserialize, Serialize, Clone)]
     struct Dropbox {
-        access_token: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        access_token: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        refresh_token: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app_key: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app_secret: Option<String>,
     }
 }
 
@@ -82,9 +158,14 @@ pub(crate) fn codeforces_api_key_and_secret(
     }
 }
 
+/// Reads the file at `$YUKICODER_API_KEY_FILE` if it is set, otherwise `yukicoder.json`.
 pub(crate) fn yukicoder_api_key(
     shell: &mut crate::shell::Shell<impl BufRead, impl Sized, impl Write>,
 ) -> eyre::Result<String> {
+    if let Some(path) = env::var_os("YUKICODER_API_KEY_FILE").filter(|s| !s.is_empty()) {
+        return Ok(crate::fs::read_to_string(path)?.trim().to_owned());
+    }
+
     let path = token_path("yukicoder.json")?;
 
     if path.exists() {
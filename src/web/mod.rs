@@ -2,10 +2,73 @@ pub(crate) mod credentials;
 
 use heck::{CamelCase as _, KebabCase as _, MixedCase as _, SnakeCase as _};
 use serde::Serialize;
-use std::time::Duration;
+use snowchains_core::web::Retry;
+use std::{env, io, path::Path, process::Stdio, time::Duration};
+use termcolor::WriteColor;
+use url::Url;
 
 pub(crate) const SESSION_TIMEOUT: Duration = Duration::from_secs(30);
 
+pub(crate) const SESSION_RETRY: Retry = Retry {
+    count: 3,
+    backoff: Duration::from_millis(500),
+};
+
+/// Minimum interval between requests to the same host.
+pub(crate) const SESSION_MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Opens `url` with `$SNOWCHAINS_BROWSER` or the default browser. If it fails, prints the URL
+/// instead so that it can be opened manually.
+///
+/// Returns `url` back so that callers can log it.
+pub(crate) fn open_in_browser(url: &Url, mut stderr: impl WriteColor) -> io::Result<Url> {
+    let mut cmd = if let Some(browser) = env::var_os("SNOWCHAINS_BROWSER") {
+        std::process::Command::new(browser)
+    } else if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+
+    let status = cmd
+        .arg(url.as_str())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    let err = match status {
+        Ok(status) if status.success() => {
+            writeln!(stderr, "Opened {}", url)?;
+            stderr.flush()?;
+            return Ok(url.clone());
+        }
+        Ok(status) => status.to_string(),
+        Err(err) => err.to_string(),
+    };
+    crate::shell::warn(
+        stderr,
+        format!("Could not open a browser ({}). Open {} manually", err, url),
+    )?;
+    Ok(url.clone())
+}
+
+/// Reads problems from a newline-delimited file for `--problems-from-file`.
+///
+/// Blank lines and lines starting with `#` are ignored.
+pub(crate) fn read_problems_file(path: &Path) -> eyre::Result<Vec<String>> {
+    Ok(crate::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
 #[derive(Debug, Serialize)]
 pub(crate) struct CaseConversions {
     pub(crate) original: String,